@@ -1,5 +1,5 @@
 use criterion::{criterion_group, criterion_main, Criterion};
-use db::{DBOperator, ObjectField, Row, SortDirection, Storage};
+use db::{DBOperator, JoinKind, ObjectField, Row, SortDirection, Storage};
 use uuid::Uuid;
 use fakeit::{address::country, name};
 
@@ -88,7 +88,8 @@ fn criterion_benchmark(c: &mut Criterion) {
                 .hash_match(
                     table2.table_scan(transaction),
                     |row| row.column(3),
-                    |hashed_row| hashed_row.column(0)
+                    |hashed_row| hashed_row.column(0),
+                    JoinKind::Inner
                 )
                 .in_memory_sort(|row| row.column(4), SortDirection::Descending)
                 .select(|builder, row| {
@@ -1,5 +1,5 @@
 use criterion::{criterion_group, criterion_main, Criterion};
-use db::{DBOperator, ObjectField, Row, Storage};
+use db::{DBOperator, JoinKind, ObjectField, Row, Storage};
 use uuid::Uuid;
 use fakeit::{address::country, name};
 
@@ -84,7 +84,7 @@ fn criterion_benchmark(c: &mut Criterion) {
 
             let rows = table1
                 .table_scan(transaction)
-                .nested_loop(table2.table_scan(transaction), 3, 0)
+                .nested_loop(table2.table_scan(transaction), |a, b| a.column(3) == b.column(0), JoinKind::Inner)
                 .collect()
                 .unwrap();
 
@@ -0,0 +1,102 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use db::{DBOperator, ObjectField, Row, SortDirection, Storage};
+use uuid::Uuid;
+
+use crate::utils::{init_benchmark, wipe_log_files};
+
+mod utils;
+
+fn criterion_benchmark(c: &mut Criterion) {
+    init_benchmark();
+    wipe_log_files();
+    let mut engine = Storage::new().unwrap();
+
+    {
+        pub fn generate_sample_data() -> Vec<Row> {
+            let data: Vec<Row> = (0..10000)
+            .map(|_| {
+                let id = Uuid::new_v4();
+                let fields: Vec<ObjectField> = vec![
+                    ObjectField::I32(rand::random()),
+                    ObjectField::I64(rand::random()),
+                    ObjectField::Id(Uuid::new_v4()),
+                ];
+                Row { id, fields: fields.into() }
+            })
+            .collect();
+            data
+        }
+
+        let mut collection = engine
+            .create_new_collection("table")
+            .unwrap()
+            .write()
+            .unwrap();
+
+        collection.set_objects(Uuid::nil(), generate_sample_data()).unwrap();
+        collection.print_debug_info();
+        collection.clear_cache();
+    }
+
+    c.bench_function("10K table sorted on an integer field - radix sort", |b| {
+        b.iter(|| {
+            let table = engine.get_collection("table")
+                .unwrap()
+                .read()
+                .unwrap();
+
+            let rows = table
+                .table_scan(Uuid::now_v7())
+                .in_memory_sort(|row| row.column(0), SortDirection::Ascending)
+                .collect()
+                .unwrap();
+
+            assert_eq!(rows.len(), 10000);
+            std::mem::drop(rows);
+        });
+    });
+
+    c.bench_function("10K table sorted on an integer field - comparison sort", |b| {
+        b.iter(|| {
+            let table = engine.get_collection("table")
+                .unwrap()
+                .read()
+                .unwrap();
+
+            // A tuple key is not radix sortable, so the rows are sorted by comparisons
+            let rows = table
+                .table_scan(Uuid::now_v7())
+                .in_memory_sort(|row| (row.column(0),), SortDirection::Ascending)
+                .collect()
+                .unwrap();
+
+            assert_eq!(rows.len(), 10000);
+            std::mem::drop(rows);
+        });
+    });
+
+    c.bench_function("10K table sorted on an id field - radix sort", |b| {
+        b.iter(|| {
+            let table = engine.get_collection("table")
+                .unwrap()
+                .read()
+                .unwrap();
+
+            let rows = table
+                .table_scan(Uuid::now_v7())
+                .in_memory_sort(|row| row.column(2), SortDirection::Descending)
+                .collect()
+                .unwrap();
+
+            assert_eq!(rows.len(), 10000);
+            std::mem::drop(rows);
+        });
+    });
+}
+
+criterion_group!{
+    name = collection_radix_sort;
+    config = Criterion::default();
+    targets = criterion_benchmark
+}
+criterion_main!(collection_radix_sort);
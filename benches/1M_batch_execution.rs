@@ -0,0 +1,96 @@
+use std::{time::Duration};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use db::{DBOperator, ObjectField, Storage};
+use uuid::Uuid;
+
+use crate::utils::{generate_sample_data, init_benchmark, wipe_log_files};
+
+mod utils;
+
+/// Counts the rows of the operator, read one by one
+fn count_rows(mut operator: impl DBOperator) -> usize {
+    let mut count = 0;
+    while operator.next().unwrap().is_some() {
+        count += 1;
+    }
+    count
+}
+
+/// Counts the rows of the operator, read in batches
+fn count_batches(mut operator: impl DBOperator) -> usize {
+    let mut count = 0;
+    loop {
+        let batch = operator.next_batch().unwrap();
+        if batch.is_empty() {
+            return count;
+        }
+        count += batch.len();
+    }
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    init_benchmark();
+    wipe_log_files();
+    let mut engine = Storage::new().unwrap();
+
+    {
+        let mut collection = engine
+            .create_new_collection("table")
+            .unwrap()
+            .write()
+            .unwrap();
+
+        // Setup
+        let data = generate_sample_data(1_000_000);
+        collection.set_objects(Uuid::nil(), data).unwrap();
+        collection.print_debug_info();
+    }
+
+    let collection = engine
+        .get_collection("table")
+        .unwrap()
+        .read()
+        .unwrap();
+    let transaction = Uuid::now_v7();
+    let expected = count_rows(collection
+        .table_scan(transaction)
+        .filter(|row| row.column(3) == ObjectField::Bool(true)));
+
+    c.bench_function("1M filter and select by rows", |b| {
+        b.iter(|| {
+            let operator = collection
+                .table_scan(transaction)
+                .filter(|row| row.column(3) == ObjectField::Bool(true))
+                .select(|builder, _| builder.column(1).column(2));
+            assert_eq!(count_rows(operator), expected);
+        });
+    });
+
+    c.bench_function("1M filter and select by batches", |b| {
+        b.iter(|| {
+            let operator = collection
+                .table_scan(transaction)
+                .filter(|row| row.column(3) == ObjectField::Bool(true))
+                .select(|builder, _| builder.column(1).column(2));
+            assert_eq!(count_batches(operator), expected);
+        });
+    });
+
+    c.bench_function("1M filter and select by column batches", |b| {
+        b.iter(|| {
+            let operator = collection
+                .table_scan(transaction)
+                .filter_column(3, |value| *value == ObjectField::Bool(true))
+                .select_columns(&[1, 2]);
+            assert_eq!(count_batches(operator), expected);
+        });
+    });
+}
+
+criterion_group!{
+    name = big_batch_execution;
+    config = Criterion::default().measurement_time(Duration::from_secs(10)).sample_size(15);
+    targets = criterion_benchmark
+}
+criterion_main!(big_batch_execution);
@@ -4,8 +4,16 @@ use itertools::Itertools;
 use log::debug;
 use log_err::LogErrOption;
 
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{from_str, to_string};
+
 use crate::{storage::{storage_config::DatabaseConfig}, utils::DBResult};
 
+use super::{collection_statistics::CollectionStatistics, indexes::IndexDefinition};
+
+const INDEX_DEFINITIONS: &str = "indexes.json";
+const STATISTICS: &str = "statistics.json";
+
 #[derive(Clone)]
 pub struct CollectionConfig {
     pub collection_name: String,
@@ -72,6 +80,42 @@ impl CollectionConfig {
         Ok(valid_log_files)
     }
 
+    /// Loads the definitions of the secondary indexes of the collection
+    pub fn load_index_definitions(&self) -> DBResult<Vec<IndexDefinition>> {
+        self.load_metadata(INDEX_DEFINITIONS)
+    }
+
+    /// Saves the definitions of the secondary indexes of the collection
+    pub fn save_index_definitions(&self, definitions: &[IndexDefinition]) -> DBResult<()> {
+        self.save_metadata(INDEX_DEFINITIONS, definitions)
+    }
+
+    /// Loads the persisted statistics of the collection
+    pub fn load_statistics(&self) -> DBResult<CollectionStatistics> {
+        self.load_metadata(STATISTICS)
+    }
+
+    /// Saves the statistics of the collection
+    pub fn save_statistics(&self, statistics: &CollectionStatistics) -> DBResult<()> {
+        self.save_metadata(STATISTICS, statistics)
+    }
+
+    fn load_metadata<T: DeserializeOwned + Default>(&self, file_name: &str) -> DBResult<T> {
+        let path = self.get_collection_files_destination().join(file_name);
+        if !Path::is_file(&path) {
+            return Ok(T::default());
+        }
+        let data = fs::read_to_string(path)?;
+        Ok(from_str(&data)?)
+    }
+
+    fn save_metadata<T: Serialize + ?Sized>(&self, file_name: &str, data: &T) -> DBResult<()> {
+        let path = self.get_collection_files_destination().join(file_name);
+        fs::write(path, to_string(data)?)?;
+        debug!("{} of collection {} saved", file_name, self.collection_name);
+        Ok(())
+    }
+
     /// Returns the path to the log file for the specific index
     pub fn get_log_path(&self, file_index: usize) -> PathBuf {
         let file_name = format!("{}.log", file_index);
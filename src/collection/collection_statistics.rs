@@ -1,9 +1,36 @@
-use crate::storage::{log_file::log_entry::LogEntry, storage_config::DatabaseConfig};
+use serde::{Deserialize, Serialize};
 
-#[derive(Default, Clone)]
+use crate::{storage::{log_file::log_entry::LogEntry, storage_config::DatabaseConfig}, utils::HyperLogLog, ObjectField};
+
+const HISTOGRAM_BUCKETS: usize = 32;
+
+/// Statistics of a collection, persisted alongside its log files.
+/// The row counts are kept up to date by every write, while the column statistics are only computed by [`crate::collection::Collection::analyze`],
+/// since the values of overwritten or deleted rows can not be taken back out of them.
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
 pub struct CollectionStatistics {
     pub approximate_total_entries: usize,
-    pub approximate_redundant_entries: usize
+    pub approximate_redundant_entries: usize,
+    /// Number of rows whose newest version is not a deletion
+    pub live_rows: usize,
+    pub columns: Vec<ColumnStatistics>
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct ColumnStatistics {
+    pub distinct_values: HyperLogLog,
+    pub min: Option<ObjectField>,
+    pub max: Option<ObjectField>,
+    pub null_count: usize,
+    /// Equi-depth histogram
+    pub histogram: Vec<HistogramBucket>
+}
+
+/// Bucket of an equi-depth histogram, holding the values up to and including the upper bound
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HistogramBucket {
+    pub upper_bound: ObjectField,
+    pub count: usize
 }
 
 impl CollectionStatistics {
@@ -13,7 +40,7 @@ impl CollectionStatistics {
         redundant_percent >= config.log_file.compaction_redundancy_percentage
     }
 
-    pub fn count_entry(&mut self, entry: &LogEntry, is_in_cache: bool) {
+    pub fn count_entry(&mut self, _entry: &LogEntry, is_in_cache: bool) {
         self.approximate_total_entries += 1;
         if is_in_cache {
             self.approximate_redundant_entries += 1;
@@ -23,4 +50,61 @@ impl CollectionStatistics {
     pub fn approximate_entries(&self) -> usize {
         self.approximate_total_entries - self.approximate_redundant_entries
     }
-}
\ No newline at end of file
+
+    /// Estimates the number of distinct values in the column
+    pub fn distinct_values(&self, column: usize) -> Option<usize> {
+        self.columns.get(column).map(|c| c.distinct_values.estimate())
+    }
+
+    /// Estimates the fraction of the live rows having the column value less than or equal to the given one
+    pub fn selectivity_at_most(&self, column: usize, value: &ObjectField) -> Option<f64> {
+        let column = self.columns.get(column)?;
+        if column.histogram.is_empty() {
+            return None;
+        }
+        let total: usize = column.histogram.iter().map(|b| b.count).sum();
+        let below: usize = column.histogram
+            .iter()
+            .take_while(|b| &b.upper_bound <= value)
+            .map(|b| b.count)
+            .sum();
+        Some(below as f64 / (total + column.null_count).max(1) as f64)
+    }
+}
+
+impl ColumnStatistics {
+    /// Builds the statistics of a column from all of its live values
+    pub(crate) fn analyze(mut values: Vec<ObjectField>) -> Self {
+        let mut statistics = ColumnStatistics::default();
+        values.retain(|value| {
+            if value.is_null() {
+                statistics.null_count += 1;
+                false
+            } else {
+                true
+            }
+        });
+        values.sort_unstable();
+        for value in &values {
+            statistics.distinct_values.add(value);
+        }
+        statistics.min = values.first().cloned();
+        statistics.max = values.last().cloned();
+
+        let bucket_size = values.len().div_ceil(HISTOGRAM_BUCKETS).max(1);
+        let mut start = 0;
+        while start < values.len() {
+            let mut end = (start + bucket_size).min(values.len());
+            // Equal values have to stay in the same bucket
+            while end < values.len() && values[end] == values[end - 1] {
+                end += 1;
+            }
+            statistics.histogram.push(HistogramBucket {
+                upper_bound: values[end - 1].clone(),
+                count: end - start
+            });
+            start = end;
+        }
+        statistics
+    }
+}
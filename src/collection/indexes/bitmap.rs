@@ -1,17 +1,53 @@
+use gxhash::{HashMap, HashMapExt};
 use uuid::Uuid;
 
-use crate::ObjectField;
+use crate::{EntryFields, ObjectField};
 
-use super::Index;
+use super::{Index, IndexKey, KeyRange};
 
+/// Index keeping a bitmap of row ordinals for every key - best suited for keys with few distinct values.
 #[derive(Clone, Debug)]
 pub struct BitmapIndex {
-    pub column: usize,
-    pub data: Vec<u64>
+    pub key: IndexKey,
+    pub ids: Vec<Uuid>,
+    pub ordinals: HashMap<Uuid, usize>,
+    pub data: HashMap<Vec<ObjectField>, Vec<u64>>
+}
+
+impl BitmapIndex {
+    pub fn new(key: IndexKey) -> Self {
+        BitmapIndex { key, ids: vec![], ordinals: HashMap::new(), data: HashMap::new() }
+    }
 }
 
 impl Index for BitmapIndex {
-    fn update(&mut self, entry_id: Uuid, entry: &Box<[ObjectField]>) {
-        let column_data = entry[self.column].clone();
+    fn key(&self) -> &IndexKey {
+        &self.key
+    }
+
+    fn update(&mut self, entry_id: Uuid, entry: &EntryFields) {
+        let ordinal = *self.ordinals.entry(entry_id).or_insert_with(|| {
+            self.ids.push(entry_id);
+            self.ids.len() - 1
+        });
+        let bitmap = self.data.entry(self.key.extract(entry)).or_default();
+        if bitmap.len() <= ordinal / 64 {
+            bitmap.resize(ordinal / 64 + 1, 0);
+        }
+        bitmap[ordinal / 64] |= 1 << (ordinal % 64);
+    }
+
+    fn seek(&self, range: &KeyRange) -> Vec<(Vec<ObjectField>, Uuid)> {
+        self.data
+            .iter()
+            .filter(|(key, _)| range.contains(key))
+            .flat_map(|(key, bitmap)| bitmap
+                .iter()
+                .enumerate()
+                .flat_map(|(word_index, word)| (0..64)
+                    .filter(move |bit| word & (1 << bit) != 0)
+                    .map(move |bit| word_index * 64 + bit))
+                .map(|ordinal| (key.clone(), self.ids[ordinal])))
+            .collect()
     }
-}
\ No newline at end of file
+}
@@ -1,19 +1,44 @@
-use std::collections::HashMap;
-
+use gxhash::{HashMap, HashMapExt, HashSet};
 use uuid::Uuid;
 
-use crate::{objects::{ObjectField}};
+use crate::{objects::ObjectField, EntryFields};
 
-use super::Index;
+use super::{Index, IndexKey, KeyRange};
 
+/// Index mapping every key to the set of ids having it, without any ordering.
 #[derive(Clone, Debug)]
 pub struct HashIndex {
-    pub column: usize,
-    pub data: HashMap<ObjectField, Vec<Uuid>>
+    pub key: IndexKey,
+    pub data: HashMap<Vec<ObjectField>, HashSet<Uuid>>
+}
+
+impl HashIndex {
+    pub fn new(key: IndexKey) -> Self {
+        HashIndex { key, data: HashMap::new() }
+    }
 }
 
 impl Index for HashIndex {
-    fn update(&mut self, entry_id: Uuid, entry: &Box<[ObjectField]>) {
-        let column_data = entry[self.column].clone();
+    fn key(&self) -> &IndexKey {
+        &self.key
+    }
+
+    fn update(&mut self, entry_id: Uuid, entry: &EntryFields) {
+        self.data
+            .entry(self.key.extract(entry))
+            .or_default()
+            .insert(entry_id);
+    }
+
+    fn seek(&self, range: &KeyRange) -> Vec<(Vec<ObjectField>, Uuid)> {
+        if let Some(key) = range.exact_key()
+            && let Some(ids) = self.data.get(key) {
+            return ids.iter().map(|id| (key.clone(), *id)).collect();
+        }
+        self.data
+            .iter()
+            .filter(|(key, _)| range.contains(key))
+            .flat_map(|(key, ids)| ids.iter().map(|id| (key.clone(), *id)))
+            .collect()
     }
 }
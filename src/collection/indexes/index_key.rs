@@ -0,0 +1,259 @@
+use std::{cmp::Ordering, fmt::Debug, ops::Bound, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{EntryFields, ObjectField};
+
+/// User-provided function computing an index key from the row.
+pub type KeyExpression = Arc<dyn Fn(&EntryFields) -> ObjectField>;
+
+/// Persisted description of what an index is keyed on.
+/// Expressions are referenced by name - the function has to be registered on the collection after each restart.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum IndexKeyDefinition {
+    Columns(Vec<usize>),
+    Expression(String)
+}
+
+impl IndexKeyDefinition {
+    pub(crate) fn expression_name(&self) -> &str {
+        match self {
+            IndexKeyDefinition::Columns(_) => "",
+            IndexKeyDefinition::Expression(name) => name
+        }
+    }
+}
+
+/// Key extractor of an index - either a list of columns, compared lexicographically, or a key expression.
+#[derive(Clone)]
+pub enum IndexKey {
+    Columns(Vec<usize>),
+    Expression(KeyExpression)
+}
+
+impl IndexKey {
+    pub fn extract(&self, fields: &EntryFields) -> Vec<ObjectField> {
+        match self {
+            IndexKey::Columns(columns) => columns.iter().map(|column| fields.column(*column)).collect(),
+            IndexKey::Expression(expression) => vec![expression(fields)]
+        }
+    }
+}
+
+impl Debug for IndexKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IndexKey::Columns(columns) => f.debug_tuple("Columns").field(columns).finish(),
+            IndexKey::Expression(_) => f.write_str("Expression")
+        }
+    }
+}
+
+/// A range of index keys.
+/// The bounds are treated as key prefixes - a bound of `[a]` on an index over `(a, b)` matches every `b`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyRange {
+    pub start: Bound<Vec<ObjectField>>,
+    pub end: Bound<Vec<ObjectField>>
+}
+
+impl KeyRange {
+    /// Range matching every key
+    pub fn full() -> Self {
+        KeyRange { start: Bound::Unbounded, end: Bound::Unbounded }
+    }
+
+    /// Range matching every key starting with the given prefix
+    pub fn point(key: Vec<ObjectField>) -> Self {
+        KeyRange { start: Bound::Included(key.clone()), end: Bound::Included(key) }
+    }
+
+    pub fn new(start: Bound<Vec<ObjectField>>, end: Bound<Vec<ObjectField>>) -> Self {
+        KeyRange { start, end }
+    }
+
+    /// Checks if the key is not before the start of the range
+    pub fn is_after_start(&self, key: &[ObjectField]) -> bool {
+        match &self.start {
+            Bound::Included(start) => compare_prefix(key, start) != Ordering::Less,
+            Bound::Excluded(start) => compare_prefix(key, start) == Ordering::Greater,
+            Bound::Unbounded => true
+        }
+    }
+
+    /// Checks if the key is not after the end of the range
+    pub fn is_before_end(&self, key: &[ObjectField]) -> bool {
+        match &self.end {
+            Bound::Included(end) => compare_prefix(key, end) != Ordering::Greater,
+            Bound::Excluded(end) => compare_prefix(key, end) == Ordering::Less,
+            Bound::Unbounded => true
+        }
+    }
+
+    pub fn contains(&self, key: &[ObjectField]) -> bool {
+        self.is_after_start(key) && self.is_before_end(key)
+    }
+
+    /// Returns the key if the range matches a single key prefix
+    pub(crate) fn exact_key(&self) -> Option<&Vec<ObjectField>> {
+        match (&self.start, &self.end) {
+            (Bound::Included(start), Bound::Included(end)) if start == end => Some(start),
+            _ => None
+        }
+    }
+
+    /// Returns the lowest key prefix that can be contained in the range - used to start a lookup in sorted indexes
+    pub(crate) fn lookup_start(&self) -> Bound<Vec<ObjectField>> {
+        match &self.start {
+            Bound::Included(start) | Bound::Excluded(start) => Bound::Included(start.clone()),
+            Bound::Unbounded => Bound::Unbounded
+        }
+    }
+}
+
+/// Position of a range bound between the keys - before or after all the keys starting with the prefix
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum BoundPosition<'a> {
+    Start,
+    Before(&'a [ObjectField]),
+    After(&'a [ObjectField]),
+    End
+}
+
+impl<'a> BoundPosition<'a> {
+    fn of_start(bound: &'a Bound<Vec<ObjectField>>) -> Self {
+        match bound {
+            Bound::Included(prefix) => BoundPosition::Before(prefix),
+            Bound::Excluded(prefix) => BoundPosition::After(prefix),
+            Bound::Unbounded => BoundPosition::Start
+        }
+    }
+
+    fn of_end(bound: &'a Bound<Vec<ObjectField>>) -> Self {
+        match bound {
+            Bound::Included(prefix) => BoundPosition::After(prefix),
+            Bound::Excluded(prefix) => BoundPosition::Before(prefix),
+            Bound::Unbounded => BoundPosition::End
+        }
+    }
+
+    /// Side of the prefix the position is at - before the keys starting with it, or after them
+    fn prefix(&self) -> Option<(&'a [ObjectField], Ordering)> {
+        match self {
+            BoundPosition::Before(prefix) => Some((prefix, Ordering::Less)),
+            BoundPosition::After(prefix) => Some((prefix, Ordering::Greater)),
+            BoundPosition::Start | BoundPosition::End => None
+        }
+    }
+
+    /// Order of the positions not specifying a prefix, relative to the ones which do
+    fn rank(&self) -> u8 {
+        match self {
+            BoundPosition::Start => 0,
+            BoundPosition::Before(_) | BoundPosition::After(_) => 1,
+            BoundPosition::End => 2
+        }
+    }
+}
+
+impl PartialOrd for BoundPosition<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BoundPosition<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.prefix(), other.prefix()) {
+            (Some((prefix, side)), Some((other_prefix, other_side))) => match compare_prefix(prefix, other_prefix) {
+                Ordering::Equal => match prefix.len().cmp(&other_prefix.len()) {
+                    Ordering::Equal => side.cmp(&other_side),
+                    // The shorter prefix surrounds all the keys starting with the longer one
+                    Ordering::Less => side,
+                    Ordering::Greater => other_side.reverse()
+                },
+                ordering => ordering
+            },
+            _ => self.rank().cmp(&other.rank())
+        }
+    }
+}
+
+impl KeyRange {
+    /// Checks if no key can be in the range
+    pub fn is_empty(&self) -> bool {
+        BoundPosition::of_start(&self.start) >= BoundPosition::of_end(&self.end)
+    }
+
+    /// Merges the ranges into disjoint intervals, sorted by their keys - every key contained in any of the ranges is in exactly one of them.
+    /// Empty ranges are dropped, and overlapping or adjacent ranges are merged into one.
+    pub fn merge_intervals(ranges: impl IntoIterator<Item = KeyRange>) -> Vec<KeyRange> {
+        let mut ranges: Vec<KeyRange> = ranges
+            .into_iter()
+            .filter(|range| !range.is_empty())
+            .collect();
+        ranges.sort_by(|a, b| BoundPosition::of_start(&a.start).cmp(&BoundPosition::of_start(&b.start)));
+
+        let mut merged: Vec<KeyRange> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            if let Some(last) = merged.last_mut()
+                && BoundPosition::of_start(&range.start) <= BoundPosition::of_end(&last.end) {
+                if BoundPosition::of_end(&range.end) > BoundPosition::of_end(&last.end) {
+                    last.end = range.end;
+                }
+                continue;
+            }
+            merged.push(range);
+        }
+        merged
+    }
+}
+
+/// Compares the key with a bound, looking only at the columns the bound specifies
+fn compare_prefix(key: &[ObjectField], bound: &[ObjectField]) -> Ordering {
+    let length = key.len().min(bound.len());
+    key[..length].cmp(&bound[..length])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound;
+
+    use serial_test::parallel;
+
+    use crate::ObjectField;
+
+    use super::KeyRange;
+
+    fn key(values: &[i32]) -> Vec<ObjectField> {
+        values.iter().map(|value| ObjectField::I32(*value)).collect()
+    }
+
+    #[test]
+    #[parallel]
+    fn test_merge_intervals() {
+        let ranges = vec![
+            KeyRange::new(Bound::Included(key(&[10])), Bound::Excluded(key(&[20]))),
+            KeyRange::new(Bound::Included(key(&[15])), Bound::Included(key(&[25]))),
+            KeyRange::point(key(&[25, 3])),
+            KeyRange::new(Bound::Excluded(key(&[25])), Bound::Excluded(key(&[30]))),
+            KeyRange::new(Bound::Excluded(key(&[5])), Bound::Included(key(&[5]))),
+            KeyRange::new(Bound::Unbounded, Bound::Excluded(key(&[1]))),
+            KeyRange::point(key(&[40]))
+        ];
+        let merged = KeyRange::merge_intervals(ranges);
+        assert_eq!(merged, vec![
+            KeyRange::new(Bound::Unbounded, Bound::Excluded(key(&[1]))),
+            KeyRange::new(Bound::Included(key(&[10])), Bound::Excluded(key(&[30]))),
+            KeyRange::point(key(&[40]))
+        ]);
+
+        // A prefix bound surrounds the longer keys starting with it
+        assert!(!KeyRange::new(Bound::Included(key(&[1, 5])), Bound::Included(key(&[1]))).is_empty());
+        assert!(KeyRange::new(Bound::Excluded(key(&[1])), Bound::Included(key(&[1, 5]))).is_empty());
+        assert_eq!(
+            KeyRange::merge_intervals([KeyRange::point(key(&[1])), KeyRange::point(key(&[1, 5]))]),
+            vec![KeyRange::point(key(&[1]))]
+        );
+    }
+}
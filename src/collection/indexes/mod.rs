@@ -1,14 +1,70 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::{ObjectField};
+use crate::{query::condition::Condition, utils::DBResult, EntryFields, ObjectField};
 
 use self::{sorted::SortedIndex, reverse::ReverseIndex, bitmap::BitmapIndex, hash::HashIndex};
 
+pub use self::primary::PrimaryIndex;
+pub use self::index_key::{IndexKey, IndexKeyDefinition, KeyExpression, KeyRange};
+
+mod primary;
+mod index_key;
 mod sorted;
 mod reverse;
 mod bitmap;
 mod hash;
 
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IndexKind {
+    Sorted { descending: bool },
+    Reverse { descending: bool },
+    Bitmap,
+    Hash
+}
+
+/// Persisted definition of a secondary index of a collection.
+/// A partial index only covers the row versions satisfying its predicate.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct IndexDefinition {
+    pub name: String,
+    pub kind: IndexKind,
+    pub key: IndexKeyDefinition,
+    #[serde(default)]
+    pub predicate: Option<Condition>
+}
+
+impl IndexDefinition {
+    /// Checks if the index covers all the rows matching the query conditions.
+    /// The conditions are expected to be the normalized conjuncts of the query filter.
+    pub fn is_applicable(&self, conditions: &BTreeSet<Condition>) -> bool {
+        match &self.predicate {
+            Some(predicate) => predicate.conjuncts().is_subset(conditions),
+            None => true
+        }
+    }
+
+    /// Checks if the row version should be stored in the index - fails if the predicate can not be evaluated on the row
+    pub(crate) fn covers(&self, row: &EntryFields) -> DBResult<bool> {
+        self.predicate
+            .as_ref()
+            .map_or(Ok(true), |predicate| predicate.is_satisfied(row))
+    }
+}
+
+impl std::fmt::Debug for IndexDefinition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IndexDefinition")
+            .field("name", &self.name)
+            .field("kind", &self.kind)
+            .field("key", &self.key)
+            .field("predicate", &self.predicate.as_ref().map(ToString::to_string))
+            .finish()
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum WrappedIndex {
     Sorted(SortedIndex),
@@ -18,5 +74,64 @@ pub enum WrappedIndex {
 }
 
 pub trait Index {
-    fn update(&mut self, entry_id: Uuid, entry: &Box<[ObjectField]>);
-}
\ No newline at end of file
+    /// Returns the key extractor of the index
+    fn key(&self) -> &IndexKey;
+
+    /// Adds the row version to the index.
+    /// Older versions are not removed - they are filtered out during the lookup.
+    fn update(&mut self, entry_id: Uuid, entry: &EntryFields);
+
+    /// Returns all (key, id) pairs with keys in the given range
+    fn seek(&self, range: &KeyRange) -> Vec<(Vec<ObjectField>, Uuid)>;
+}
+
+impl WrappedIndex {
+    pub fn new(kind: IndexKind, key: IndexKey) -> Self {
+        match kind {
+            IndexKind::Sorted { descending } => WrappedIndex::Sorted(SortedIndex::new(key, descending)),
+            IndexKind::Reverse { descending } => WrappedIndex::Reverse(ReverseIndex::new(key, descending)),
+            IndexKind::Bitmap => WrappedIndex::Bitmap(BitmapIndex::new(key)),
+            IndexKind::Hash => WrappedIndex::Hash(HashIndex::new(key)),
+        }
+    }
+
+    fn inner(&self) -> &dyn Index {
+        match self {
+            WrappedIndex::Sorted(index) => index,
+            WrappedIndex::Reverse(index) => index,
+            WrappedIndex::Bitmap(index) => index,
+            WrappedIndex::Hash(index) => index,
+        }
+    }
+
+    fn inner_mut(&mut self) -> &mut dyn Index {
+        match self {
+            WrappedIndex::Sorted(index) => index,
+            WrappedIndex::Reverse(index) => index,
+            WrappedIndex::Bitmap(index) => index,
+            WrappedIndex::Hash(index) => index,
+        }
+    }
+}
+
+impl Index for WrappedIndex {
+    fn key(&self) -> &IndexKey {
+        self.inner().key()
+    }
+
+    fn update(&mut self, entry_id: Uuid, entry: &EntryFields) {
+        self.inner_mut().update(entry_id, entry);
+    }
+
+    fn seek(&self, range: &KeyRange) -> Vec<(Vec<ObjectField>, Uuid)> {
+        self.inner().seek(range)
+    }
+}
+
+/// Secondary index of a collection, together with its definition.
+/// Expression indexes loaded from disk stay unbuilt until their key expression gets registered.
+#[derive(Clone, Debug)]
+pub struct CollectionIndex {
+    pub definition: IndexDefinition,
+    pub(crate) index: Option<WrappedIndex>
+}
@@ -0,0 +1,77 @@
+use gxhash::{HashMap, HashMapExt, HashSet, HashSetExt};
+use smallvec::SmallVec;
+use uuid::Uuid;
+
+use crate::storage::log_file::{log_entry::{EntityEntry, LogEntry, TransactionEntry}, log_position::LogPosition};
+
+/// In-memory index mapping every row id to the positions of all of its versions in the log files.
+/// It is rebuilt from the log files when the collection is loaded.
+#[derive(Clone, Debug)]
+pub struct PrimaryIndex {
+    positions: HashMap<Uuid, SmallVec<[LogPosition; 1]>>,
+    /// Ids whose newest written version is a deletion
+    tombstones: HashSet<Uuid>,
+    committed_transactions: HashSet<Uuid>
+}
+
+impl PrimaryIndex {
+    pub fn new() -> Self {
+        let mut committed_transactions = HashSet::new();
+        committed_transactions.insert(Uuid::nil());
+        PrimaryIndex {
+            positions: HashMap::new(),
+            tombstones: HashSet::new(),
+            committed_transactions
+        }
+    }
+
+    /// Registers the entry found at the given position - entries have to be registered in the order they were written.
+    pub fn register(&mut self, entry: &LogEntry, position: LogPosition) {
+        match entry {
+            LogEntry::Entity(_, entity) => {
+                let id = entity.object_id();
+                self.positions
+                    .entry(id)
+                    .or_default()
+                    .push(position);
+                match entity {
+                    EntityEntry::Updated(_) => self.tombstones.remove(&id),
+                    EntityEntry::Deleted(_) => self.tombstones.insert(id)
+                };
+            },
+            LogEntry::Transaction(transaction_id, TransactionEntry::Committed) => {
+                self.committed_transactions.insert(*transaction_id);
+            },
+            LogEntry::Transaction(_, TransactionEntry::Rollbacked) => { }
+        }
+    }
+
+    /// Returns the positions of all versions of the row, from the oldest to the newest.
+    pub fn positions(&self, id: &Uuid) -> &[LogPosition] {
+        self.positions
+            .get(id)
+            .map_or(&[], |positions| positions.as_slice())
+    }
+
+    /// Checks if an entry written by `entry_transaction_id` is visible from the transaction `transaction_id`.
+    pub fn is_visible(&self, entry_transaction_id: &Uuid, transaction_id: &Uuid) -> bool {
+        entry_transaction_id <= transaction_id && self.committed_transactions.contains(entry_transaction_id)
+    }
+
+    /// Returns the number of distinct row ids ever written to the collection.
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Returns the number of rows whose newest written version is not a deletion.
+    pub fn live_rows(&self) -> usize {
+        self.positions.len() - self.tombstones.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.positions.clear();
+        self.tombstones.clear();
+        self.committed_transactions.clear();
+        self.committed_transactions.insert(Uuid::nil());
+    }
+}
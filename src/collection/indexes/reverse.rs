@@ -1,18 +1,47 @@
+use std::{collections::{BTreeMap, BTreeSet}, ops::Bound};
+
 use uuid::Uuid;
 
-use crate::{objects::{ObjectField}};
+use crate::{objects::ObjectField, EntryFields};
 
-use super::Index;
+use super::{Index, IndexKey, KeyRange};
 
+/// Inverted index, mapping every key to the set of ids having it, in key order.
 #[derive(Clone, Debug)]
 pub struct ReverseIndex {
-    pub column: usize,
+    pub key: IndexKey,
     pub descending: bool,
-    pub data: Vec<(ObjectField, Vec<Uuid>)>
+    pub data: BTreeMap<Vec<ObjectField>, BTreeSet<Uuid>>
+}
+
+impl ReverseIndex {
+    pub fn new(key: IndexKey, descending: bool) -> Self {
+        ReverseIndex { key, descending, data: BTreeMap::new() }
+    }
 }
 
 impl Index for ReverseIndex {
-    fn update(&mut self, entry_id: Uuid, entry: &Box<[ObjectField]>) {
-        let column_data = entry[self.column].clone();
+    fn key(&self) -> &IndexKey {
+        &self.key
+    }
+
+    fn update(&mut self, entry_id: Uuid, entry: &EntryFields) {
+        self.data
+            .entry(self.key.extract(entry))
+            .or_default()
+            .insert(entry_id);
+    }
+
+    fn seek(&self, range: &KeyRange) -> Vec<(Vec<ObjectField>, Uuid)> {
+        let mut result: Vec<_> = self.data
+            .range((range.lookup_start(), Bound::Unbounded))
+            .take_while(|(key, _)| range.is_before_end(key))
+            .filter(|(key, _)| range.is_after_start(key))
+            .flat_map(|(key, ids)| ids.iter().map(|id| (key.clone(), *id)))
+            .collect();
+        if self.descending {
+            result.reverse();
+        }
+        result
     }
 }
@@ -1,18 +1,48 @@
+use std::{collections::BTreeSet, ops::Bound};
+
 use uuid::Uuid;
 
-use crate::{objects::{ObjectField}};
+use crate::{objects::ObjectField, EntryFields};
 
-use super::Index;
+use super::{Index, IndexKey, KeyRange};
 
+/// Index keeping a sorted list of (key, id) pairs.
 #[derive(Clone, Debug)]
 pub struct SortedIndex {
-    pub column: usize,
+    pub key: IndexKey,
     pub descending: bool,
-    pub data: Vec<(ObjectField, Uuid)>
+    pub data: BTreeSet<(Vec<ObjectField>, Uuid)>
+}
+
+impl SortedIndex {
+    pub fn new(key: IndexKey, descending: bool) -> Self {
+        SortedIndex { key, descending, data: BTreeSet::new() }
+    }
 }
 
 impl Index for SortedIndex {
-    fn update(&mut self, entry_id: Uuid, entry: &Box<[ObjectField]>) {
-        let column_data = entry[self.column].clone();
+    fn key(&self) -> &IndexKey {
+        &self.key
+    }
+
+    fn update(&mut self, entry_id: Uuid, entry: &EntryFields) {
+        self.data.insert((self.key.extract(entry), entry_id));
+    }
+
+    fn seek(&self, range: &KeyRange) -> Vec<(Vec<ObjectField>, Uuid)> {
+        let start = match range.lookup_start() {
+            Bound::Included(start) => Bound::Included((start, Uuid::nil())),
+            _ => Bound::Unbounded
+        };
+        let mut result: Vec<_> = self.data
+            .range((start, Bound::Unbounded))
+            .take_while(|(key, _)| range.is_before_end(key))
+            .filter(|(key, _)| range.is_after_start(key))
+            .cloned()
+            .collect();
+        if self.descending {
+            result.reverse();
+        }
+        result
     }
 }
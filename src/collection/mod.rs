@@ -1,22 +1,29 @@
-use std::{ops::ControlFlow::Break, path::PathBuf, sync::{Arc, Mutex}};
+use std::{collections::{BTreeMap, BTreeSet}, ops::ControlFlow::Break, path::PathBuf, sync::{Arc, Mutex}};
 
 use itertools::Itertools;
+use log::debug;
 use schnellru::{ByLength, LruMap};
+use serde::Deserialize;
 use uuid::Uuid;
 
-use crate::{errors::storage_error::{SchemaError, StorageError}, operators::TableScan, storage::log_file::{log_entry::{EntityEntry, LogEntry}, LogFile}, utils::{DBResult, GuardExtensions}, DBOperator, Row};
+use crate::{errors::{client_side_error::{ClientSideError, IndexAlreadyExistsError, IndexDoesNotExistError, KeyExpressionNotRegisteredError}, storage_error::{SchemaError, StorageError}}, operators::{IndexSeek, ParallelTableScan, TableScan, TableSeek}, query::{condition::Condition, Query}, storage::log_file::{log_entry::{EntityEntry, LogEntry}, log_position::LogPosition, LogFile}, utils::{DBResult, GuardExtensions}, DBOperator, EntryFields, ObjectField, Row};
 
-use self::{collection_config::CollectionConfig, indexes::WrappedIndex, collection_statistics::CollectionStatistics};
+use self::{collection_config::CollectionConfig, indexes::{CollectionIndex, Index, IndexKey, KeyExpression, PrimaryIndex, WrappedIndex}};
+
+pub use self::indexes::{IndexDefinition, IndexKeyDefinition, IndexKind, KeyRange};
+pub use self::collection_statistics::{CollectionStatistics, ColumnStatistics, HistogramBucket};
 
 pub mod collection_config;
 mod collection_statistics;
-mod indexes;
+pub(crate) mod indexes;
 
 pub struct Collection {
     pub(crate) last_file_index: usize,
     log_files: Mutex<LruMap<usize, Arc<LogFile>, ByLength>>,
     pub config: CollectionConfig,
-    indexes: Vec<WrappedIndex>,
+    primary_index: PrimaryIndex,
+    indexes: Vec<CollectionIndex>,
+    key_expressions: BTreeMap<String, KeyExpression>,
     pub(crate) statistics: CollectionStatistics
 }
 
@@ -26,14 +33,23 @@ impl Collection {
         config.ensure_file_exists(0)?;
 
         let file_count = config.storage_config.cache.file_count;
+        let indexes = config
+            .load_index_definitions()?
+            .into_iter()
+            .map(|definition| CollectionIndex { definition, index: None })
+            .collect();
+        let statistics = config.load_statistics()?;
 
-        let collection = Collection {
+        let mut collection = Collection {
             last_file_index: config.get_log_file_paths()?.len() - 1,
             config,
             log_files: Mutex::new(LruMap::new(ByLength::new(file_count as u32))),
-            indexes: std::vec::Vec::new(),
-            statistics: CollectionStatistics::default()
+            primary_index: PrimaryIndex::new(),
+            indexes,
+            key_expressions: BTreeMap::new(),
+            statistics
         };
+        collection.rebuild_indexes()?;
 
         Ok(collection)
     }
@@ -57,6 +73,7 @@ impl Collection {
                 return Err(SchemaError::from_string(e.object_id().to_string()).into());
             }
         }
+        self.check_index_predicates(&entries)?;
 
         let mut newest_file = self.get_file(self.last_file_index)?.ok_or(StorageError::Inconsistency())?;
         let max_entries = self.config.storage_config.log_file.max_entries;
@@ -75,7 +92,15 @@ impl Collection {
                     Some(vec)
                 }
             }) {
-            let added_entries = LogFile::append_entries(&newest_file, &self.config, chunk.into_iter().map(|e| LogEntry::Entity(transaction_id, e)))?;
+            let first_position = newest_file.read()?.len();
+            let chunk = chunk.into_iter().map(|e| LogEntry::Entity(transaction_id, e)).collect_vec();
+            let added_entries = LogFile::append_entries(&newest_file, &self.config, chunk.iter().cloned())?;
+            for (offset, entry) in chunk.iter().take(added_entries).enumerate() {
+                self.register_entry(entry, LogPosition {
+                    file_id: self.last_file_index,
+                    entry_position: first_position + offset
+                })?;
+            }
 
             appends += added_entries;
             entries_left -= added_entries;
@@ -88,20 +113,234 @@ impl Collection {
         }
 
         self.statistics.approximate_total_entries += appends;
+        self.statistics.live_rows = self.primary_index.live_rows();
+        self.save_statistics()?;
 
         Ok(appends)
     }
 
     /// Retrieves the specified log file from the collection
     pub(crate) fn get_file(&self, index: usize) -> DBResult<Option<Arc<LogFile>>> {
-        match self.log_files
-            .lock()
-            .not_poisoned()
-            .get_or_insert_fallible(index, || LogFile::load_log_file(&self.config, index).map(Arc::new)) {
-            Ok(Some(file)) => Ok(Some(file.clone())),
-            Ok(None) => Ok(None),
-            Err(e) => Err(e),
+        self.file_loader()(index)
+    }
+
+    /// Returns a function getting the log files like [`Collection::get_file`], which can be shared with other threads.
+    /// The files are read without holding the lock of the cache, so that several threads can load different files at once.
+    pub(crate) fn file_loader(&self) -> impl Fn(usize) -> DBResult<Option<Arc<LogFile>>> + Sync + '_ {
+        let log_files = &self.log_files;
+        let config = &self.config;
+        move |index| {
+            if let Some(file) = log_files.lock().not_poisoned().get(&index) {
+                return Ok(Some(file.clone()));
+            }
+            let file = Arc::new(LogFile::load_log_file(config, index)?);
+            Ok(log_files
+                .lock()
+                .not_poisoned()
+                .get_or_insert(index, || file)
+                .cloned())
+        }
+    }
+
+    /// Adds the entry written at the given position to the primary and all of the built secondary indexes
+    fn register_entry(&mut self, entry: &LogEntry, position: LogPosition) -> DBResult<()> {
+        self.primary_index.register(entry, position);
+        if let LogEntry::Entity(_, EntityEntry::Updated(row)) = entry {
+            for collection_index in self.indexes.iter_mut() {
+                if let Some(index) = collection_index.index.as_mut()
+                    && collection_index.definition.covers(&row.fields)? {
+                    index.update(row.id, &row.fields);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the predicates of the partial indexes can be evaluated on the rows, before they are written
+    fn check_index_predicates(&self, entries: &[EntityEntry]) -> DBResult<()> {
+        for entry in entries {
+            if let EntityEntry::Updated(row) = entry {
+                for collection_index in self.indexes.iter().filter(|index| index.index.is_some()) {
+                    collection_index.definition.covers(&row.fields)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Rebuilds the primary and the secondary indexes by reading all of the log files of the collection
+    pub(crate) fn rebuild_indexes(&mut self) -> DBResult<()> {
+        self.primary_index.clear();
+        for index in self.indexes.iter_mut() {
+            index.index = self.key_expressions
+                .build_key(&index.definition.key)
+                .map(|key| WrappedIndex::new(index.definition.kind, key));
+        }
+        let mut total_entries = 0;
+        for file_id in 0..=self.last_file_index {
+            let file = self.get_file(file_id)?.ok_or(StorageError::Inconsistency())?;
+            let entries = file.read()?;
+            for (entry_position, entry) in entries.iter().enumerate() {
+                self.register_entry(entry, LogPosition { file_id, entry_position })?;
+            }
+            total_entries += entries.len();
+        }
+        self.statistics.approximate_total_entries = total_entries;
+        self.statistics.live_rows = self.primary_index.live_rows();
+        Ok(())
+    }
+
+    /// Recomputes the statistics of the collection from all of its committed rows, and persists them
+    pub fn analyze(&mut self) -> DBResult<&CollectionStatistics> {
+        let mut scan = self.table_scan(Uuid::max());
+        let mut values: Vec<Vec<ObjectField>> = vec![];
+        let mut live_rows = 0;
+        while let Some(row) = scan.next()? {
+            if values.len() < row.fields.len() {
+                values.resize_with(row.fields.len(), Vec::new);
+            }
+            for (index, column) in values.iter_mut().enumerate().take(row.fields.len()) {
+                column.push(row.fields.column(index));
+            }
+            live_rows += 1;
+        }
+
+        self.statistics.live_rows = live_rows;
+        self.statistics.columns = values
+            .into_iter()
+            .map(ColumnStatistics::analyze)
+            .collect();
+        self.save_statistics()?;
+        debug!("Collection {} analyzed - {} live rows", self.name(), live_rows);
+        Ok(&self.statistics)
+    }
+
+    pub fn statistics(&self) -> &CollectionStatistics {
+        &self.statistics
+    }
+
+    /// Persists the statistics of the collection - done after every write and analysis
+    pub fn save_statistics(&self) -> DBResult<()> {
+        self.config.save_statistics(&self.statistics)
+    }
+
+    /// Returns the number of distinct row ids ever written to the collection - an upper bound on the rows any scan returns
+    pub(crate) fn written_ids(&self) -> usize {
+        self.primary_index.len()
+    }
+
+    /// Fills the index with all the row versions stored in the log files of the collection, covered by the definition
+    fn build_index(&self, definition: &IndexDefinition, index: &mut WrappedIndex) -> DBResult<()> {
+        for file_id in 0..=self.last_file_index {
+            let file = self.get_file(file_id)?.ok_or(StorageError::Inconsistency())?;
+            let entries = file.read()?;
+            for entry in entries.iter() {
+                if let LogEntry::Entity(_, EntityEntry::Updated(row)) = entry
+                    && definition.covers(&row.fields)? {
+                    index.update(row.id, &row.fields);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates a new secondary index and persists its definition.
+    /// Expression keys have to be registered with [`Collection::register_key_expression`] beforehand.
+    /// When a predicate is given, only the rows satisfying it are indexed - the index is not created if it can not be evaluated on a stored row,
+    /// and afterwards, writing rows it can not be evaluated on fails.
+    pub fn create_index(&mut self, name: &str, kind: IndexKind, key: IndexKeyDefinition, predicate: Option<Condition>) -> DBResult<()> {
+        if self.indexes.iter().any(|index| index.definition.name == name) {
+            return Err(ClientSideError::from(IndexAlreadyExistsError { name: name.to_owned() }).into());
+        }
+        let index_key = match self.key_expressions.build_key(&key) {
+            Some(index_key) => index_key,
+            None => return Err(ClientSideError::from(KeyExpressionNotRegisteredError { name: key.expression_name().to_owned() }).into())
+        };
+        let definition = IndexDefinition { name: name.to_owned(), kind, key, predicate };
+        let mut index = WrappedIndex::new(kind, index_key);
+        self.build_index(&definition, &mut index)?;
+        self.indexes.push(CollectionIndex {
+            definition,
+            index: Some(index)
+        });
+        self.save_index_definitions()
+    }
+
+    /// Removes the secondary index with the given name
+    pub fn drop_index(&mut self, name: &str) -> DBResult<()> {
+        let length = self.indexes.len();
+        self.indexes.retain(|index| index.definition.name != name);
+        if self.indexes.len() == length {
+            return Err(ClientSideError::from(IndexDoesNotExistError { name: name.to_owned() }).into());
+        }
+        self.save_index_definitions()
+    }
+
+    /// Registers a named key expression usable in index definitions.
+    /// Indexes loaded from disk that use the expression get built on registration.
+    pub fn register_key_expression(&mut self, name: &str, expression: impl Fn(&EntryFields) -> ObjectField + 'static) -> DBResult<()> {
+        self.key_expressions.insert(name.to_owned(), Arc::new(expression));
+        let mut pending = std::mem::take(&mut self.indexes);
+        for index in pending.iter_mut().filter(|index| index.index.is_none()) {
+            if let Some(key) = self.key_expressions.build_key(&index.definition.key) {
+                let mut built = WrappedIndex::new(index.definition.kind, key);
+                self.build_index(&index.definition, &mut built)?;
+                index.index = Some(built);
+            }
+        }
+        self.indexes = pending;
+        Ok(())
+    }
+
+    pub fn index_definitions(&self) -> impl Iterator<Item = &IndexDefinition> {
+        self.indexes.iter().map(|index| &index.definition)
+    }
+
+    fn save_index_definitions(&self) -> DBResult<()> {
+        let definitions = self.indexes
+            .iter()
+            .map(|index| index.definition.clone())
+            .collect_vec();
+        self.config.save_index_definitions(&definitions)
+    }
+
+    /// Returns the built indexes covering all the rows matching the normalized query conditions
+    pub fn applicable_indexes<'a>(&'a self, conditions: &'a BTreeSet<Condition>) -> impl Iterator<Item = &'a IndexDefinition> {
+        self.indexes
+            .iter()
+            .filter(|index| index.index.is_some() && index.definition.is_applicable(conditions))
+            .map(|index| &index.definition)
+    }
+
+    fn get_index(&self, name: &str) -> DBResult<(&IndexDefinition, &WrappedIndex)> {
+        let index = self.indexes
+            .iter()
+            .find(|index| index.definition.name == name)
+            .ok_or_else(|| ClientSideError::from(IndexDoesNotExistError { name: name.to_owned() }))?;
+        match &index.index {
+            Some(built) => Ok((&index.definition, built)),
+            None => Err(ClientSideError::from(KeyExpressionNotRegisteredError { name: index.definition.key.expression_name().to_owned() }).into())
+        }
+    }
+
+    /// Retrieves the newest version of the row with the given id, visible from the given transaction
+    pub fn get(&self, transaction_id: Uuid, id: Uuid) -> DBResult<Option<Row>> {
+        for position in self.primary_index.positions(&id).iter().rev() {
+            let file = self.get_file(position.file_id)?.ok_or(StorageError::Inconsistency())?;
+            let entries = file.read()?;
+            match entries.get(position.entry_position) {
+                Some(LogEntry::Entity(entry_transaction_id, entity)) => {
+                    if self.primary_index.is_visible(entry_transaction_id, &transaction_id) {
+                        return Ok(match entity {
+                            EntityEntry::Updated(row) => Some(row.clone()),
+                            EntityEntry::Deleted(_) => None
+                        });
+                    }
+                },
+                _ => return Err(StorageError::Inconsistency().into())
+            }
         }
+        Ok(None)
     }
 
     fn get_any_entry_data(&self) -> DBResult<Option<EntityEntry>> {
@@ -116,6 +355,45 @@ impl Collection {
         TableScan::new(self, transaction_id)
     }
 
+    /// Scans the log files concurrently - the rows are the same as of [`Collection::table_scan`], but in no particular order
+    pub fn parallel_table_scan(&self, transaction_id: Uuid) -> ParallelTableScan<'_> {
+        ParallelTableScan::new(self, transaction_id)
+    }
+
+    pub fn query<'a, Item: Deserialize<'a> + 'a>(&'a self, transaction_id: Uuid) -> Query<'a, Item> {
+        Query::from_collection(self, transaction_id)
+    }
+
+    pub fn table_seek(&self, transaction_id: Uuid, ids: impl IntoIterator<Item = Uuid>) -> TableSeek<'_> {
+        TableSeek::new(self, transaction_id, ids.into_iter().collect())
+    }
+
+    /// Retrieves the rows with index keys in the given range, in the index order
+    pub fn index_seek(&self, transaction_id: Uuid, index_name: &str, range: KeyRange) -> DBResult<IndexSeek<'_>> {
+        let (definition, index) = self.get_index(index_name)?;
+        Ok(IndexSeek::new(self, transaction_id, index.key().clone(), definition.predicate.clone(), index.seek(&range)))
+    }
+
+    /// Retrieves the rows with index keys in any of the given ranges, in the index order.
+    /// The ranges are merged into disjoint intervals first, so every row is returned once, even if the ranges overlap.
+    pub fn index_seek_ranges(&self, transaction_id: Uuid, index_name: &str, ranges: impl IntoIterator<Item = KeyRange>) -> DBResult<IndexSeek<'_>> {
+        let (definition, index) = self.get_index(index_name)?;
+        let mut intervals = KeyRange::merge_intervals(ranges);
+        if let IndexKind::Sorted { descending: true } | IndexKind::Reverse { descending: true } = definition.kind {
+            intervals.reverse();
+        }
+        let entries = intervals
+            .iter()
+            .flat_map(|interval| index.seek(interval))
+            .collect();
+        Ok(IndexSeek::new(self, transaction_id, index.key().clone(), definition.predicate.clone(), entries))
+    }
+
+    /// Retrieves all the rows of the collection, in the index order
+    pub fn index_scan(&self, transaction_id: Uuid, index_name: &str) -> DBResult<IndexSeek<'_>> {
+        self.index_seek(transaction_id, index_name, KeyRange::full())
+    }
+
     pub fn print_debug_info(&self) -> CollectionDebugInfo {
         let lock = self.log_files.lock().not_poisoned();
         CollectionDebugInfo {
@@ -141,3 +419,16 @@ pub struct CollectionDebugInfo {
     pub log_file_directory: PathBuf,
     pub log_file_last_index: usize
 }
+
+trait KeyExpressionRegistry {
+    fn build_key(&self, definition: &IndexKeyDefinition) -> Option<IndexKey>;
+}
+
+impl KeyExpressionRegistry for BTreeMap<String, KeyExpression> {
+    fn build_key(&self, definition: &IndexKeyDefinition) -> Option<IndexKey> {
+        match definition {
+            IndexKeyDefinition::Columns(columns) => Some(IndexKey::Columns(columns.clone())),
+            IndexKeyDefinition::Expression(name) => self.get(name).cloned().map(IndexKey::Expression)
+        }
+    }
+}
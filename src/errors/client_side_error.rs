@@ -3,9 +3,13 @@ use std::fmt::Display;
 use thiserror::Error as ErrorMacro;
 
 #[derive(ErrorMacro, Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum ClientSideError {
     #[error("Collection already exists")] CollectionAlreadyExistsError(#[from] CollectionAlreadyExistsError),
-    #[error("Collection does not exist")] CollectionDoesNotExistError(#[from] CollectionDoesNotExistError)
+    #[error("Collection does not exist")] CollectionDoesNotExistError(#[from] CollectionDoesNotExistError),
+    #[error("Index already exists")] IndexAlreadyExistsError(#[from] IndexAlreadyExistsError),
+    #[error("Index does not exist")] IndexDoesNotExistError(#[from] IndexDoesNotExistError),
+    #[error("Key expression is not registered")] KeyExpressionNotRegisteredError(#[from] KeyExpressionNotRegisteredError)
 }
 
 #[derive(ErrorMacro, Debug)]
@@ -28,4 +32,37 @@ impl Display for CollectionDoesNotExistError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_fmt(format_args!("Collection \"{}\" already exists.", self.name))
     }
+}
+
+#[derive(ErrorMacro, Debug)]
+pub struct IndexAlreadyExistsError {
+    pub name: String
+}
+
+impl Display for IndexAlreadyExistsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("Index \"{}\" already exists.", self.name))
+    }
+}
+
+#[derive(ErrorMacro, Debug)]
+pub struct IndexDoesNotExistError {
+    pub name: String
+}
+
+impl Display for IndexDoesNotExistError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("Index \"{}\" does not exist.", self.name))
+    }
+}
+
+#[derive(ErrorMacro, Debug)]
+pub struct KeyExpressionNotRegisteredError {
+    pub name: String
+}
+
+impl Display for KeyExpressionNotRegisteredError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("Key expression \"{}\" is not registered.", self.name))
+    }
 }
\ No newline at end of file
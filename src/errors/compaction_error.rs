@@ -5,14 +5,14 @@ use thiserror::Error as ErrorMacro;
 use super::StringError;
 
 #[derive(ErrorMacro, Debug)]
-pub struct CompactionError(pub Box<dyn Error>);
+pub struct CompactionError(pub Box<dyn Error + Send + Sync>);
 
 impl CompactionError {
     pub fn from_str(text: &'static str) -> Self {
         CompactionError::wrap(StringError::Static(text))
     }
 
-    pub fn wrap(error: impl Error + 'static) -> Self {
+    pub fn wrap(error: impl Error + Send + Sync + 'static) -> Self {
         CompactionError(Box::new(error))
     }
 }
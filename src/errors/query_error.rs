@@ -2,11 +2,12 @@ use std::fmt::Display;
 
 use thiserror::Error as ErrorMacro;
 
-use super::StringError;
+use super::{DatabaseError, StringError};
 
 #[derive(ErrorMacro, Debug)]
 pub enum QueryError {
-    #[error("Error while deserializing a record")] DeserializerError(#[from] DeserializerError)
+    #[error("Error while deserializing a record")] DeserializerError(#[from] DeserializerError),
+    #[error("Error while evaluating a condition")] EvaluationError(#[from] EvaluationError)
 }
 
 #[derive(ErrorMacro, Debug)]
@@ -28,4 +29,29 @@ impl DeserializerError {
     pub fn from_str(s: &'static str) -> Self {
         DeserializerError(StringError::Static(s))
     }
+}
+
+#[derive(ErrorMacro, Debug)]
+pub struct EvaluationError(StringError);
+
+impl Display for EvaluationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl EvaluationError {
+    pub fn from_str(s: &'static str) -> Self {
+        EvaluationError(StringError::Static(s))
+    }
+
+    pub fn from_string(s: String) -> Self {
+        EvaluationError(StringError::Owning(s))
+    }
+}
+
+impl From<EvaluationError> for DatabaseError {
+    fn from(value: EvaluationError) -> Self {
+        QueryError::from(value).into()
+    }
 }
\ No newline at end of file
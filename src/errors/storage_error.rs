@@ -15,7 +15,7 @@ pub enum StorageError {
 }
 
 #[derive(ErrorMacro, Debug)]
-pub struct CompressionError(pub Box<dyn Error>);
+pub struct CompressionError(pub Box<dyn Error + Send + Sync>);
 
 impl CompressionError {
     pub fn from_str(text: &'static str) -> Self {
@@ -26,7 +26,7 @@ impl CompressionError {
         CompressionError::wrap(StringError::Owning(text))
     }
 
-    pub fn wrap(error: impl Error + 'static) -> Self {
+    pub fn wrap(error: impl Error + Send + Sync + 'static) -> Self {
         CompressionError(Box::new(error))
     }
 }
@@ -38,7 +38,7 @@ impl Display for CompressionError {
 }
 
 #[derive(ErrorMacro, Debug)]
-pub struct CacheAccessError(pub Box<dyn Error>);
+pub struct CacheAccessError(pub Box<dyn Error + Send + Sync>);
 
 impl Display for CacheAccessError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -47,10 +47,10 @@ impl Display for CacheAccessError {
 }
 
 #[derive(ErrorMacro, Debug)]
-pub struct SchemaError(pub Box<dyn Error>);
+pub struct SchemaError(pub Box<dyn Error + Send + Sync>);
 
 impl SchemaError {
-    fn wrap(error: impl Error + 'static) -> Self {
+    fn wrap(error: impl Error + Send + Sync + 'static) -> Self {
         SchemaError(Box::new(error))
     }
 
@@ -8,7 +8,9 @@ mod operators;
 mod transaction;
 mod query;
 
-pub use storage::{Storage};
+pub use storage::{Storage, storage_config::{SpillConfig, ExecutionConfig}};
+pub use collection::{IndexDefinition, IndexKeyDefinition, IndexKind, KeyRange, CollectionStatistics, ColumnStatistics, HistogramBucket};
+pub use query::{Query, condition::{Condition, Evaluable}, binary_expression::{BinaryExpression, BinaryExpressionType}, unary_expression::{UnaryExpression, UnaryExpressionType}};
 pub use objects::{ObjectField};
 pub use utils::DBResult;
 pub use storage::log_file::entry_fields::EntryFields;
@@ -18,7 +20,7 @@ pub use operators::*;
 
 #[cfg(test)]
 mod tests {
-    use crate::{DBOperator, ObjectField, Row};
+    use crate::{BinaryExpression, BinaryExpressionType, Condition, DBOperator, IndexKeyDefinition, IndexKind, JoinKind, JoinStrategy, KeyRange, constant_scan, Exchange, ExecutionConfig, BATCH_ROWS, RowBatch, ObjectField, Row, SortDirection, SpillConfig, OrderBy, NullsOrder, Collation, WindowFunction, WindowFrame, FrameBound};
     use std::{borrow::Cow, fs, time::Instant};
     use fakeit::{address::country, name};
     use log::{info};
@@ -26,7 +28,7 @@ mod tests {
     use serial_test::serial;
     use uuid::Uuid;
 
-    use crate::{storage::Storage, utils::DBResult};
+    use crate::{storage::{Storage, storage_config::DatabaseConfig}, utils::DBResult, EntryFields};
 
     #[cfg(test)]
     #[ctor::ctor]
@@ -126,6 +128,1697 @@ mod tests {
         collection.print_debug_info();
     }
 
+    #[test]
+    #[serial(engine)]
+    fn table_seek_test() {
+        wipe_log_files();
+        let mut engine = Storage::new().unwrap();
+        let mut collection = engine
+            .create_new_collection("table")
+            .unwrap()
+            .write()
+            .unwrap();
+
+        let (data, ids) = generate_sample_data();
+        collection.set_objects(Uuid::nil(), data).unwrap();
+
+        let rows = collection
+            .table_seek(Uuid::now_v7(), ids.iter().copied())
+            .collect()
+            .unwrap();
+        assert_eq!(rows.len(), ids.len());
+        assert!(rows.iter().zip(ids.iter()).all(|(row, id)| row.id == *id));
+
+        let updated: Vec<ObjectField> = vec!["Updated".into(), 27.into(), 1.5.into()];
+        collection.set_objects(Uuid::nil(), [
+            Row { id: ids[0], fields: updated.into() },
+            Row { id: ids[1], fields: Vec::<ObjectField>::new().into() }
+        ]).unwrap();
+
+        let row = collection.get(Uuid::now_v7(), ids[0]).unwrap().unwrap();
+        assert_eq!(row.fields.column(0), "Updated".into());
+        assert!(collection.get(Uuid::now_v7(), ids[1]).unwrap().is_none());
+        assert!(collection.get(Uuid::now_v7(), Uuid::new_v4()).unwrap().is_none());
+    }
+
+    #[test]
+    #[serial(engine)]
+    fn composite_and_expression_index_test() {
+        wipe_log_files();
+        let emails = ["Ann@Example.com", "bob@example.com", "CAROL@example.com"];
+        let data: Vec<Row> = (0..300)
+            .map(|i| {
+                let fields: Vec<ObjectField> = vec![
+                    ObjectField::I32(i % 3),
+                    ObjectField::I64(i64::from(1000 - i)),
+                    emails[(i % 3) as usize].into()
+                ];
+                Row { id: Uuid::new_v4(), fields: fields.into() }
+            })
+            .collect();
+        let lowercase_email = |row: &crate::EntryFields| -> ObjectField {
+            row.column(2).as_string().unwrap().to_lowercase().into()
+        };
+
+        {
+            let mut engine = Storage::new().unwrap();
+            let mut collection = engine
+                .create_new_collection("table")
+                .unwrap()
+                .write()
+                .unwrap();
+            collection.set_objects(Uuid::nil(), data).unwrap();
+
+            collection.create_index("tenant_created", IndexKind::Sorted { descending: false }, IndexKeyDefinition::Columns(vec![0, 1]), None).unwrap();
+            assert!(collection.create_index("email", IndexKind::Hash, IndexKeyDefinition::Expression("lower_email".to_owned()), None).is_err());
+            collection.register_key_expression("lower_email", lowercase_email).unwrap();
+            collection.create_index("email", IndexKind::Hash, IndexKeyDefinition::Expression("lower_email".to_owned()), None).unwrap();
+
+            let rows = collection
+                .index_seek(Uuid::now_v7(), "tenant_created", KeyRange::point(vec![1.into()]))
+                .unwrap()
+                .collect()
+                .unwrap();
+            assert_eq!(rows.len(), 100);
+            assert!(rows.iter().all(|row| row.fields.column(0) == 1.into()));
+            assert!(rows.windows(2).all(|w| w[0].fields.column(1) < w[1].fields.column(1)));
+
+            let rows = collection
+                .index_seek(Uuid::now_v7(), "tenant_created", KeyRange::new(
+                    std::ops::Bound::Included(vec![2.into(), ObjectField::I64(900)]),
+                    std::ops::Bound::Excluded(vec![2.into(), ObjectField::I64(950)])
+                ))
+                .unwrap()
+                .collect()
+                .unwrap();
+            assert!(rows.iter().all(|row| {
+                let created = row.fields.column(1).as_i64().unwrap();
+                row.fields.column(0) == 2.into() && (900..950).contains(&created)
+            }));
+            assert_eq!(rows.len(), 16);
+        }
+
+        let mut engine = Storage::new().unwrap();
+        let mut collection = engine
+            .create_collection("table")
+            .unwrap()
+            .write()
+            .unwrap();
+        assert_eq!(collection.index_definitions().count(), 2);
+        assert!(collection.index_seek(Uuid::now_v7(), "email", KeyRange::point(vec!["carol@example.com".into()])).is_err());
+        collection.register_key_expression("lower_email", lowercase_email).unwrap();
+
+        let rows = collection
+            .index_seek(Uuid::now_v7(), "email", KeyRange::point(vec!["carol@example.com".into()]))
+            .unwrap()
+            .collect()
+            .unwrap();
+        assert_eq!(rows.len(), 100);
+        assert_eq!(collection.index_scan(Uuid::now_v7(), "tenant_created").unwrap().collect().unwrap().len(), 300);
+    }
+
+    #[test]
+    #[serial(engine)]
+    fn index_seek_ranges_test() {
+        wipe_log_files();
+        let mut engine = Storage::new().unwrap();
+        let mut collection = engine
+            .create_new_collection("table")
+            .unwrap()
+            .write()
+            .unwrap();
+        let data: Vec<Row> = (0..1000)
+            .map(|i| Row { id: Uuid::new_v4(), fields: vec![ObjectField::I32(i % 100), ObjectField::I32(i)].into() })
+            .collect();
+        collection.set_objects(Uuid::nil(), data).unwrap();
+        collection.create_index("ascending", IndexKind::Sorted { descending: false }, IndexKeyDefinition::Columns(vec![0]), None).unwrap();
+        collection.create_index("descending", IndexKind::Sorted { descending: true }, IndexKeyDefinition::Columns(vec![0]), None).unwrap();
+
+        let ranges = || vec![
+            KeyRange::new(std::ops::Bound::Included(vec![10.into()]), std::ops::Bound::Excluded(vec![30.into()])),
+            KeyRange::new(std::ops::Bound::Included(vec![20.into()]), std::ops::Bound::Included(vec![40.into()])),
+            KeyRange::point(vec![35.into()]),
+            KeyRange::new(std::ops::Bound::Included(vec![90.into()]), std::ops::Bound::Unbounded)
+        ];
+        let keys = |index_name: &str| -> Vec<i32> {
+            collection
+                .index_seek_ranges(Uuid::now_v7(), index_name, ranges())
+                .unwrap()
+                .collect()
+                .unwrap()
+                .iter()
+                .map(|row| row.fields.column(0).as_i32().unwrap())
+                .collect()
+        };
+
+        let ascending = keys("ascending");
+        assert_eq!(ascending.len(), (31 + 10) * 10);
+        assert!(ascending.windows(2).all(|keys| keys[0] <= keys[1]));
+        assert!(ascending.iter().all(|key| (10..=40).contains(key) || *key >= 90));
+
+        let descending = keys("descending");
+        assert_eq!(descending.len(), ascending.len());
+        assert!(descending.windows(2).all(|keys| keys[0] >= keys[1]));
+    }
+
+    #[test]
+    #[serial(engine)]
+    fn partial_index_test() {
+        wipe_log_files();
+        let mut engine = Storage::new().unwrap();
+        let mut collection = engine
+            .create_new_collection("jobs")
+            .unwrap()
+            .write()
+            .unwrap();
+
+        let data: Vec<Row> = (0..1000)
+            .map(|i| {
+                let fields: Vec<ObjectField> = vec![
+                    ObjectField::I32(i),
+                    ObjectField::Bool(i % 10 == 0)
+                ];
+                Row { id: Uuid::new_v4(), fields: fields.into() }
+            })
+            .collect();
+        let first_id = data[0].id;
+        collection.set_objects(Uuid::nil(), data).unwrap();
+
+        let unprocessed = Condition::Binary(BinaryExpression {
+            expression_type: BinaryExpressionType::EqualTo,
+            first: Condition::ColumnIndex(1).into(),
+            second: Condition::Value(true.into()).into()
+        });
+        collection.create_index("unprocessed", IndexKind::Sorted { descending: false }, IndexKeyDefinition::Columns(vec![0]), Some(unprocessed.clone())).unwrap();
+
+        let rows = collection.index_scan(Uuid::now_v7(), "unprocessed").unwrap().collect().unwrap();
+        assert_eq!(rows.len(), 100);
+
+        // Predicates failing on the stored rows are rejected
+        assert!(collection.create_index("named", IndexKind::Hash, IndexKeyDefinition::Columns(vec![0]), Some(Condition::Column("unprocessed".into()))).is_err());
+        assert!(collection.index_scan(Uuid::now_v7(), "named").is_err());
+
+        // Processing the job makes it disappear from the index
+        let processed: Vec<ObjectField> = vec![ObjectField::I32(0), ObjectField::Bool(false)];
+        collection.set_objects(Uuid::nil(), [Row { id: first_id, fields: processed.into() }]).unwrap();
+        let rows = collection.index_scan(Uuid::now_v7(), "unprocessed").unwrap().collect().unwrap();
+        assert_eq!(rows.len(), 99);
+
+        let later_jobs = Condition::Binary(BinaryExpression {
+            expression_type: BinaryExpressionType::GreaterThanOrEqualTo,
+            first: Condition::ColumnIndex(0).into(),
+            second: Condition::Value(500.into()).into()
+        });
+        let conditions = Condition::And(crate::set![unprocessed, later_jobs.clone()]).conjuncts();
+        assert_eq!(collection.applicable_indexes(&conditions).count(), 1);
+        assert_eq!(collection.applicable_indexes(&later_jobs.conjuncts()).count(), 0);
+
+        #[derive(Deserialize)]
+        struct Job {
+            number: i32,
+            _unprocessed: bool
+        }
+        let jobs = collection
+            .query::<Job>(Uuid::now_v7())
+            .filter(Condition::And(crate::set![
+                Condition::Binary(BinaryExpression {
+                    expression_type: BinaryExpressionType::EqualTo,
+                    first: Condition::ColumnIndex(1).into(),
+                    second: Condition::Value(true.into()).into()
+                }),
+                later_jobs
+            ]))
+            .collect()
+            .unwrap();
+        assert_eq!(jobs.len(), 50);
+        assert!(jobs.iter().all(|job| job.number >= 500 && job.number % 10 == 0));
+    }
+
+    #[test]
+    #[serial(engine)]
+    fn statistics_test() {
+        wipe_log_files();
+        let data: Vec<Row> = (0..1000)
+            .map(|i| {
+                let fields: Vec<ObjectField> = vec![
+                    ObjectField::I32(i % 100),
+                    if i % 4 == 0 { ObjectField::Null } else { ObjectField::I64(i64::from(i)) }
+                ];
+                Row { id: Uuid::new_v4(), fields: fields.into() }
+            })
+            .collect();
+        let deleted_id = data[0].id;
+
+        {
+            let mut engine = Storage::new().unwrap();
+            let mut collection = engine
+                .create_new_collection("table")
+                .unwrap()
+                .write()
+                .unwrap();
+            // Overwriting the rows does not count them twice
+            let overwrites = data[..10].to_vec();
+            collection.set_objects(Uuid::nil(), data).unwrap();
+            collection.set_objects(Uuid::nil(), overwrites.clone()).unwrap();
+            collection.set_objects(Uuid::nil(), overwrites).unwrap();
+            collection.set_objects(Uuid::nil(), [Row { id: deleted_id, fields: Vec::<ObjectField>::new().into() }]).unwrap();
+
+            let statistics = collection.statistics();
+            assert_eq!(statistics.live_rows, 999);
+            assert!(statistics.columns.is_empty());
+            assert_eq!(collection.table_scan(Uuid::now_v7()).size_hint(), (0, Some(1000)));
+
+            let statistics = collection.analyze().unwrap();
+            assert_eq!(statistics.live_rows, 999);
+            assert!(statistics.distinct_values(0).unwrap().abs_diff(100) <= 5);
+            assert_eq!(statistics.columns[1].null_count, 249);
+            assert_eq!(statistics.columns[1].min, Some(ObjectField::I64(1)));
+            assert_eq!(statistics.columns[1].max, Some(ObjectField::I64(999)));
+            assert_eq!(statistics.columns[0].histogram.iter().map(|b| b.count).sum::<usize>(), 999);
+            let selectivity = statistics.selectivity_at_most(0, &ObjectField::I32(49)).unwrap();
+            assert!((selectivity - 0.5).abs() < 0.05);
+        }
+
+        let mut engine = Storage::new().unwrap();
+        let collection = engine
+            .create_collection("table")
+            .unwrap()
+            .read()
+            .unwrap();
+        let statistics = collection.statistics();
+        assert_eq!(statistics.live_rows, 999);
+        assert_eq!(statistics.columns[1].null_count, 249);
+        assert!(!statistics.columns[0].histogram.is_empty());
+
+        #[derive(Deserialize)]
+        struct NullableRow {
+            _a: i32,
+            b: Option<i64>
+        }
+        let rows = collection
+            .table_scan(Uuid::now_v7())
+            .deserialize::<NullableRow>()
+            .collect::<DBResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(rows.iter().filter(|row| row.b.is_none()).count(), 249);
+    }
+
+    #[test]
+    #[serial(engine)]
+    fn group_by_test() {
+        wipe_log_files();
+        let mut engine = Storage::new().unwrap();
+        let spill_config = engine.spill_config();
+        let mut collection = engine
+            .create_new_collection("table")
+            .unwrap()
+            .write()
+            .unwrap();
+
+        let data: Vec<Row> = (0..1000)
+            .map(|i| {
+                let fields: Vec<ObjectField> = vec![
+                    ObjectField::I32(i % 10),
+                    ObjectField::I64(i64::from(i)),
+                    if i % 5 == 0 { ObjectField::Null } else { format!("Value {}", i % 7).into() }
+                ];
+                Row { id: Uuid::new_v4(), fields: fields.into() }
+            })
+            .collect();
+        collection.set_objects(Uuid::nil(), data).unwrap();
+
+        let aggregate = |config: Option<SpillConfig>| {
+            let operator = collection
+                .table_scan(Uuid::now_v7())
+                .group_by(
+                    |row| vec![row.column(0)],
+                    |builder, row| builder
+                        .count()
+                        .sum_value(row.column(1))
+                        .avg_value(row.column(1))
+                        .max_value(row.column(1))
+                        .count_distinct(row.column(2))
+                );
+            let operator = match config {
+                Some(config) => operator.with_spill_config(config),
+                None => operator
+            };
+            let mut rows = operator.collect().unwrap();
+            rows.sort_by_key(|row| row.fields.column(0));
+            rows
+        };
+
+        let rows = aggregate(None);
+        assert_eq!(rows.len(), 10);
+        for (key, row) in rows.iter().enumerate() {
+            let key = key as i64;
+            assert_eq!(row.fields.column(0), ObjectField::I32(key as i32));
+            assert_eq!(row.fields.column(1), ObjectField::I64(100));
+            assert_eq!(row.fields.column(2), ObjectField::I64(100 * key + 49500));
+            assert_eq!(row.fields.column(3), ObjectField::Decimal(key as f64 + 495.0));
+            assert_eq!(row.fields.column(4), ObjectField::I64(990 + key));
+            // Keys 0 and 5 only contain the nulls
+            assert_eq!(row.fields.column(5), ObjectField::I64(if key % 5 == 0 { 0 } else { 7 }));
+        }
+
+        let spill_directory = spill_config.directory.clone().unwrap();
+        let spilled_rows = aggregate(Some(SpillConfig {
+            memory_budget: 256,
+            ..spill_config
+        }));
+        assert_eq!(spilled_rows.len(), 10);
+        assert!(spilled_rows.iter().zip(rows.iter()).all(|(a, b)| a.fields.to_string() == b.fields.to_string()));
+        assert!(spill_directory.is_dir());
+        assert_eq!(fs::read_dir(&spill_directory).unwrap().count(), 0);
+
+        // Without an explicit spill configuration, the one of the storage the rows are read from is used
+        let mut config = DatabaseConfig::load().unwrap();
+        config.log_file.destination = "./logfile/nested".into();
+        config.spill.memory_budget = 256;
+        fs::create_dir_all(&config.log_file.destination).unwrap();
+        let mut nested_engine = Storage::with_config(config);
+        let nested_spill_directory = nested_engine.spill_config().directory.unwrap();
+        let mut nested_collection = nested_engine
+            .create_new_collection("table")
+            .unwrap()
+            .write()
+            .unwrap();
+        nested_collection.set_objects(Uuid::nil(), collection.table_scan(Uuid::now_v7()).collect().unwrap()).unwrap();
+        let nested_rows = nested_collection
+            .table_scan(Uuid::now_v7())
+            .group_by(|row| vec![row.column(0)], |builder, _| builder.count())
+            .collect()
+            .unwrap();
+        assert_eq!(nested_rows.len(), 10);
+        assert!(nested_spill_directory.starts_with("./logfile/nested"));
+        assert!(nested_spill_directory.is_dir());
+        assert_eq!(fs::read_dir(&nested_spill_directory).unwrap().count(), 0);
+    }
+
+    #[test]
+    #[serial(engine)]
+    fn aggregate_functions_test() {
+        wipe_log_files();
+        let mut engine = Storage::new().unwrap();
+        let mut collection = engine
+            .create_new_collection("table")
+            .unwrap()
+            .write()
+            .unwrap();
+
+        let data: Vec<Row> = (0..20)
+            .map(|i| {
+                let fields: Vec<ObjectField> = vec![
+                    ObjectField::I32(i % 2),
+                    if i % 10 == 9 { ObjectField::Null } else { ObjectField::I32(i) },
+                    if i % 4 == 0 { ObjectField::I64(i64::from(i)) } else { ObjectField::I32(i) },
+                    format!("v{i}").into(),
+                    ObjectField::I64(i64::from(i))
+                ];
+                Row { id: Uuid::new_v4(), fields: fields.into() }
+            })
+            .collect();
+        collection.set_objects(Uuid::nil(), data).unwrap();
+
+        let scanned: Vec<ObjectField> = collection
+            .table_scan(Uuid::now_v7())
+            .collect()
+            .unwrap()
+            .iter()
+            .filter(|row| row.fields.column(0) == ObjectField::I32(1))
+            .map(|row| row.fields.column(3))
+            .collect();
+
+        let mut rows = collection
+            .table_scan(Uuid::now_v7())
+            .group_by(
+                |row| vec![row.column(0)],
+                |builder, row| builder
+                    .sum_value(row.column(2))
+                    .min_value(row.column(1))
+                    .max_value(row.column(1))
+                    .avg_value(row.column(1))
+                    .variance_value(row.column(1))
+                    .stddev_value(row.column(1))
+                    .median_value(row.column(1))
+                    .first_value(row.column(3))
+                    .last_value(row.column(3))
+                    .string_agg(row.column(3), ",")
+                    .array_agg(row.column(1))
+            )
+            .collect()
+            .unwrap();
+        rows.sort_by_key(|row| row.fields.column(0));
+        assert_eq!(rows.len(), 2);
+
+        let (even, odd) = (&rows[0].fields, &rows[1].fields);
+        assert_eq!(even.column(1), ObjectField::I64(90));
+        assert_eq!(odd.column(1), ObjectField::I32(100));
+        assert_eq!(even.column(2), ObjectField::I32(0));
+        assert_eq!(odd.column(2), ObjectField::I32(1));
+        assert_eq!(odd.column(3), ObjectField::I32(17));
+        assert_eq!(even.column(4), ObjectField::Decimal(9.0));
+        assert_eq!(odd.column(4), ObjectField::Decimal(9.0));
+        assert_eq!(even.column(5), ObjectField::Decimal(36.666_666_666_666_664));
+        assert_eq!(odd.column(5), ObjectField::Decimal(34.285_714_285_714_285));
+        assert_eq!(odd.column(6), ObjectField::Decimal(5.855_400_437_691_199));
+        assert_eq!(even.column(7), ObjectField::Decimal(9.0));
+        assert_eq!(odd.column(7), ObjectField::Decimal(9.0));
+        // FIRST, LAST and the concatenations follow the order of the input rows
+        assert_eq!(odd.column(8), scanned[0]);
+        assert_eq!(odd.column(9), scanned[9]);
+        let concatenated = scanned.iter().map(|value| value.as_string().unwrap().to_string()).collect::<Vec<_>>().join(",");
+        assert_eq!(odd.column(10), concatenated.into());
+
+        let array = odd.column(11).as_array().unwrap();
+        assert_eq!(array.len(), 10);
+        let mut values: Vec<ObjectField> = (0..array.len()).map(|i| array.column(i)).collect();
+        values.sort();
+        assert_eq!(values[..3], [ObjectField::Null, ObjectField::Null, ObjectField::I32(1)]);
+        assert_eq!(values[9], ObjectField::I32(17));
+        assert!(ObjectField::Bytes([3u8, 1].as_slice().into()).as_array().is_none());
+
+        // Integer sums overflowing I64 are promoted to Decimal
+        let overflowing = constant_scan(vec![vec![ObjectField::I64(i64::MAX)], vec![ObjectField::I32(1)]])
+            .group_by(|_| vec![], |builder, row| builder.sum_value(row.column(0)))
+            .collect()
+            .unwrap();
+        assert_eq!(overflowing[0].fields.column(0), ObjectField::Decimal(i64::MAX as f64 + 1.0));
+
+        // A global aggregate over no rows returns a single row, a grouped one no rows
+        let global = constant_scan(vec![])
+            .group_by(|_| vec![], |builder, row| builder.count().sum_value(row.column(0)).string_agg(row.column(1), ","))
+            .collect()
+            .unwrap();
+        assert_eq!(global.len(), 1);
+        assert_eq!(global[0].fields.to_string(), "Row<I64: 0, Null, Null>");
+        assert!(constant_scan(vec![]).group_by(|row| vec![row.column(0)], |builder, _| builder.count()).collect().unwrap().is_empty());
+
+        // Rows of a group with different aggregates fail the query
+        let mismatched = constant_scan(vec![vec![ObjectField::I32(1)], vec![ObjectField::I32(2)]])
+            .group_by(|_| vec![], |builder, row| if row.column(0) == ObjectField::I32(1) { builder.count() } else { builder.sum_value(row.column(0)) })
+            .collect();
+        assert!(mismatched.is_err());
+    }
+
+    #[test]
+    #[serial(engine)]
+    fn window_test() {
+        wipe_log_files();
+        let mut engine = Storage::new().unwrap();
+        let mut collection = engine
+            .create_new_collection("table")
+            .unwrap()
+            .write()
+            .unwrap();
+
+        let data: Vec<Row> = [("A", 1, Some(10)), ("A", 2, Some(20)), ("A", 2, Some(5)), ("A", 4, Some(7)), ("A", 5, None), ("B", 1, Some(1)), ("B", 2, Some(2)), ("B", 3, Some(3))]
+            .into_iter()
+            .map(|(partition, day, amount)| {
+                let fields: Vec<ObjectField> = vec![partition.into(), day.into(), amount.map_or(ObjectField::Null, ObjectField::I32)];
+                Row { id: Uuid::new_v4(), fields: fields.into() }
+            })
+            .collect();
+        collection.set_objects(Uuid::nil(), data).unwrap();
+
+        let rows = collection
+            .table_scan(Uuid::now_v7())
+            .window(
+                |row| vec![row.column(0)],
+                |row| vec![row.column(1)],
+                vec![
+                    WindowFunction::RowNumber,
+                    WindowFunction::Rank,
+                    WindowFunction::DenseRank,
+                    WindowFunction::Lag { column: 1, offset: 1, default: ObjectField::I32(-1) },
+                    WindowFunction::Lead { column: 1, offset: 1, default: ObjectField::Null },
+                    WindowFunction::Sum { column: 2, frame: WindowFrame::default() },
+                    WindowFunction::Sum { column: 2, frame: WindowFrame::range(FrameBound::Preceding(1), FrameBound::CurrentRow) },
+                    WindowFunction::Avg { column: 2, frame: WindowFrame::default() },
+                    WindowFunction::Sum { column: 2, frame: WindowFrame::rows(FrameBound::Preceding(1), FrameBound::Following(1)) }
+                ]
+            )
+            .collect()
+            .unwrap();
+        assert_eq!(rows.len(), 8);
+
+        let expected: [[ObjectField; 10]; 8] = [
+            [1.into(), 1i64.into(), 1i64.into(), 1i64.into(), (-1).into(), 2.into(), 10.into(), 10.into(), 10.0.into(), ObjectField::Null],
+            [2.into(), 2i64.into(), 2i64.into(), 2i64.into(), 1.into(), 2.into(), 35.into(), 35.into(), (35.0 / 3.0).into(), ObjectField::Null],
+            [2.into(), 3i64.into(), 2i64.into(), 2i64.into(), 2.into(), 4.into(), 35.into(), 35.into(), (35.0 / 3.0).into(), ObjectField::Null],
+            [4.into(), 4i64.into(), 4i64.into(), 3i64.into(), 2.into(), 5.into(), 42.into(), 7.into(), 10.5.into(), ObjectField::Null],
+            [5.into(), 5i64.into(), 5i64.into(), 4i64.into(), 4.into(), ObjectField::Null, 42.into(), 7.into(), 10.5.into(), ObjectField::Null],
+            [1.into(), 1i64.into(), 1i64.into(), 1i64.into(), (-1).into(), 2.into(), 1.into(), 1.into(), 1.0.into(), 3.into()],
+            [2.into(), 2i64.into(), 2i64.into(), 2i64.into(), 1.into(), 3.into(), 3.into(), 3.into(), 1.5.into(), 6.into()],
+            [3.into(), 3i64.into(), 3i64.into(), 3i64.into(), 2.into(), ObjectField::Null, 6.into(), 5.into(), 2.0.into(), 5.into()],
+        ];
+        for (row, expected) in rows.iter().zip(expected) {
+            assert_eq!(row.fields.len(), 12);
+            assert_eq!(row.fields.column(1), expected[0]);
+            for (column, value) in expected.into_iter().enumerate().skip(1) {
+                // Frames over ties depend on the order of the peers - checked on the partition without ties only
+                if column == 9 && row.fields.column(0) == "A".into() {
+                    continue;
+                }
+                assert_eq!(row.fields.column(column + 2), value, "Column {column} of {}", row.fields);
+            }
+        }
+
+        // Sums not fitting I32 are promoted to I64, then to Decimal
+        let sums = |values: Vec<ObjectField>| constant_scan(values.into_iter().map(|value| vec![value]).collect())
+            .window(|_| vec![], |_| vec![], vec![WindowFunction::Sum { column: 0, frame: WindowFrame::default() }])
+            .collect()
+            .unwrap()[0]
+            .fields
+            .column(1);
+        assert_eq!(sums(vec![ObjectField::I32(i32::MAX), ObjectField::I32(1)]), ObjectField::I64(i64::from(i32::MAX) + 1));
+        assert_eq!(sums(vec![ObjectField::I32(1), ObjectField::I64(2)]), ObjectField::I64(3));
+        assert_eq!(sums(vec![ObjectField::I64(i64::MAX), ObjectField::I64(i64::MAX)]), ObjectField::Decimal(i64::MAX as f64 * 2.0));
+    }
+
+    #[test]
+    #[serial(engine)]
+    fn external_sort_test() {
+        wipe_log_files();
+        let mut engine = Storage::new().unwrap();
+        let spill_config = engine.spill_config();
+        let mut collection = engine
+            .create_new_collection("table")
+            .unwrap()
+            .write()
+            .unwrap();
+
+        let data: Vec<Row> = (0..3000)
+            .map(|i| {
+                let fields: Vec<ObjectField> = vec![ObjectField::I32(i * 7919 % 1000), ObjectField::I32(i)];
+                Row { id: Uuid::new_v4(), fields: fields.into() }
+            })
+            .collect();
+        collection.set_objects(Uuid::nil(), data).unwrap();
+
+        let mut expected = collection.table_scan(Uuid::now_v7()).collect().unwrap();
+        expected.sort_by_key(|row| row.fields.column(0));
+        let expected_ids: Vec<Uuid> = expected.iter().map(|row| row.id).collect();
+
+        let in_memory: Vec<Uuid> = collection
+            .table_scan(Uuid::now_v7())
+            .external_sort(|row| row.column(0), SortDirection::Ascending)
+            .collect()
+            .unwrap()
+            .iter()
+            .map(|row| row.id)
+            .collect();
+        assert_eq!(in_memory, expected_ids);
+
+        // Small budget creates more runs than are merged at once
+        let small_budget = SpillConfig { memory_budget: 2000, ..spill_config.clone() };
+        let spilled: Vec<Uuid> = collection
+            .table_scan(Uuid::now_v7())
+            .external_sort(|row| row.column(0), SortDirection::Ascending)
+            .with_spill_config(small_budget.clone())
+            .collect()
+            .unwrap()
+            .iter()
+            .map(|row| row.id)
+            .collect();
+        assert_eq!(spilled, expected_ids);
+
+        let descending = collection
+            .table_scan(Uuid::now_v7())
+            .external_sort(|row| row.column(0), SortDirection::Descending)
+            .with_spill_config(small_budget)
+            .take(4)
+            .collect()
+            .unwrap();
+        let keys: Vec<ObjectField> = descending.iter().map(|row| row.fields.column(0)).collect();
+        assert_eq!(keys, [999.into(), 999.into(), 999.into(), 998.into()]);
+
+        let spill_directory = spill_config.directory.unwrap();
+        assert!(spill_directory.starts_with("./logfile"));
+        assert_eq!(fs::read_dir(&spill_directory).unwrap().count(), 0);
+    }
+
+    #[test]
+    #[serial(engine)]
+    fn top_n_test() {
+        wipe_log_files();
+        let mut engine = Storage::new().unwrap();
+        let mut collection = engine
+            .create_new_collection("table")
+            .unwrap()
+            .write()
+            .unwrap();
+
+        let data: Vec<Row> = (0..1000)
+            .map(|i| {
+                let fields: Vec<ObjectField> = vec![ObjectField::I32(i % 100), ObjectField::I32(i)];
+                Row { id: Uuid::new_v4(), fields: fields.into() }
+            })
+            .collect();
+        collection.set_objects(Uuid::nil(), data).unwrap();
+
+        let scanned = collection.table_scan(Uuid::now_v7()).collect().unwrap();
+        for (direction, count) in [(SortDirection::Ascending, 25), (SortDirection::Descending, 50), (SortDirection::Ascending, 0)] {
+            // Stable sorts keep the input order of the ties
+            let mut expected = scanned.clone();
+            match direction {
+                SortDirection::Ascending => expected.sort_by_key(|row| row.fields.column(0)),
+                SortDirection::Descending => expected.sort_by_key(|row| std::cmp::Reverse(row.fields.column(0)))
+            }
+            let expected_ids: Vec<Uuid> = expected.iter().take(count).map(|row| row.id).collect();
+
+            let top_n = collection
+                .table_scan(Uuid::now_v7())
+                .top_n(|row| row.column(0), direction, count);
+            assert_eq!(top_n.size_hint(), (0, Some(count)));
+            let ids: Vec<Uuid> = top_n.collect().unwrap().iter().map(|row| row.id).collect();
+            assert_eq!(ids, expected_ids);
+        }
+    }
+
+    #[test]
+    #[serial(engine)]
+    fn order_by_test() {
+        wipe_log_files();
+        let mut engine = Storage::new().unwrap();
+        let mut collection = engine
+            .create_new_collection("table")
+            .unwrap()
+            .write()
+            .unwrap();
+
+        let data: Vec<Row> = [("item10", Some(1)), ("Item2", Some(1)), ("item1", None), ("ITEM3", Some(2)), ("item20", Some(2)), ("item9", Some(1))]
+            .into_iter()
+            .map(|(name, group)| {
+                let fields: Vec<ObjectField> = vec![name.into(), group.map_or(ObjectField::Null, ObjectField::I32)];
+                Row { id: Uuid::new_v4(), fields: fields.into() }
+            })
+            .collect();
+        collection.set_objects(Uuid::nil(), data).unwrap();
+
+        let names = |rows: Vec<Row>| -> Vec<String> {
+            rows.iter().map(|row| row.fields.column(0).as_string().unwrap().to_string()).collect()
+        };
+
+        let ascending = OrderBy::new()
+            .column(1, SortDirection::Ascending).nulls(NullsOrder::Last)
+            .column(0, SortDirection::Ascending).collation(Collation::NaturalCaseInsensitive);
+        let rows = collection.table_scan(Uuid::now_v7()).order_by(ascending.clone()).collect().unwrap();
+        assert_eq!(names(rows), ["Item2", "item9", "item10", "ITEM3", "item20", "item1"]);
+
+        let rows = collection
+            .table_scan(Uuid::now_v7())
+            .external_sort(ascending.key_function(), SortDirection::Ascending)
+            .collect()
+            .unwrap();
+        assert_eq!(names(rows), ["Item2", "item9", "item10", "ITEM3", "item20", "item1"]);
+
+        let descending = OrderBy::new()
+            .column(1, SortDirection::Descending).nulls(NullsOrder::First)
+            .expression(|row| row.column(0), SortDirection::Descending).collation(Collation::CaseInsensitive);
+        let rows = collection
+            .table_scan(Uuid::now_v7())
+            .top_n(descending.key_function(), SortDirection::Ascending, 4)
+            .collect()
+            .unwrap();
+        assert_eq!(names(rows), ["item1", "ITEM3", "item20", "item9"]);
+
+        let rows = collection
+            .table_scan(Uuid::now_v7())
+            .in_memory_sort(|row| row.column(0), SortDirection::Ascending)
+            .collect()
+            .unwrap();
+        assert_eq!(names(rows), ["ITEM3", "Item2", "item1", "item10", "item20", "item9"]);
+    }
+
+    #[test]
+    #[serial(engine)]
+    fn radix_sort_test() {
+        wipe_log_files();
+        let mut engine = Storage::new().unwrap();
+        let mut collection = engine
+            .create_new_collection("table")
+            .unwrap()
+            .write()
+            .unwrap();
+
+        let data: Vec<Row> = (0..1000)
+            .map(|i| {
+                let fields: Vec<ObjectField> = vec![ObjectField::I32((i * 7919 % 1000) - 500), ObjectField::I64(i64::from(i % 10))];
+                Row { id: Uuid::new_v4(), fields: fields.into() }
+            })
+            .collect();
+        collection.set_objects(Uuid::nil(), data).unwrap();
+
+        let ids = |rows: Vec<Row>| -> Vec<Uuid> { rows.iter().map(|row| row.id).collect() };
+        for direction in [SortDirection::Ascending, SortDirection::Descending] {
+            // A tuple key is sorted by comparisons, keeping the same order of the ties
+            let compared = collection
+                .table_scan(Uuid::now_v7())
+                .in_memory_sort(|row| (row.column(1),), direction)
+                .collect()
+                .unwrap();
+            let radix_sorted = collection
+                .table_scan(Uuid::now_v7())
+                .radix_sort(|row| row.column(1).as_i64().unwrap(), direction)
+                .collect()
+                .unwrap();
+            let delegated = collection
+                .table_scan(Uuid::now_v7())
+                .in_memory_sort(|row| row.column(1), direction)
+                .collect()
+                .unwrap();
+            assert_eq!(ids(radix_sorted), ids(compared.clone()));
+            assert_eq!(ids(delegated), ids(compared));
+        }
+
+        // Keys borrowing from outside of the operator are sorted by comparisons
+        let labels = ["low", "high"];
+        let rows = collection
+            .table_scan(Uuid::now_v7())
+            .in_memory_sort(|row| labels[usize::from(row.column(0).as_i32().unwrap() >= 0)], SortDirection::Ascending)
+            .collect()
+            .unwrap();
+        assert!(rows[..500].iter().all(|row| row.fields.column(0).as_i32().unwrap() >= 0));
+    }
+
+    #[test]
+    #[serial(engine)]
+    fn merge_join_test() {
+        wipe_log_files();
+        let mut engine = Storage::new().unwrap();
+        for (name, count, modulo) in [("table", 1000, 50), ("table2", 200, 80)] {
+            let mut collection = engine
+                .create_new_collection(name)
+                .unwrap()
+                .write()
+                .unwrap();
+            let data: Vec<Row> = (0..count)
+                .map(|i| {
+                    let fields: Vec<ObjectField> = vec![ObjectField::I32(i % modulo), ObjectField::I32(i)];
+                    Row { id: Uuid::new_v4(), fields: fields.into() }
+                })
+                .collect();
+            collection.set_objects(Uuid::nil(), data).unwrap();
+        }
+
+        let transaction = Uuid::now_v7();
+        let table1 = engine.get_collection("table").unwrap().read().unwrap();
+        let table2 = engine.get_collection("table2").unwrap().read().unwrap();
+
+        let pairs = |rows: Vec<Row>| -> Vec<(i32, i32)> {
+            let mut pairs: Vec<(i32, i32)> = rows
+                .iter()
+                .map(|row| (row.fields.column(1).as_i32().unwrap(), row.fields.column(3).as_i32().unwrap()))
+                .collect();
+            pairs.sort();
+            pairs
+        };
+
+        let merged = table1
+            .table_scan(transaction)
+            .in_memory_sort(|row| row.column(0), SortDirection::Ascending)
+            .merge_join(
+                table2.table_scan(transaction).in_memory_sort(|row| row.column(0), SortDirection::Ascending),
+                |row| row.column(0),
+                |row| row.column(0)
+            )
+            .collect()
+            .unwrap();
+        let hashed = table1
+            .table_scan(transaction)
+            .hash_match(table2.table_scan(transaction), |row| row.column(0), |row| row.column(0), JoinKind::Inner)
+            .collect()
+            .unwrap();
+        // Every one of the 50 left keys appears 20 times, the right keys below 40 appear 3 times and the others twice
+        assert_eq!(merged.len(), 20 * (40 * 3 + 10 * 2));
+        assert!(merged.windows(2).all(|rows| rows[0].fields.column(0) <= rows[1].fields.column(0)));
+        assert_eq!(pairs(merged), pairs(hashed));
+
+        if cfg!(debug_assertions) {
+            let unsorted = table1
+                .table_scan(transaction)
+                .merge_join(table2.table_scan(transaction), |row| row.column(0), |row| row.column(0))
+                .collect();
+            assert!(unsorted.is_err());
+        }
+    }
+
+    #[test]
+    #[serial(engine)]
+    fn join_kinds_test() {
+        wipe_log_files();
+        let mut engine = Storage::new().unwrap();
+        for (name, keys) in [("table", [1, 2, 2, 3]), ("table2", [2, 3, 3, 4])] {
+            let mut collection = engine
+                .create_new_collection(name)
+                .unwrap()
+                .write()
+                .unwrap();
+            let data: Vec<Row> = keys
+                .into_iter()
+                .map(|key| {
+                    let fields: Vec<ObjectField> = vec![ObjectField::I32(key), name.into()];
+                    Row { id: Uuid::new_v4(), fields: fields.into() }
+                })
+                .collect();
+            collection.set_objects(Uuid::nil(), data).unwrap();
+        }
+
+        let transaction = Uuid::now_v7();
+        let table1 = engine.get_collection("table").unwrap().read().unwrap();
+        let table2 = engine.get_collection("table2").unwrap().read().unwrap();
+
+        let keys = |rows: Vec<Row>| -> Vec<(ObjectField, ObjectField)> {
+            let mut keys: Vec<_> = rows
+                .iter()
+                .map(|row| (row.fields.column(0), if row.fields.len() > 2 { row.fields.column(2) } else { ObjectField::Null }))
+                .collect();
+            keys.sort();
+            keys
+        };
+        let (null, i) = (ObjectField::Null, ObjectField::I32);
+        let expectations = [
+            (JoinKind::Inner, vec![(i(2), i(2)), (i(2), i(2)), (i(3), i(3)), (i(3), i(3))]),
+            (JoinKind::LeftOuter, vec![(i(1), null.clone()), (i(2), i(2)), (i(2), i(2)), (i(3), i(3)), (i(3), i(3))]),
+            (JoinKind::RightOuter, vec![(null.clone(), i(4)), (i(2), i(2)), (i(2), i(2)), (i(3), i(3)), (i(3), i(3))]),
+            (JoinKind::FullOuter, vec![(null.clone(), i(4)), (i(1), null.clone()), (i(2), i(2)), (i(2), i(2)), (i(3), i(3)), (i(3), i(3))]),
+            (JoinKind::Semi, vec![(i(2), null.clone()), (i(2), null.clone()), (i(3), null.clone())]),
+            (JoinKind::Anti, vec![(i(1), null.clone())])
+        ];
+        for (join_kind, expected) in expectations {
+            let nested_loop = table1
+                .table_scan(transaction)
+                .nested_loop(table2.table_scan(transaction), |a, b| a.column(0) == b.column(0), join_kind)
+                .collect()
+                .unwrap();
+            let hash_match = table1
+                .table_scan(transaction)
+                .hash_match(table2.table_scan(transaction), |row| row.column(0), |row| row.column(0), join_kind)
+                .collect()
+                .unwrap();
+            let width = if matches!(join_kind, JoinKind::Semi | JoinKind::Anti) { 2 } else { 4 };
+            assert!(nested_loop.iter().chain(hash_match.iter()).all(|row| row.fields.len() == width));
+            assert_eq!(keys(nested_loop), expected, "{:?}", join_kind);
+            assert_eq!(keys(hash_match), expected, "{:?}", join_kind);
+        }
+
+        // The inputs may not match at all - only the left and full outer joins return every row of the first input
+        let hints = [
+            (JoinKind::Inner, (0, Some(6))),
+            (JoinKind::LeftOuter, (3, Some(11))),
+            (JoinKind::RightOuter, (0, Some(11))),
+            (JoinKind::FullOuter, (3, Some(11))),
+            (JoinKind::Semi, (0, Some(3))),
+            (JoinKind::Anti, (0, Some(3)))
+        ];
+        for (join_kind, expected) in hints {
+            let first = || constant_scan(vec![vec![i(1)]; 3]);
+            let second = || constant_scan(vec![vec![i(2)]; 2]);
+            assert_eq!(first().nested_loop(second(), |_, _| false, join_kind).size_hint(), expected, "{:?}", join_kind);
+            assert_eq!(first().hash_match(second(), |row| row.column(0), |row| row.column(0), join_kind).size_hint(), expected, "{:?}", join_kind);
+        }
+    }
+
+    #[test]
+    #[serial(engine)]
+    fn grace_hash_join_test() {
+        wipe_log_files();
+        let mut engine = Storage::new().unwrap();
+        let spill_config = engine.spill_config();
+        for (name, count, modulo) in [("table", 2000, 300), ("table2", 1500, 400)] {
+            let mut collection = engine
+                .create_new_collection(name)
+                .unwrap()
+                .write()
+                .unwrap();
+            let data: Vec<Row> = (0..count)
+                .map(|i| {
+                    // Every 50th key is null, and a fifth of the rows share a single key
+                    let key = match i {
+                        i if i % 50 == 0 => ObjectField::Null,
+                        i if i % 5 == 0 => ObjectField::I32(7),
+                        i => ObjectField::I32(i % modulo)
+                    };
+                    let fields: Vec<ObjectField> = vec![key, ObjectField::I32(i)];
+                    Row { id: Uuid::new_v4(), fields: fields.into() }
+                })
+                .collect();
+            collection.set_objects(Uuid::nil(), data).unwrap();
+        }
+
+        let transaction = Uuid::now_v7();
+        let table1 = engine.get_collection("table").unwrap().read().unwrap();
+        let table2 = engine.get_collection("table2").unwrap().read().unwrap();
+
+        let join = |join_kind: JoinKind, config: SpillConfig| -> Vec<String> {
+            let mut rows: Vec<String> = table1
+                .table_scan(transaction)
+                .hash_match(table2.table_scan(transaction), |row| row.column(0), |row| row.column(0), join_kind)
+                .with_spill_config(config)
+                .collect()
+                .unwrap()
+                .iter()
+                .map(|row| format!("{} {}", row.id, row.fields))
+                .collect();
+            rows.sort();
+            rows
+        };
+        let small_budget = SpillConfig { memory_budget: 1000, ..spill_config.clone() };
+        for join_kind in [JoinKind::Inner, JoinKind::LeftOuter, JoinKind::RightOuter, JoinKind::FullOuter, JoinKind::Semi, JoinKind::Anti] {
+            let in_memory = join(join_kind, spill_config.clone());
+            assert!(!in_memory.is_empty());
+            assert_eq!(join(join_kind, small_budget.clone()), in_memory, "{:?}", join_kind);
+        }
+
+        let spill_directory = spill_config.directory.unwrap();
+        assert_eq!(fs::read_dir(&spill_directory).unwrap().count(), 0);
+    }
+
+    #[test]
+    #[serial(engine)]
+    fn adaptive_join_test() {
+        wipe_log_files();
+        let mut engine = Storage::new().unwrap();
+        for (name, count) in [("table", 500), ("small", 40), ("large", 300)] {
+            let mut collection = engine
+                .create_new_collection(name)
+                .unwrap()
+                .write()
+                .unwrap();
+            let data: Vec<Row> = (0..count)
+                .map(|i| {
+                    let key = if i % 13 == 0 { ObjectField::Null } else { ObjectField::I32(i % 70) };
+                    let fields: Vec<ObjectField> = vec![key, ObjectField::I32(i)];
+                    Row { id: Uuid::new_v4(), fields: fields.into() }
+                })
+                .collect();
+            collection.set_objects(Uuid::nil(), data).unwrap();
+        }
+
+        let transaction = Uuid::now_v7();
+        let table = engine.get_collection("table").unwrap().read().unwrap();
+        let sorted = |rows: Vec<Row>| -> Vec<String> {
+            let mut rows: Vec<String> = rows.iter().map(|row| format!("{} {}", row.id, row.fields)).collect();
+            rows.sort();
+            rows
+        };
+
+        for (other, strategy) in [("small", JoinStrategy::NestedLoop), ("large", JoinStrategy::HashMatch)] {
+            let other = engine.get_collection(other).unwrap().read().unwrap();
+            for join_kind in [JoinKind::Inner, JoinKind::LeftOuter, JoinKind::RightOuter, JoinKind::FullOuter, JoinKind::Semi, JoinKind::Anti] {
+                let mut adaptive = table
+                    .table_scan(transaction)
+                    .adaptive_join(other.table_scan(transaction), |row| row.column(0), |row| row.column(0), join_kind);
+                assert_eq!(adaptive.strategy(), None);
+                let mut rows = vec![];
+                while let Some(row) = adaptive.next().unwrap() {
+                    rows.push(row);
+                }
+                assert_eq!(adaptive.strategy(), Some(strategy));
+
+                let expected = table
+                    .table_scan(transaction)
+                    .hash_match(other.table_scan(transaction), |row| row.column(0), |row| row.column(0), join_kind)
+                    .collect()
+                    .unwrap();
+                assert_eq!(sorted(rows), sorted(expected), "{:?} {:?}", strategy, join_kind);
+
+                adaptive.reset();
+                assert_eq!(adaptive.strategy(), Some(strategy));
+            }
+        }
+
+        // A failing other input keeps both inputs, so that the join fails again instead of returning no rows
+        if cfg!(debug_assertions) {
+            let unsorted = constant_scan(vec![vec![ObjectField::I32(2)], vec![ObjectField::I32(1)]])
+                .merge_join(constant_scan(vec![vec![ObjectField::I32(1)], vec![ObjectField::I32(2)]]), |row| row.column(0), |row| row.column(0));
+            let mut adaptive = table
+                .table_scan(transaction)
+                .adaptive_join(unsorted, |row| row.column(0), |row| row.column(0), JoinKind::Inner);
+            assert!(adaptive.next().is_err());
+            assert_eq!(adaptive.strategy(), None);
+            adaptive.reset();
+            assert!(adaptive.next().is_err());
+        }
+    }
+
+    #[test]
+    #[serial(engine)]
+    fn composite_join_key_test() {
+        wipe_log_files();
+        let mut engine = Storage::new().unwrap();
+        let tenants = [Some(1), Some(2), None];
+        for (name, count) in [("users", 30), ("orders", 90)] {
+            let mut collection = engine
+                .create_new_collection(name)
+                .unwrap()
+                .write()
+                .unwrap();
+            let data: Vec<Row> = (0..count)
+                .map(|i| {
+                    let fields: Vec<ObjectField> = vec![
+                        tenants[i % 3].map_or(ObjectField::Null, ObjectField::I32),
+                        ObjectField::I32((i % 10) as i32),
+                        ObjectField::I32(i as i32)
+                    ];
+                    Row { id: Uuid::new_v4(), fields: fields.into() }
+                })
+                .collect();
+            collection.set_objects(Uuid::nil(), data).unwrap();
+        }
+
+        let transaction = Uuid::now_v7();
+        let users = engine.get_collection("users").unwrap().read().unwrap();
+        let orders = engine.get_collection("orders").unwrap().read().unwrap();
+        let key = |row: &EntryFields| (row.column(0), row.column(1));
+
+        let pairs = |rows: Vec<Row>| -> Vec<(ObjectField, ObjectField)> {
+            let mut pairs: Vec<_> = rows.iter().map(|row| (row.fields.column(2), row.fields.column(5))).collect();
+            pairs.sort();
+            pairs
+        };
+
+        let hashed = users
+            .table_scan(transaction)
+            .hash_match(orders.table_scan(transaction), key, key, JoinKind::Inner)
+            .collect()
+            .unwrap();
+        let merged = users
+            .table_scan(transaction)
+            .in_memory_sort(key, SortDirection::Ascending)
+            .merge_join(orders.table_scan(transaction).in_memory_sort(key, SortDirection::Ascending), key, key)
+            .collect()
+            .unwrap();
+        // Every (tenant, user) pair appears once in users and 3 times in orders - the 10 pairs with a null tenant never match
+        assert_eq!(hashed.len(), 20 * 3);
+        assert!(hashed.iter().all(|row| row.fields.column(0) == row.fields.column(3) && row.fields.column(1) == row.fields.column(4)));
+        assert_eq!(pairs(hashed), pairs(merged));
+
+        let vector_key = |row: &EntryFields| vec![row.column(0), row.column(1)];
+        let left_outer = users
+            .table_scan(transaction)
+            .hash_match(orders.table_scan(transaction), vector_key, vector_key, JoinKind::LeftOuter)
+            .collect()
+            .unwrap();
+        assert_eq!(left_outer.len(), 20 * 3 + 10);
+        assert_eq!(left_outer.iter().filter(|row| row.fields.column(3) == ObjectField::Null).count(), 10);
+    }
+
+    #[test]
+    #[serial(engine)]
+    fn range_join_test() {
+        wipe_log_files();
+        let mut engine = Storage::new().unwrap();
+        {
+            let mut intervals = engine
+                .create_new_collection("intervals")
+                .unwrap()
+                .write()
+                .unwrap();
+            let data: Vec<Row> = [(0, 10), (5, 15), (20, 30)]
+                .into_iter()
+                .map(|(start, end)| {
+                    let fields: Vec<ObjectField> = vec![ObjectField::I32(start), ObjectField::I32(end)];
+                    Row { id: Uuid::new_v4(), fields: fields.into() }
+                })
+                .collect();
+            intervals.set_objects(Uuid::nil(), data).unwrap();
+        }
+        {
+            let mut events = engine
+                .create_new_collection("events")
+                .unwrap()
+                .write()
+                .unwrap();
+            let data: Vec<Row> = (0..40)
+                .map(|ts| Row { id: Uuid::new_v4(), fields: vec![ObjectField::I32(ts)].into() })
+                .collect();
+            events.set_objects(Uuid::nil(), data).unwrap();
+        }
+
+        let transaction = Uuid::now_v7();
+        let intervals = engine.get_collection("intervals").unwrap().read().unwrap();
+        let events = engine.get_collection("events").unwrap().read().unwrap();
+
+        let rows = intervals
+            .table_scan(transaction)
+            .nested_loop(
+                events.table_scan(transaction),
+                |interval, event| interval.column(0) <= event.column(0) && event.column(0) < interval.column(1),
+                JoinKind::Inner
+            )
+            .collect()
+            .unwrap();
+        assert_eq!(rows.len(), 30);
+        assert!(rows.iter().all(|row| row.fields.column(0) <= row.fields.column(2) && row.fields.column(2) < row.fields.column(1)));
+
+        // Events outside of every interval
+        let outside = events
+            .table_scan(transaction)
+            .nested_loop(
+                intervals.table_scan(transaction),
+                |event, interval| interval.column(0) <= event.column(0) && event.column(0) < interval.column(1),
+                JoinKind::Anti
+            )
+            .collect()
+            .unwrap();
+        let mut timestamps: Vec<i32> = outside.iter().map(|row| row.fields.column(0).as_i32().unwrap()).collect();
+        timestamps.sort();
+        assert_eq!(timestamps, [15, 16, 17, 18, 19, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39]);
+    }
+
+    #[test]
+    #[serial(engine)]
+    fn spools_test() {
+        wipe_log_files();
+        let mut engine = Storage::new().unwrap();
+        for (name, count) in [("table", 200), ("table2", 50)] {
+            let mut collection = engine
+                .create_new_collection(name)
+                .unwrap()
+                .write()
+                .unwrap();
+            let data: Vec<Row> = (0..count)
+                .map(|i| {
+                    let fields: Vec<ObjectField> = vec![ObjectField::I32(i % 20), ObjectField::I32(i)];
+                    Row { id: Uuid::new_v4(), fields: fields.into() }
+                })
+                .collect();
+            collection.set_objects(Uuid::nil(), data).unwrap();
+        }
+
+        fn drain(operator: &mut impl DBOperator) -> Vec<Row> {
+            let mut rows = vec![];
+            while let Some(row) = operator.next().unwrap() {
+                rows.push(row);
+            }
+            rows
+        }
+        let sorted = |rows: Vec<Row>| -> Vec<String> {
+            let mut rows: Vec<String> = rows.iter().map(|row| format!("{} {}", row.id, row.fields)).collect();
+            rows.sort();
+            rows
+        };
+
+        let transaction = Uuid::now_v7();
+        let table1 = engine.get_collection("table").unwrap().read().unwrap();
+        let table2 = engine.get_collection("table2").unwrap().read().unwrap();
+        let expected = table2.table_scan(transaction).collect().unwrap();
+
+        // Reset in the middle of the first pass replays the cached rows, then continues the scan
+        let mut table_spool = table2.table_scan(transaction).table_spool();
+        let first_rows: Vec<Row> = (0..10).map(|_| table_spool.next().unwrap().unwrap()).collect();
+        table_spool.reset();
+        let all_rows = drain(&mut table_spool);
+        assert_eq!(all_rows.iter().take(10).map(|row| row.id).collect::<Vec<_>>(), first_rows.iter().map(|row| row.id).collect::<Vec<_>>());
+        table_spool.reset();
+        assert_eq!(table_spool.size_hint(), (50, Some(50)));
+        assert_eq!(sorted(drain(&mut table_spool)), sorted(expected.clone()));
+
+        let mut row_count_spool = table2.table_scan(transaction).filter(|row| row.column(0) == ObjectField::I32(3)).row_count_spool();
+        assert_eq!(drain(&mut row_count_spool).len(), 3);
+        row_count_spool.reset();
+        let counted = drain(&mut row_count_spool);
+        assert_eq!(counted.len(), 3);
+        assert!(counted.iter().all(|row| row.fields.is_empty()));
+
+        let equal_keys = |a: &EntryFields, b: &EntryFields| a.column(0) == b.column(0);
+        let joined = sorted(table1
+            .table_scan(transaction)
+            .nested_loop(table2.table_scan(transaction), equal_keys, JoinKind::Inner)
+            .collect()
+            .unwrap());
+        assert_eq!(joined.len(), 200 * 50 / 20);
+        let spooled = table1
+            .table_scan(transaction)
+            .nested_loop(table2.table_scan(transaction).table_spool(), equal_keys, JoinKind::Inner)
+            .collect()
+            .unwrap();
+        assert_eq!(sorted(spooled), joined);
+        let indexed = table1
+            .table_scan(transaction)
+            .nested_loop(
+                table2.table_scan(transaction).index_spool(|row| row.column(0), |outer_row| outer_row.column(0)),
+                |_, _| true,
+                JoinKind::Inner
+            )
+            .collect()
+            .unwrap();
+        assert_eq!(sorted(indexed), joined);
+
+        // Until rebound, the index spool returns all the rows
+        let mut index_spool = table2.table_scan(transaction).index_spool(|row| row.column(0), |outer_row| outer_row.column(0));
+        assert_eq!(drain(&mut index_spool).len(), 50);
+        index_spool.rebind(&vec![ObjectField::I32(7)].into());
+        assert_eq!(index_spool.size_hint(), (3, Some(3)));
+        assert!(drain(&mut index_spool).iter().all(|row| row.fields.column(0) == ObjectField::I32(7)));
+        index_spool.rebind(&vec![ObjectField::Null].into());
+        assert!(drain(&mut index_spool).is_empty());
+    }
+
+    #[test]
+    #[serial(engine)]
+    fn constant_scan_and_union_test() {
+        wipe_log_files();
+        let mut engine = Storage::new().unwrap();
+        for (name, start) in [("table", 0), ("table2", 30)] {
+            let mut collection = engine
+                .create_new_collection(name)
+                .unwrap()
+                .write()
+                .unwrap();
+            let data: Vec<Row> = (start..start + 50)
+                .map(|i| {
+                    let fields: Vec<ObjectField> = vec![ObjectField::I32(i), ObjectField::I32(i % 3)];
+                    Row { id: Uuid::new_v4(), fields: fields.into() }
+                })
+                .collect();
+            collection.set_objects(Uuid::nil(), data).unwrap();
+        }
+
+        let transaction = Uuid::now_v7();
+        let table1 = engine.get_collection("table").unwrap().read().unwrap();
+        let table2 = engine.get_collection("table2").unwrap().read().unwrap();
+
+        let lookup = constant_scan(vec![
+            vec![ObjectField::I32(0), "zero".into()],
+            vec![ObjectField::I32(1), "one".into()],
+            vec![ObjectField::I32(2), "two".into()]
+        ]);
+        assert_eq!(lookup.size_hint(), (3, Some(3)));
+        let named = table1
+            .table_scan(transaction)
+            .hash_match(lookup, |row| row.column(1), |lookup_row| lookup_row.column(0), JoinKind::Inner)
+            .filter(|row| row.column(0) == ObjectField::I32(7))
+            .collect()
+            .unwrap();
+        assert_eq!(named.len(), 1);
+        assert_eq!(named[0].fields.column(3), "one".into());
+
+        let all = table1.table_scan(transaction).union_all(table2.table_scan(transaction)).collect().unwrap();
+        assert_eq!(all.len(), 100);
+        let distinct = table1.table_scan(transaction).union(table2.table_scan(transaction)).collect().unwrap();
+        assert_eq!(distinct.len(), 80);
+        let keys = table1
+            .table_scan(transaction)
+            .select(|builder, _| builder.column(1))
+            .union(constant_scan(vec![vec![ObjectField::I32(2)], vec![ObjectField::I32(5)]]))
+            .collect()
+            .unwrap();
+        assert_eq!(keys.len(), 4);
+
+        let mismatched = table1.table_scan(transaction).concat(constant_scan(vec![vec![ObjectField::I32(1)]])).collect();
+        assert!(mismatched.is_err());
+    }
+
+    #[test]
+    #[serial(engine)]
+    fn distinct_test() {
+        wipe_log_files();
+        let mut engine = Storage::new().unwrap();
+        let spill_config = engine.spill_config();
+        let mut collection = engine
+            .create_new_collection("table")
+            .unwrap()
+            .write()
+            .unwrap();
+
+        let data: Vec<Row> = (0..300)
+            .map(|i| {
+                let fields: Vec<ObjectField> = vec![
+                    ObjectField::I32(i % 20),
+                    ObjectField::I32(i % 100),
+                    if i % 100 % 3 == 0 { ObjectField::Null } else { ObjectField::Bool(i % 2 == 0) }
+                ];
+                Row { id: Uuid::new_v4(), fields: fields.into() }
+            })
+            .collect();
+        collection.set_objects(Uuid::nil(), data).unwrap();
+
+        let transaction = Uuid::now_v7();
+        let sorted = |rows: Vec<Row>| -> Vec<String> {
+            let mut rows: Vec<String> = rows.iter().map(|row| row.fields.to_string()).collect();
+            rows.sort();
+            rows
+        };
+
+        // Rows 0-99 have the same values as rows 100-199 and 200-299, the first column has 20 keys, and the nulls are equal
+        let rows = collection.table_scan(transaction).distinct().collect().unwrap();
+        assert_eq!(rows.len(), 100);
+        let keys = collection.table_scan(transaction).distinct_by(|row| vec![row.column(0)]).collect().unwrap();
+        assert_eq!(keys.len(), 20);
+        let nullable = collection.table_scan(transaction).distinct_by(|row| vec![row.column(2)]).collect().unwrap();
+        assert_eq!(nullable.len(), 3);
+
+        let spill_directory = spill_config.directory.clone().unwrap();
+        let spilled_rows = collection
+            .table_scan(transaction)
+            .distinct()
+            .with_spill_config(SpillConfig {
+                memory_budget: 1000,
+                ..spill_config
+            })
+            .collect()
+            .unwrap();
+        assert_eq!(sorted(spilled_rows), sorted(rows));
+        assert!(spill_directory.is_dir());
+        assert_eq!(fs::read_dir(&spill_directory).unwrap().count(), 0);
+
+        let mut sorted_keys = collection
+            .table_scan(transaction)
+            .in_memory_sort(|row| row.column(0), SortDirection::Ascending)
+            .sorted_distinct_by(|row| vec![row.column(0)]);
+        assert_eq!(sorted_keys.clone().collect().unwrap().len(), 20);
+        sorted_keys.reset();
+        let first = sorted_keys.next().unwrap().unwrap();
+        assert_eq!(first.fields.column(0), ObjectField::I32(0));
+        // Duplicates which are not adjacent are not removed
+        let unsorted = collection.table_scan(transaction).sorted_distinct_by(|row| vec![row.column(0)]).collect().unwrap();
+        assert_eq!(unsorted.len(), 300);
+    }
+
+    #[test]
+    #[serial(engine)]
+    fn set_operations_test() {
+        wipe_log_files();
+        let mut engine = Storage::new().unwrap();
+        // Values 0-29 three times in the first table, values 0-24 twice in the second one
+        for (name, count, modulo) in [("table", 90, 30), ("table2", 50, 25)] {
+            let mut collection = engine
+                .create_new_collection(name)
+                .unwrap()
+                .write()
+                .unwrap();
+            let data: Vec<Row> = (0..count)
+                .map(|i| {
+                    let fields: Vec<ObjectField> = vec![ObjectField::I32(i % modulo), ObjectField::Null];
+                    Row { id: Uuid::new_v4(), fields: fields.into() }
+                })
+                .collect();
+            collection.set_objects(Uuid::nil(), data).unwrap();
+        }
+
+        let transaction = Uuid::now_v7();
+        let table1 = engine.get_collection("table").unwrap().read().unwrap();
+        let table2 = engine.get_collection("table2").unwrap().read().unwrap();
+        let ids: std::collections::HashSet<Uuid> = table1.table_scan(transaction).collect().unwrap().iter().map(|row| row.id).collect();
+        let values = |rows: Vec<Row>| -> Vec<i32> {
+            let mut values: Vec<i32> = rows.iter().map(|row| row.fields.column(0).as_i32().unwrap()).collect();
+            values.sort();
+            values
+        };
+
+        let intersected = table1.table_scan(transaction).intersect(table2.table_scan(transaction)).collect().unwrap();
+        assert!(intersected.iter().all(|row| ids.contains(&row.id)));
+        assert_eq!(values(intersected), (0..25).collect::<Vec<_>>());
+        let intersected_all = table1.table_scan(transaction).intersect_all(table2.table_scan(transaction)).collect().unwrap();
+        assert_eq!(values(intersected_all), (0..25).flat_map(|value| [value, value]).collect::<Vec<_>>());
+
+        let excepted = table1.table_scan(transaction).except(table2.table_scan(transaction)).collect().unwrap();
+        assert_eq!(values(excepted), (25..30).collect::<Vec<_>>());
+        let mut excepted_all = table1.table_scan(transaction).except_all(table2.table_scan(transaction));
+        assert_eq!(excepted_all.clone().collect().unwrap().len(), 25 + 5 * 3);
+        excepted_all.reset();
+        assert_eq!(excepted_all.collect().unwrap().len(), 40);
+
+        let nothing_excepted = table2.table_scan(transaction).except(table1.table_scan(transaction)).collect().unwrap();
+        assert!(nothing_excepted.is_empty());
+    }
+
+    #[test]
+    #[serial(engine)]
+    fn parallel_table_scan_test() {
+        wipe_log_files();
+        let mut config = DatabaseConfig::load().unwrap();
+        config.log_file.max_entries = 8192;
+        let mut engine = Storage::with_config(config);
+        let mut collection = engine
+            .create_new_collection("table")
+            .unwrap()
+            .write()
+            .unwrap();
+
+        // Spans three log files - the updates and deletes of the first rows are in the newest one
+        let ids: Vec<Uuid> = (0..20000).map(|_| Uuid::new_v4()).collect();
+        let rows = |range: std::ops::Range<usize>, sign: i32| -> Vec<Row> {
+            range
+                .map(|i| {
+                    let fields: Vec<ObjectField> = vec![ObjectField::I32(sign * i as i32), ObjectField::I32(i as i32 % 7)];
+                    Row { id: ids[i], fields: fields.into() }
+                })
+                .collect()
+        };
+        collection.set_objects(Uuid::nil(), rows(0..20000, 1)).unwrap();
+        collection.set_objects(Uuid::nil(), rows(0..100, -1)).unwrap();
+        let deleted: Vec<Row> = ids[100..200]
+            .iter()
+            .map(|id| Row { id: *id, fields: Vec::<ObjectField>::new().into() })
+            .collect();
+        collection.set_objects(Uuid::nil(), deleted).unwrap();
+        collection.set_objects(Uuid::now_v7(), rows(200..300, -1)).unwrap();
+        assert_eq!(collection.last_file_index, 2);
+
+        let transaction = Uuid::now_v7();
+        let sorted = |rows: Vec<Row>| -> Vec<String> {
+            let mut rows: Vec<String> = rows.iter().map(|row| format!("{} {}", row.id, row.fields)).collect();
+            rows.sort();
+            rows
+        };
+        let expected = collection.table_scan(transaction).collect().unwrap();
+        assert_eq!(expected.len(), 19900);
+        let mut parallel_scan = collection.parallel_table_scan(transaction);
+        let initial_hint = parallel_scan.size_hint();
+        assert_eq!(initial_hint.0, 0);
+        let rows = parallel_scan.clone().collect().unwrap();
+        assert_eq!(sorted(rows), sorted(expected.clone()));
+        for threads in [1, 2] {
+            let rows = collection
+                .parallel_table_scan(transaction)
+                .with_execution_config(ExecutionConfig { threads: Some(threads) })
+                .collect()
+                .unwrap();
+            assert_eq!(sorted(rows), sorted(expected.clone()));
+        }
+        let updated = parallel_scan
+            .clone()
+            .filter(|row| row.column(0).as_i32().unwrap() < 0)
+            .collect()
+            .unwrap();
+        assert_eq!(updated.len(), 99);
+
+        // Stopping in the middle and resetting returns all the rows again
+        for _ in 0..10 {
+            parallel_scan.next().unwrap().unwrap();
+        }
+        parallel_scan.reset();
+        assert_eq!(parallel_scan.size_hint(), initial_hint);
+        assert_eq!(sorted(parallel_scan.collect().unwrap()), sorted(expected));
+
+        let exchange = Exchange::new(vec![
+            constant_scan(vec![vec![ObjectField::I32(1)], vec![ObjectField::I32(2)]]),
+            constant_scan(vec![vec![ObjectField::I32(3)]])
+        ]);
+        assert_eq!(exchange.size_hint(), (3, Some(3)));
+        let mut values: Vec<i32> = exchange
+            .with_execution_config(ExecutionConfig { threads: Some(1) })
+            .collect().unwrap().iter().map(|row| row.fields.column(0).as_i32().unwrap()).collect();
+        values.sort();
+        assert_eq!(values, vec![1, 2, 3]);
+    }
+
+    #[test]
+    #[serial(engine)]
+    fn parallel_pipeline_test() {
+        wipe_log_files();
+        let mut engine = Storage::new().unwrap();
+        let execution_config = engine.execution_config();
+        let mut collection = engine
+            .create_new_collection("table")
+            .unwrap()
+            .write()
+            .unwrap();
+
+        let data: Vec<Row> = (0..50000)
+            .map(|i| {
+                let fields: Vec<ObjectField> = vec![
+                    ObjectField::I32(i % 100),
+                    ObjectField::I64(i64::from(i)),
+                    ObjectField::Bool(i % 3 == 0)
+                ];
+                Row { id: Uuid::new_v4(), fields: fields.into() }
+            })
+            .collect();
+        collection.set_objects(Uuid::nil(), data).unwrap();
+
+        let transaction = Uuid::now_v7();
+        let sorted = |rows: Vec<Row>| -> Vec<String> {
+            let mut rows: Vec<String> = rows.iter().map(|row| row.fields.to_string()).collect();
+            rows.sort();
+            rows
+        };
+
+        let expected = collection
+            .table_scan(transaction)
+            .filter(|row| row.column(2).as_bool().unwrap())
+            .select(|builder, _| builder.column(0).column(1))
+            .collect()
+            .unwrap();
+        assert_eq!(expected.len(), 16667);
+        for threads in [1, 4] {
+            let rows = collection
+                .table_scan(transaction)
+                .parallel(|morsel| morsel
+                    .filter(|row| row.column(2).as_bool().unwrap())
+                    .select(|builder, _| builder.column(0).column(1))
+                )
+                .with_execution_config(ExecutionConfig { threads: Some(threads) })
+                .collect()
+                .unwrap();
+            assert_eq!(sorted(rows), sorted(expected.clone()));
+        }
+
+        let expected_groups = collection
+            .table_scan(transaction)
+            .filter(|row| row.column(2).as_bool().unwrap())
+            .group_by(|row| vec![row.column(0)], |builder, row| builder.count().sum_value(row.column(1)).max_value(row.column(1)))
+            .collect()
+            .unwrap();
+        let mut groups = collection
+            .table_scan(transaction)
+            .parallel_group_by(
+                |morsel| morsel.filter(|row| row.column(2).as_bool().unwrap()),
+                |row| vec![row.column(0)],
+                |builder, row| builder.count().sum_value(row.column(1)).max_value(row.column(1))
+            )
+            .with_execution_config(execution_config);
+        assert_eq!(sorted(groups.clone().collect().unwrap()), sorted(expected_groups.clone()));
+        groups.reset();
+        assert_eq!(groups.collect().unwrap().len(), 100);
+
+        let global = collection
+            .table_scan(transaction)
+            .parallel_group_by(|morsel| morsel.filter(|_| false), |_| vec![], |builder, row| builder.count().max_value(row.column(1)))
+            .collect()
+            .unwrap();
+        assert_eq!(global.len(), 1);
+        assert_eq!(global[0].fields.to_string(), "Row<I64: 0, Null>");
+    }
+
+    #[test]
+    #[serial(engine)]
+    fn batch_execution_test() {
+        wipe_log_files();
+        let mut config = DatabaseConfig::load().unwrap();
+        config.log_file.max_entries = 8192;
+        let mut engine = Storage::with_config(config);
+        let mut collection = engine
+            .create_new_collection("table")
+            .unwrap()
+            .write()
+            .unwrap();
+
+        let ids: Vec<Uuid> = (0..20000).map(|_| Uuid::new_v4()).collect();
+        let rows = |range: std::ops::Range<usize>, name: &str| -> Vec<Row> {
+            range
+                .map(|i| {
+                    let fields: Vec<ObjectField> = vec![ObjectField::I32(i as i32 % 50), format!("{name} {i}").into(), ObjectField::I64(i as i64)];
+                    Row { id: ids[i], fields: fields.into() }
+                })
+                .collect()
+        };
+        collection.set_objects(Uuid::nil(), rows(0..20000, "Row")).unwrap();
+        collection.set_objects(Uuid::nil(), rows(0..100, "Updated")).unwrap();
+
+        fn drain(mut operator: impl DBOperator) -> Vec<String> {
+            let mut rows = vec![];
+            while let Some(row) = operator.next().unwrap() {
+                rows.push(row.fields.to_string());
+            }
+            rows
+        }
+        let batched = |operator: &dyn Fn() -> Vec<Row>| -> Vec<String> {
+            operator().iter().map(|row| row.fields.to_string()).collect()
+        };
+
+        let transaction = Uuid::now_v7();
+        let mut scan = collection.table_scan(transaction);
+        let batch = scan.next_batch().unwrap();
+        assert_eq!(batch.len(), BATCH_ROWS);
+        assert_eq!(batch.columns().len(), 3);
+        assert!(batch.rows().iter().zip(batch.column(1)).all(|(row, value)| row.fields.column(1) == *value));
+        let mut batches = 1;
+        while !scan.next_batch().unwrap().is_empty() {
+            batches += 1;
+        }
+        assert_eq!(batches, 20000usize.div_ceil(BATCH_ROWS));
+
+        let rows = drain(collection.table_scan(transaction));
+        assert_eq!(rows.len(), 20000);
+        assert_eq!(batched(&|| collection.table_scan(transaction).collect().unwrap()), rows);
+
+        let pipeline = || collection
+            .table_scan(transaction)
+            .filter(|row| row.column(0) == ObjectField::I32(7))
+            .select(|builder, row| builder.column(1).value(row.column(2).as_i64().unwrap() * 2));
+        assert_eq!(batched(&|| pipeline().collect().unwrap()), drain(pipeline()));
+
+        // The column-based predicate and selector return the same rows as the functions of the fields
+        let columnar = || collection
+            .table_scan(transaction)
+            .filter_column(0, |value| *value == ObjectField::I32(7))
+            .select_columns(&[1, 2]);
+        let expected = batched(&|| collection
+            .table_scan(transaction)
+            .filter(|row| row.column(0) == ObjectField::I32(7))
+            .select(|builder, _| builder.column(1).column(2))
+            .collect()
+            .unwrap());
+        assert_eq!(expected.len(), 400);
+        assert_eq!(batched(&|| columnar().collect().unwrap()), expected);
+        assert_eq!(drain(columnar()), expected);
+        let mut filtered = collection
+            .table_scan(transaction)
+            .filter_column(0, |value| *value == ObjectField::I32(7));
+        let batch = filtered.next_batch().unwrap();
+        assert!(batch.rows().iter().zip(batch.column(2)).all(|(row, value)| row.fields.column(2) == *value));
+        let batch = columnar().next_batch().unwrap();
+        assert_eq!(batch.columns().len(), 2);
+        assert!(batch.rows().iter().zip(batch.column(1)).all(|(row, value)| row.fields.column(1) == *value));
+
+        // Columns missing from the rows are nulls in both modes, also in empty batches
+        let narrow = || constant_scan(vec![vec![ObjectField::I32(1)], vec![ObjectField::I32(2), ObjectField::Bool(true)]])
+            .filter_column(1, |value| value.is_null())
+            .select_columns(&[0, 1, 5]);
+        assert_eq!(drain(narrow()), vec!["Row<I32: 1, Null, Null>".to_owned()]);
+        assert_eq!(batched(&|| narrow().collect().unwrap()), drain(narrow()));
+        let batch = RowBatch::default();
+        assert!(batch.column(5).is_empty());
+        let batch = RowBatch::new(constant_scan(vec![vec![ObjectField::I32(1), "a".into()], vec![ObjectField::I32(2)]]).collect().unwrap());
+        assert_eq!(batch.columns_of(&[1, 3]), vec![&["a".into(), ObjectField::Null][..], &[ObjectField::Null, ObjectField::Null][..]]);
+
+        let aggregate = || collection
+            .table_scan(transaction)
+            .select(|builder, row| builder.count().max_value(row.column(2)));
+        let aggregated = aggregate().collect().unwrap();
+        assert_eq!(aggregated.len(), 1);
+        assert_eq!(aggregated[0].fields.column(0), ObjectField::I64(20000));
+        assert_eq!(aggregated[0].fields.column(1), ObjectField::I64(19999));
+        assert_eq!(drain(aggregate()).len(), 1);
+
+        let join = || collection
+            .table_scan(transaction)
+            .hash_match(
+                constant_scan((0..10).map(|key| vec![ObjectField::I32(key), ObjectField::Bool(key % 2 == 0)]).collect()),
+                |row| row.column(0),
+                |lookup_row| lookup_row.column(0),
+                JoinKind::Inner
+            );
+        let joined = drain(join());
+        assert_eq!(joined.len(), 4000);
+        assert_eq!(batched(&|| join().collect().unwrap()), joined);
+    }
+
     #[test]
     #[serial(engine)]
     fn nested_loop_test() {
@@ -197,7 +1890,7 @@ mod tests {
 
         let rows = table1
             .table_scan(transaction)
-            .nested_loop(table2.table_scan(transaction), 3, 0)
+            .nested_loop(table2.table_scan(transaction), |a, b| a.column(3) == b.column(0), JoinKind::Inner)
             .collect()
             .unwrap();
 
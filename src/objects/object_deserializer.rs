@@ -42,6 +42,7 @@ impl<'de> Deserializer<'de> for &mut ObjectDeserializer {
             Some(ObjectField::Id(id)) => visitor.visit_bytes(id.as_bytes()),
             Some(ObjectField::Bytes(b)) => visitor.visit_bytes(b),
             Some(ObjectField::String(s)) => visitor.visit_str(s),
+            Some(ObjectField::Null) => visitor.visit_none(),
             None => Err(DeserializerError::missing_field("Error - no more columns in row")),
         }
     }
@@ -57,6 +58,7 @@ impl<'de> Deserializer<'de> for &mut ObjectDeserializer {
                 ObjectField::Id(_) => Err(DeserializerError::invalid_type(Unexpected::Other("uuid"), &visitor)),
                 ObjectField::Bytes(bytes) => Err(DeserializerError::invalid_type(Unexpected::Bytes(bytes), &visitor)),
                 ObjectField::String(str) => Err(DeserializerError::invalid_type(Unexpected::Str(str), &visitor)),
+                ObjectField::Null => Err(DeserializerError::invalid_type(Unexpected::Unit, &visitor)),
             },
             None => Err(DeserializerError::missing_field("Error - no more columns in row"))
         }
@@ -73,6 +75,7 @@ impl<'de> Deserializer<'de> for &mut ObjectDeserializer {
                 ObjectField::Id(_) => Err(DeserializerError::invalid_type(Unexpected::Other("uuid"), &visitor)),
                 ObjectField::Bytes(bytes) => Err(DeserializerError::invalid_type(Unexpected::Bytes(bytes), &visitor)),
                 ObjectField::String(str) => Err(DeserializerError::invalid_type(Unexpected::Str(str), &visitor)),
+                ObjectField::Null => Err(DeserializerError::invalid_type(Unexpected::Unit, &visitor)),
             },
             None => Err(DeserializerError::missing_field("Error - no more columns in row"))
         }
@@ -89,6 +92,7 @@ impl<'de> Deserializer<'de> for &mut ObjectDeserializer {
                 ObjectField::Id(_) => Err(DeserializerError::invalid_type(Unexpected::Other("uuid"), &visitor)),
                 ObjectField::Bytes(bytes) => Err(DeserializerError::invalid_type(Unexpected::Bytes(bytes), &visitor)),
                 ObjectField::String(str) => Err(DeserializerError::invalid_type(Unexpected::Str(str), &visitor)),
+                ObjectField::Null => Err(DeserializerError::invalid_type(Unexpected::Unit, &visitor)),
             },
             None => Err(DeserializerError::missing_field("Error - no more columns in row"))
         }
@@ -105,6 +109,7 @@ impl<'de> Deserializer<'de> for &mut ObjectDeserializer {
                 ObjectField::Id(_) => Err(DeserializerError::invalid_type(Unexpected::Other("uuid"), &visitor)),
                 ObjectField::Bytes(bytes) => Err(DeserializerError::invalid_type(Unexpected::Bytes(bytes), &visitor)),
                 ObjectField::String(str) => Err(DeserializerError::invalid_type(Unexpected::Str(str), &visitor)),
+                ObjectField::Null => Err(DeserializerError::invalid_type(Unexpected::Unit, &visitor)),
             },
             None => Err(DeserializerError::missing_field("Error - no more columns in row"))
         }
@@ -121,6 +126,7 @@ impl<'de> Deserializer<'de> for &mut ObjectDeserializer {
                 ObjectField::Id(_) => Err(DeserializerError::invalid_type(Unexpected::Other("uuid"), &visitor)),
                 ObjectField::Bytes(bytes) => Err(DeserializerError::invalid_type(Unexpected::Bytes(bytes), &visitor)),
                 ObjectField::String(str) => visitor.visit_str(str),
+                ObjectField::Null => Err(DeserializerError::invalid_type(Unexpected::Unit, &visitor)),
             },
             None => Err(DeserializerError::missing_field("Error - no more columns in row"))
         }
@@ -137,6 +143,7 @@ impl<'de> Deserializer<'de> for &mut ObjectDeserializer {
                 ObjectField::Id(_) => Err(DeserializerError::invalid_type(Unexpected::Other("uuid"), &visitor)),
                 ObjectField::Bytes(bytes) => Err(DeserializerError::invalid_type(Unexpected::Bytes(bytes), &visitor)),
                 ObjectField::String(str) => visitor.visit_string(str.to_string()),
+                ObjectField::Null => Err(DeserializerError::invalid_type(Unexpected::Unit, &visitor)),
             },
             None => Err(DeserializerError::missing_field("Error - no more columns in row"))
         }
@@ -153,11 +160,24 @@ impl<'de> Deserializer<'de> for &mut ObjectDeserializer {
                 ObjectField::Id(uuid) => visitor.visit_bytes(uuid.as_bytes()),
                 ObjectField::Bytes(bytes) => visitor.visit_bytes(bytes),
                 ObjectField::String(str) => visitor.visit_bytes(str.as_bytes()),
+                ObjectField::Null => Err(DeserializerError::invalid_type(Unexpected::Unit, &visitor)),
             },
             None => Err(DeserializerError::missing_field("Error - no more columns in row"))
         }
     }
 
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where V: serde::de::Visitor<'de> {
+        match self.data.get().get(self.index) {
+            Some(ObjectField::Null) => {
+                self.index += 1;
+                visitor.visit_none()
+            },
+            Some(_) => visitor.visit_some(self),
+            None => Err(DeserializerError::missing_field("Error - no more columns in row"))
+        }
+    }
+
     fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where V: serde::de::Visitor<'de> {
         visitor.visit_seq(self)
@@ -180,7 +200,7 @@ impl<'de> Deserializer<'de> for &mut ObjectDeserializer {
 
     forward_to_deserialize_any! {
         i8 i16 i128 u8 u16 u32 u64 u128 f32 char
-        byte_buf option unit unit_struct newtype_struct tuple
+        byte_buf unit unit_struct newtype_struct tuple
         tuple_struct map enum identifier ignored_any
     }
 }
@@ -1,17 +1,21 @@
-use std::{borrow::Cow, cmp::Ordering, fmt::Display, hash::Hash, rc::Rc};
+use std::{borrow::Cow, cmp::Ordering, fmt::Display, hash::Hash, sync::Arc};
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::EntryFields;
+
 type Decimal = f64;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum ObjectField {
     Bool(bool),
     I32(i32),
     I64(i64),
     Decimal(Decimal),
     Id(Uuid),
-    Bytes(Rc<[u8]>),
-    String(Rc<str>),
+    Bytes(Arc<[u8]>),
+    String(Arc<str>),
+    Null,
 }
 
 #[derive(PartialEq, Eq, Debug, Clone, Copy)]
@@ -24,9 +28,31 @@ pub enum FieldType {
     Id = 4,
     Bytes = 5,
     String = 6,
+    Null = 7,
 }
 
 impl ObjectField {
+    pub fn is_null(&self) -> bool {
+        matches!(self, ObjectField::Null)
+    }
+
+    /// Reads the byte array as a list of fields, as produced by [`crate::SelectBuilder::array_agg`]
+    pub fn as_array(&self) -> Option<EntryFields> {
+        match self {
+            ObjectField::Bytes(bytes) if EntryFields::is_well_formed(bytes) => Some(EntryFields(0..bytes.len(), bytes.clone())),
+            _ => None
+        }
+    }
+
+    /// Approximate number of bytes held by the field, including its heap allocation
+    pub(crate) fn memory_size(&self) -> usize {
+        std::mem::size_of::<Self>() + match self {
+            ObjectField::Bytes(bytes) => bytes.len(),
+            ObjectField::String(string) => string.len(),
+            _ => 0
+        }
+    }
+
     pub fn as_bool(&self) -> Option<bool> {
         if let ObjectField::Bool(b) = self {
             Some(*b)
@@ -67,7 +93,7 @@ impl ObjectField {
         }
     }
 
-    pub fn as_bytes(&self) -> Option<Rc<[u8]>> {
+    pub fn as_bytes(&self) -> Option<Arc<[u8]>> {
         if let ObjectField::Bytes(bytes) = self {
             Some(bytes.clone())
         } else {
@@ -75,7 +101,7 @@ impl ObjectField {
         }
     }
 
-    pub fn as_string(&self) -> Option<Rc<str>> {
+    pub fn as_string(&self) -> Option<Arc<str>> {
         if let ObjectField::String(string) = self {
             Some(string.clone())
         } else {
@@ -100,6 +126,7 @@ impl PartialEq for ObjectField {
             (Self::Id(l0), Self::Id(r0)) => l0 == r0,
             (Self::Bytes(l0), Self::Bytes(r0)) => l0 == r0,
             (Self::String(l0), Self::String(r0)) => l0 == r0,
+            (Self::Null, Self::Null) => true,
             _ => false,
         }
     }
@@ -116,6 +143,10 @@ impl PartialOrd for ObjectField {
 impl Ord for ObjectField {
     fn cmp(&self, other: &Self) -> Ordering {
         match (self, other) {
+            (Self::Null, Self::Null) => Ordering::Equal,
+            (Self::Null, _) => Ordering::Less,
+            (_, Self::Null) => Ordering::Greater,
+
             (Self::Bool(b1), Self::Bool(b2)) => b1.cmp(b2),
             (Self::Bool(_), _) => Ordering::Less,
 
@@ -151,6 +182,7 @@ impl Display for ObjectField {
             ObjectField::Id(id) => f.write_fmt(format_args!("Id: \"{}\"", id)),
             ObjectField::Bytes(b) => f.write_fmt(format_args!("Bytes: \"{:?}\"", b)),
             ObjectField::String(s) => f.write_fmt(format_args!("String: \"{}\"", s)),
+            ObjectField::Null => f.write_str("Null"),
         }
     }
 }
@@ -166,6 +198,7 @@ impl Hash for ObjectField {
             ObjectField::Id(uuid) => uuid.hash(state),
             ObjectField::Bytes(cow) => cow.hash(state),
             ObjectField::String(cow) => cow.hash(state),
+            ObjectField::Null => { },
         };
     }
 }
@@ -0,0 +1,173 @@
+use std::hash::{Hash, Hasher};
+
+use gxhash::{GxHasher, HashMap, HashMapExt};
+use uuid::Uuid;
+
+use crate::{operators::spill_file::{SpillFile, SpillReader}, storage::storage_config::SpillConfig, DBOperator, DBResult, EntryFields, ObjectField, Row, SelectBuilder, SelectField};
+use crate::storage::storage_config::DatabaseConfig;
+
+/// Number of files the groups not fitting in the memory are partitioned into
+const SPILL_PARTITIONS: usize = 16;
+
+type Group = (Vec<ObjectField>, Vec<SelectField>);
+
+/// Returns the single group of a global aggregate (one without key columns) over no rows, like COUNT = 0.
+/// Without any row, the key and the aggregates are evaluated on a row of nulls, only to find out their shape.
+pub(crate) fn empty_group<KeyFunction, Aggregates>(key_function: &KeyFunction, aggregates: &Aggregates) -> Option<Group>
+where   KeyFunction: Fn(&EntryFields) -> Vec<ObjectField>,
+        Aggregates: for<'x> Fn(SelectBuilder<'x>, &EntryFields) -> SelectBuilder<'x>
+{
+    let nulls = EntryFields::from(vec![ObjectField::Null; u8::MAX as usize]);
+    if !key_function(&nulls).is_empty() {
+        return None;
+    }
+    let aggregates = aggregates(SelectBuilder::new(&nulls), &nulls)
+        .into_fields()
+        .iter()
+        .map(SelectField::empty)
+        .collect();
+    Some((vec![], aggregates))
+}
+
+/// Groups the rows by a key, aggregating every group into a single row - consisting of the key columns, followed by the aggregates.
+/// Once the group table exceeds the memory budget, rows of the new groups are spilled to partition files, which are aggregated after the groups kept in memory.
+#[derive(Clone)]
+pub struct HashAggregate<Iter: DBOperator, KeyFunction, Aggregates>
+where   KeyFunction: Clone + Fn(&EntryFields) -> Vec<ObjectField>,
+        Aggregates: Clone + for<'x> Fn(SelectBuilder<'x>, &EntryFields) -> SelectBuilder<'x>
+{
+    iterator: Iter,
+    key_function: KeyFunction,
+    aggregates: Aggregates,
+    spill_config: SpillConfig,
+    groups: Option<std::vec::IntoIter<Group>>,
+    /// Spilled partitions still to be aggregated, along with their partitioning depth
+    spilled_partitions: Vec<(SpillReader, u64)>
+}
+
+impl<Iter: DBOperator, KeyFunction, Aggregates> HashAggregate<Iter, KeyFunction, Aggregates>
+where   KeyFunction: Clone + Fn(&EntryFields) -> Vec<ObjectField>,
+        Aggregates: Clone + for<'x> Fn(SelectBuilder<'x>, &EntryFields) -> SelectBuilder<'x>
+{
+    pub fn new(iterator: Iter, key_function: KeyFunction, aggregates: Aggregates) -> Self {
+        let spill_config = SpillConfig::of_storage(iterator.storage_config());
+        HashAggregate {
+            iterator,
+            key_function,
+            aggregates,
+            spill_config,
+            groups: None,
+            spilled_partitions: vec![]
+        }
+    }
+
+    /// Sets the memory budget of the group table, and the directory the groups over the budget are spilled to.
+    pub fn with_spill_config(mut self, spill_config: SpillConfig) -> Self {
+        self.spill_config = spill_config;
+        self
+    }
+
+    fn aggregate(&mut self, source: Option<SpillReader>, depth: u64) -> DBResult<()> {
+        let mut table = HashMap::<Vec<ObjectField>, Vec<SelectField>>::new();
+        let mut memory_size = 0;
+        let mut partitions: Vec<Option<SpillFile>> = (0..SPILL_PARTITIONS).map(|_| None).collect();
+
+        let mut source = source;
+        loop {
+            let next_row = match source.as_mut() {
+                Some(reader) => reader.next()?,
+                None => self.iterator.next()?
+            };
+            let Some(row) = next_row else {
+                break;
+            };
+
+            let key = (self.key_function)(&row.fields);
+            let fields = (self.aggregates)(SelectBuilder::new(&row.fields), &row.fields).into_fields();
+            if let Some(group) = table.get_mut(&key) {
+                let previous_size: usize = group.iter().map(SelectField::memory_size).sum();
+                SelectField::combine_all(group, fields)?;
+                memory_size = memory_size - previous_size + group.iter().map(SelectField::memory_size).sum::<usize>();
+            } else if memory_size < self.spill_config.memory_budget || table.is_empty() {
+                memory_size += key.iter().map(ObjectField::memory_size).sum::<usize>()
+                    + fields.iter().map(SelectField::memory_size).sum::<usize>();
+                table.insert(key, fields);
+            } else {
+                let partition = &mut partitions[Self::partition(&key, depth)];
+                let file = match partition {
+                    Some(file) => file,
+                    None => partition.insert(SpillFile::create(&self.spill_config)?)
+                };
+                file.write(&row)?;
+            }
+        }
+
+        let reading_input = source.is_none();
+        for file in partitions.into_iter().flatten() {
+            self.spilled_partitions.push((file.into_reader()?, depth + 1));
+        }
+        let mut groups = table.into_iter().collect::<Vec<_>>();
+        if reading_input && groups.is_empty() {
+            groups.extend(empty_group(&self.key_function, &self.aggregates));
+        }
+        self.groups = Some(groups.into_iter());
+        Ok(())
+    }
+
+    /// Partitions differently on every depth, so that the groups of a spilled partition get spread again
+    fn partition(key: &Vec<ObjectField>, depth: u64) -> usize {
+        let mut hasher = GxHasher::with_seed(depth as i64);
+        key.hash(&mut hasher);
+        hasher.finish() as usize % SPILL_PARTITIONS
+    }
+}
+
+impl<Iter: DBOperator, KeyFunction, Aggregates> DBOperator for HashAggregate<Iter, KeyFunction, Aggregates>
+where   KeyFunction: Clone + Fn(&EntryFields) -> Vec<ObjectField>,
+        Aggregates: Clone + for<'x> Fn(SelectBuilder<'x>, &EntryFields) -> SelectBuilder<'x>
+{
+    fn next(&mut self) -> DBResult<Option<Row>> {
+        loop {
+            match self.groups.as_mut() {
+                None => self.aggregate(None, 0)?,
+                Some(groups) => {
+                    if let Some((key, aggregates)) = groups.next() {
+                        let mut fields = key;
+                        fields.extend(aggregates.into_iter().map(SelectField::finish));
+                        return Ok(Some(Row {
+                            id: Uuid::new_v4(),
+                            fields: fields.into()
+                        }));
+                    }
+                    match self.spilled_partitions.pop() {
+                        Some((reader, depth)) => self.aggregate(Some(reader), depth)?,
+                        None => return Ok(None)
+                    }
+                }
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.iterator.reset();
+        self.groups = None;
+        self.spilled_partitions.clear();
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.groups {
+            None => (0, self.iterator.size_hint().1),
+            Some(groups) => {
+                let spilled_rows: usize = self.spilled_partitions
+                    .iter()
+                    .map(|(reader, _)| reader.size_hint().0)
+                    .sum();
+                (groups.len(), Some(groups.len() + spilled_rows))
+            }
+        }
+    }
+
+    fn storage_config(&self) -> Option<&DatabaseConfig> {
+        self.iterator.storage_config()
+    }
+}
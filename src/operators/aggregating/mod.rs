@@ -0,0 +1 @@
+mod hash_aggregate; pub use hash_aggregate::HashAggregate; pub(crate) use hash_aggregate::empty_group;
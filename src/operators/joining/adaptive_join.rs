@@ -0,0 +1,347 @@
+use log_err::LogErrOption;
+
+use crate::operators::joining::{matchable_key, pad_left, pad_right, JoinKey, JoinKeyValues, JoinKind};
+use crate::storage::storage_config::{DatabaseConfig, SpillConfig};
+use crate::{DBOperator, DBResult, EntryFields, HashMatch, Row};
+
+/// Number of rows of the other input, up to which the rows are joined by a nested loop
+const DEFAULT_NESTED_LOOP_THRESHOLD: usize = 64;
+
+/// Algorithm chosen by the adaptive join
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JoinStrategy {
+    NestedLoop,
+    HashMatch
+}
+
+/// Joins the rows with the ones of the other input of the same key, choosing the algorithm on the first row.
+/// The other input is buffered up to a threshold - if it ends before, its rows are joined by a nested loop in memory,
+/// otherwise the buffered rows and the rest of the input are joined by a hash match.
+/// If the other input's size hint already exceeds the threshold, the hash match is used without buffering.
+/// If the other input fails while being buffered, the rows buffered so far are kept, and the next call continues buffering.
+/// The first input is always scanned - seeking an index of it for the keys of a small other input is left to
+/// `Collection::index_seek_ranges`, whose rows can then be joined instead.
+#[derive(Clone)]
+pub struct AdaptiveJoin<Iter, Iter2, Key, LeftKeyFunction, RightKeyFunction>
+where   Iter: DBOperator,
+        Iter2: DBOperator,
+        Key: JoinKey,
+        LeftKeyFunction: Clone + Fn(&EntryFields) -> Key,
+        RightKeyFunction: Clone + Fn(&EntryFields) -> Key
+{
+    left_key_function: LeftKeyFunction,
+    right_key_function: RightKeyFunction,
+    join_kind: JoinKind,
+    threshold: usize,
+    spill_config: SpillConfig,
+    state: JoinState<Iter, Iter2, Key, LeftKeyFunction, RightKeyFunction>
+}
+
+#[derive(Clone)]
+enum JoinState<Iter, Iter2, Key, LeftKeyFunction, RightKeyFunction>
+where   Iter: DBOperator,
+        Iter2: DBOperator,
+        Key: JoinKey,
+        LeftKeyFunction: Clone + Fn(&EntryFields) -> Key,
+        RightKeyFunction: Clone + Fn(&EntryFields) -> Key
+{
+    /// Both inputs, along with the rows of the other input buffered so far
+    Undecided(Iter, Iter2, Vec<Row>),
+    /// Only while the state is being replaced by the chosen algorithm
+    Deciding,
+    NestedLoop(BufferedNestedLoop<Iter, Key, LeftKeyFunction>),
+    HashMatch(Box<HashMatch<Iter, BufferedInput<Iter2>, Key, LeftKeyFunction, RightKeyFunction>>)
+}
+
+impl<Iter, Iter2, Key, LeftKeyFunction, RightKeyFunction> AdaptiveJoin<Iter, Iter2, Key, LeftKeyFunction, RightKeyFunction>
+where   Iter: DBOperator,
+        Iter2: DBOperator,
+        Key: JoinKey,
+        LeftKeyFunction: Clone + Fn(&EntryFields) -> Key,
+        RightKeyFunction: Clone + Fn(&EntryFields) -> Key
+{
+    pub fn new(iterator: Iter, other: Iter2, left_key_function: LeftKeyFunction, right_key_function: RightKeyFunction, join_kind: JoinKind) -> Self {
+        let spill_config = SpillConfig::of_storage(iterator.storage_config().or_else(|| other.storage_config()));
+        AdaptiveJoin {
+            left_key_function,
+            right_key_function,
+            join_kind,
+            threshold: DEFAULT_NESTED_LOOP_THRESHOLD,
+            spill_config,
+            state: JoinState::Undecided(iterator, other, vec![])
+        }
+    }
+
+    /// Sets the number of rows of the other input, up to which the rows are joined by a nested loop
+    pub fn with_threshold(mut self, threshold: usize) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Sets the spill configuration of the hash match, if it gets chosen
+    pub fn with_spill_config(mut self, spill_config: SpillConfig) -> Self {
+        self.spill_config = spill_config;
+        self
+    }
+
+    /// The chosen algorithm, or [`None`] before the first row is requested
+    pub fn strategy(&self) -> Option<JoinStrategy> {
+        match &self.state {
+            JoinState::Undecided(..) | JoinState::Deciding => None,
+            JoinState::NestedLoop(_) => Some(JoinStrategy::NestedLoop),
+            JoinState::HashMatch(_) => Some(JoinStrategy::HashMatch)
+        }
+    }
+
+    /// Buffers the other input, returning whether it ended within the threshold
+    fn buffer(other: &mut Iter2, buffered: &mut Vec<Row>, threshold: usize) -> DBResult<bool> {
+        while buffered.len() <= threshold {
+            match other.next()? {
+                Some(row) => buffered.push(row),
+                None => return Ok(true)
+            }
+        }
+        Ok(false)
+    }
+
+    /// Chooses the algorithm - the inputs are only taken out of the state once the other input has been buffered without errors
+    fn decide(&mut self) -> DBResult<()> {
+        let JoinState::Undecided(_, other, buffered) = &mut self.state else {
+            return Ok(());
+        };
+        let nested_loop = (!buffered.is_empty() || other.size_hint().0 <= self.threshold)
+            && Self::buffer(other, buffered, self.threshold)?;
+        let JoinState::Undecided(iterator, other, buffered) = std::mem::replace(&mut self.state, JoinState::Deciding) else {
+            unreachable!()
+        };
+        if nested_loop {
+            self.state = JoinState::NestedLoop(BufferedNestedLoop::new(iterator, buffered, self.left_key_function.clone(), &self.right_key_function, self.join_kind));
+            return Ok(());
+        }
+        let other = BufferedInput {
+            buffered: buffered.into_iter(),
+            rest: other
+        };
+        let hash_match = HashMatch::new(iterator, other, self.left_key_function.clone(), self.right_key_function.clone(), self.join_kind)
+            .with_spill_config(self.spill_config.clone());
+        self.state = JoinState::HashMatch(Box::new(hash_match));
+        Ok(())
+    }
+}
+
+impl<Iter, Iter2, Key, LeftKeyFunction, RightKeyFunction> DBOperator for AdaptiveJoin<Iter, Iter2, Key, LeftKeyFunction, RightKeyFunction>
+where   Iter: DBOperator,
+        Iter2: DBOperator,
+        Key: JoinKey,
+        LeftKeyFunction: Clone + Fn(&EntryFields) -> Key,
+        RightKeyFunction: Clone + Fn(&EntryFields) -> Key
+{
+    fn next(&mut self) -> DBResult<Option<Row>> {
+        self.decide()?;
+        match &mut self.state {
+            JoinState::Undecided(..) | JoinState::Deciding => Ok(None),
+            JoinState::NestedLoop(nested_loop) => nested_loop.next(),
+            JoinState::HashMatch(hash_match) => hash_match.next()
+        }
+    }
+
+    /// Keeps the chosen algorithm - the nested loop keeps the buffered rows of the other input too
+    fn reset(&mut self) {
+        match &mut self.state {
+            JoinState::Undecided(iterator, other, buffered) => {
+                iterator.reset();
+                other.reset();
+                buffered.clear();
+            },
+            JoinState::Deciding => {},
+            JoinState::NestedLoop(nested_loop) => nested_loop.reset(),
+            JoinState::HashMatch(hash_match) => hash_match.reset()
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.state {
+            JoinState::Undecided(iterator, other, buffered) => {
+                let hint1 = iterator.size_hint();
+                let hint2 = (0, other.size_hint().1.map(|high| high + buffered.len()));
+                (0, hint1.1.zip(hint2.1).map(|(a, b)| a.saturating_mul(b).saturating_add(a).saturating_add(b)))
+            },
+            JoinState::Deciding => (0, None),
+            JoinState::NestedLoop(nested_loop) => nested_loop.size_hint(),
+            JoinState::HashMatch(hash_match) => hash_match.size_hint()
+        }
+    }
+
+    fn storage_config(&self) -> Option<&DatabaseConfig> {
+        match &self.state {
+            JoinState::Undecided(iterator, other, _) => iterator.storage_config().or_else(|| other.storage_config()),
+            JoinState::Deciding => None,
+            JoinState::NestedLoop(nested_loop) => nested_loop.storage_config(),
+            JoinState::HashMatch(hash_match) => hash_match.storage_config()
+        }
+    }
+}
+
+/// Rows buffered while choosing the algorithm, followed by the rest of the input
+#[derive(Clone)]
+struct BufferedInput<Iter: DBOperator> {
+    buffered: std::vec::IntoIter<Row>,
+    rest: Iter
+}
+
+impl<Iter: DBOperator> DBOperator for BufferedInput<Iter> {
+    fn next(&mut self) -> DBResult<Option<Row>> {
+        match self.buffered.next() {
+            Some(row) => Ok(Some(row)),
+            None => self.rest.next()
+        }
+    }
+
+    /// Drops the buffered rows, as the input returns them again
+    fn reset(&mut self) {
+        self.buffered = vec![].into_iter();
+        self.rest.reset();
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (low, high) = self.rest.size_hint();
+        (low + self.buffered.len(), high.map(|high| high + self.buffered.len()))
+    }
+
+    fn storage_config(&self) -> Option<&DatabaseConfig> {
+        self.rest.storage_config()
+    }
+}
+
+/// Nested loop over the buffered rows of the other input, with their keys computed once
+#[derive(Clone)]
+struct BufferedNestedLoop<Iter, Key, LeftKeyFunction>
+where   Iter: DBOperator,
+        Key: JoinKey,
+        LeftKeyFunction: Clone + Fn(&EntryFields) -> Key
+{
+    iterator: Iter,
+    left_key_function: LeftKeyFunction,
+    join_kind: JoinKind,
+    buffered_rows: Vec<(Option<JoinKeyValues>, Row)>,
+    buffered_matched: Vec<bool>,
+    buffered_width: usize,
+    current_row: Option<(Option<JoinKeyValues>, Row)>,
+    current_matched: bool,
+    position: usize,
+    width: usize,
+    /// Position of the next buffered row to check for being unmatched, once the rows are exhausted
+    unmatched_position: Option<usize>
+}
+
+impl<Iter, Key, LeftKeyFunction> BufferedNestedLoop<Iter, Key, LeftKeyFunction>
+where   Iter: DBOperator,
+        Key: JoinKey,
+        LeftKeyFunction: Clone + Fn(&EntryFields) -> Key
+{
+    fn new(iterator: Iter, buffered: Vec<Row>, left_key_function: LeftKeyFunction, right_key_function: &impl Fn(&EntryFields) -> Key, join_kind: JoinKind) -> Self {
+        BufferedNestedLoop {
+            iterator,
+            left_key_function,
+            join_kind,
+            buffered_matched: vec![false; buffered.len()],
+            buffered_width: buffered.first().map_or(0, |row| row.fields.len()),
+            buffered_rows: buffered
+                .into_iter()
+                .map(|row| (matchable_key(right_key_function(&row.fields)), row))
+                .collect(),
+            current_row: None,
+            current_matched: false,
+            position: 0,
+            width: 0,
+            unmatched_position: None
+        }
+    }
+
+    fn next_unmatched(&mut self, mut position: usize) -> Option<Row> {
+        while position < self.buffered_rows.len() {
+            position += 1;
+            if !self.buffered_matched[position - 1] {
+                self.unmatched_position = Some(position);
+                return Some(pad_left(self.width, &self.buffered_rows[position - 1].1));
+            }
+        }
+        self.unmatched_position = Some(position);
+        None
+    }
+}
+
+impl<Iter, Key, LeftKeyFunction> DBOperator for BufferedNestedLoop<Iter, Key, LeftKeyFunction>
+where   Iter: DBOperator,
+        Key: JoinKey,
+        LeftKeyFunction: Clone + Fn(&EntryFields) -> Key
+{
+    fn next(&mut self) -> DBResult<Option<Row>> {
+        if let Some(position) = self.unmatched_position {
+            return Ok(self.next_unmatched(position));
+        }
+        loop {
+            if self.current_row.is_none() {
+                let Some(row) = self.iterator.next()? else {
+                    if self.join_kind.keeps_unmatched_right() {
+                        return Ok(self.next_unmatched(0));
+                    }
+                    return Ok(None);
+                };
+                self.width = row.fields.len();
+                self.current_row = Some((matchable_key((self.left_key_function)(&row.fields)), row));
+                self.current_matched = false;
+                self.position = 0;
+            }
+
+            let (key, row) = self.current_row.as_ref().log_unwrap();
+            if key.is_some() {
+                while let Some((buffered_key, buffered_row)) = self.buffered_rows.get(self.position) {
+                    self.position += 1;
+                    if buffered_key != key {
+                        continue;
+                    }
+                    self.current_matched = true;
+                    match self.join_kind {
+                        JoinKind::Semi => return Ok(self.current_row.take().map(|(_, row)| row)),
+                        JoinKind::Anti => break,
+                        _ => {
+                            self.buffered_matched[self.position - 1] = true;
+                            return Ok(Some(Row::combine(row, buffered_row)));
+                        }
+                    }
+                }
+            }
+
+            let (_, row) = self.current_row.take().log_unwrap();
+            if !self.current_matched {
+                match self.join_kind {
+                    JoinKind::Anti => return Ok(Some(row)),
+                    join_kind if join_kind.keeps_unmatched_left() => return Ok(Some(pad_right(&row, self.buffered_width))),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.iterator.reset();
+        self.buffered_matched.fill(false);
+        self.current_row = None;
+        self.current_matched = false;
+        self.position = 0;
+        self.unmatched_position = None;
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (_, high) = self.iterator.size_hint();
+        match self.join_kind {
+            JoinKind::Semi | JoinKind::Anti => (0, high),
+            _ => (0, high.map(|high| high.saturating_mul(self.buffered_rows.len()).saturating_add(high).saturating_add(self.buffered_rows.len())))
+        }
+    }
+
+    fn storage_config(&self) -> Option<&DatabaseConfig> {
+        self.iterator.storage_config()
+    }
+}
@@ -1,120 +1,369 @@
-use gxhash::HashMap;
-use gxhash::HashMapExt;
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+
+use gxhash::{GxHasher, HashMap, HashMapExt};
 use log_err::LogErrOption;
 
-use crate::EntryFields;
-use crate::ObjectField;
+use crate::operators::joining::{matchable_key, pad_left, pad_right, JoinKey, JoinKeyValues, JoinKind};
+use crate::operators::spill_file::{SpillFile, SpillReader};
+use crate::storage::storage_config::{DatabaseConfig, SpillConfig};
+use crate::{EntryFields, RowBatch, BATCH_ROWS};
 use crate::{DBOperator, DBResult, Row};
 
+/// Number of files both inputs are partitioned into, when the hashed rows do not fit in the memory
+const SPILL_PARTITIONS: usize = 16;
+
+/// Partitions deeper than this are joined in memory regardless of the budget, as their rows likely share a single key
+const MAX_PARTITION_DEPTH: u64 = 3;
+
+/// Joins the rows with the hashed rows of the same key.
+/// Once the hashed rows exceed the memory budget, both inputs are partitioned by the key to disk (a grace hash join),
+/// and the partitions are joined one after another - so the rows are no longer returned in the order of the first input.
+/// Both inputs are read in batches - the hash table is built from whole batches, and every batch of the first input probes it at once.
 #[derive(Clone)]
 pub struct HashMatch<Iter, HashedIter, Key, IterGetter, HashedGetter>
 where   Iter: DBOperator,
         HashedIter: DBOperator,
-        Key: Into<ObjectField> + Clone,
+        Key: JoinKey,
         IterGetter: Clone + Fn(&EntryFields) -> Key,
         HashedGetter: Clone + Fn(&EntryFields) -> Key
 {
     iterator: Iter,
-    current_row: Option<Row>,
-    current_vector: Option<Vec<Row>>,
     hashed_iterator: HashedIter,
     first_getter: IterGetter,
     hashed_getter: HashedGetter,
-    hash_map: Option<HashMap<ObjectField, Vec<Row>>>
+    join_kind: JoinKind,
+    spill_config: SpillConfig,
+    started: bool,
+    hash_table: Option<HashTable>,
+    /// Source of the rows probing the current hash table
+    probe_side: Side,
+    /// Partitions still to be joined - the hashed rows, the probing rows and the partitioning depth
+    spilled_partitions: Vec<(Side, Side, u64)>,
+    hashed_width: usize,
+    width: usize,
+    /// Joined rows of the last probing batch, not returned yet
+    output: VecDeque<Row>
+}
+
+/// Hashed rows, stored contiguously by their key
+#[derive(Clone, Default)]
+struct HashTable {
+    rows: Vec<Row>,
+    /// Positions of the rows of every key, and whether any row matched the key
+    groups: HashMap<JoinKeyValues, (Range<usize>, bool)>,
+    /// Rows with a null in their key, which cannot match
+    null_key_rows: Vec<Row>
+}
+
+/// Source of the rows of one of the join's sides
+#[derive(Clone)]
+enum Side {
+    Input,
+    Spilled(SpillReader),
+    Empty
 }
 
 impl<Iter, HashedIter, Key, IterGetter, HashedGetter> HashMatch<Iter, HashedIter, Key, IterGetter, HashedGetter>
 where   Iter: DBOperator,
         HashedIter: DBOperator,
-        Key: Into<ObjectField> + Clone,
+        Key: JoinKey,
         IterGetter: Clone + Fn(&EntryFields) -> Key,
         HashedGetter: Clone + Fn(&EntryFields) -> Key
 {
-    pub fn new(iterator: Iter, hashed_iterator: HashedIter, first_getter: IterGetter, hashed_getter: HashedGetter) -> Self {
+    pub fn new(iterator: Iter, hashed_iterator: HashedIter, first_getter: IterGetter, hashed_getter: HashedGetter, join_kind: JoinKind) -> Self {
+        let spill_config = SpillConfig::of_storage(iterator.storage_config().or_else(|| hashed_iterator.storage_config()));
         HashMatch {
             iterator,
-            current_row: None,
-            current_vector: None,
             hashed_iterator,
             first_getter,
             hashed_getter,
-            hash_map: None
+            join_kind,
+            spill_config,
+            started: false,
+            hash_table: None,
+            probe_side: Side::Empty,
+            spilled_partitions: vec![],
+            hashed_width: 0,
+            width: 0,
+            output: VecDeque::new()
         }
     }
-}
 
-impl<Iter, HashedIter, Key, IterGetter, HashedGetter> DBOperator for HashMatch<Iter, HashedIter, Key, IterGetter, HashedGetter>
-where   Iter: DBOperator,
-        HashedIter: DBOperator,
-        Key: Into<ObjectField> + Clone,
-        IterGetter: Clone + Fn(&EntryFields) -> Key,
-        HashedGetter: Clone + Fn(&EntryFields) -> Key
-{
+    /// Sets the memory budget of the hashed rows, and the directory both inputs are partitioned to once the budget is exceeded.
+    pub fn with_spill_config(mut self, spill_config: SpillConfig) -> Self {
+        self.spill_config = spill_config;
+        self
+    }
 
-    fn next(&mut self) -> DBResult<Option<Row>> {
-        if self.hash_map.is_none() {
-            let mut map = HashMap::<ObjectField, Vec<Row>>::new();
-            while let Some(row) = self.hashed_iterator.next()? {
-                let key = (self.hashed_getter)(&row.fields).into();
-                match map.get_mut(&key) {
-                    Some(vec) => {
-                        vec.push(row);
-                    },
-                    None => {
-                        map.insert(key, vec![row]);
-                    }
-                };
+    /// Reads the next batch of the hashed rows - an empty batch once they are exhausted
+    fn next_hashed_batch(&mut self, side: &mut Side) -> DBResult<Vec<Row>> {
+        let rows = read_batch(side, &mut self.hashed_iterator)?;
+        if let Some(row) = rows.last() {
+            self.hashed_width = row.fields.len();
+        }
+        Ok(rows)
+    }
+
+    /// Reads the next batch of the probing rows - an empty batch once they are exhausted
+    fn next_probe_batch(&mut self) -> DBResult<Vec<Row>> {
+        let rows = read_batch(&mut self.probe_side, &mut self.iterator)?;
+        if let Some(row) = rows.last() {
+            self.width = row.fields.len();
+        }
+        Ok(rows)
+    }
+
+    /// Builds the hash table of the hashed rows, or partitions both sides to disk if they do not fit in the memory budget
+    fn build(&mut self, mut hashed_side: Side, probe_side: Side, depth: u64) -> DBResult<()> {
+        let mut groups = HashMap::<JoinKeyValues, Vec<Row>>::new();
+        let mut null_key_rows = vec![];
+        let mut memory_size = 0;
+        loop {
+            let batch = self.next_hashed_batch(&mut hashed_side)?;
+            if batch.is_empty() {
+                break;
+            }
+            let mut rows = batch.into_iter();
+            while let Some(row) = rows.next() {
+                memory_size += row.fields.byte_size();
+                match matchable_key((self.hashed_getter)(&row.fields)) {
+                    Some(key) => groups.entry(key).or_default().push(row),
+                    None => null_key_rows.push(row)
+                }
+                if memory_size > self.spill_config.memory_budget && depth < MAX_PARTITION_DEPTH {
+                    let buffered_rows = groups.into_values().flatten().chain(null_key_rows).chain(rows);
+                    return self.partition(buffered_rows, hashed_side, probe_side, depth);
+                }
+            }
+        }
+
+        let mut table = HashTable {
+            rows: Vec::with_capacity(groups.values().map(Vec::len).sum()),
+            groups: HashMap::with_capacity(groups.len()),
+            null_key_rows
+        };
+        for (key, rows) in groups {
+            let start = table.rows.len();
+            table.rows.extend(rows);
+            table.groups.insert(key, (start..table.rows.len(), false));
+        }
+        self.hash_table = Some(table);
+        self.probe_side = probe_side;
+        Ok(())
+    }
+
+    fn partition(&mut self, buffered_rows: impl Iterator<Item = Row>, mut hashed_side: Side, probe_side: Side, depth: u64) -> DBResult<()> {
+        let mut hashed_partitions: Vec<Option<SpillFile>> = (0..SPILL_PARTITIONS).map(|_| None).collect();
+        for row in buffered_rows {
+            let key = (self.hashed_getter)(&row.fields);
+            Self::write_partition(&mut hashed_partitions, key, depth, &row, &self.spill_config)?;
+        }
+        loop {
+            let batch = self.next_hashed_batch(&mut hashed_side)?;
+            if batch.is_empty() {
+                break;
+            }
+            for row in batch {
+                let key = (self.hashed_getter)(&row.fields);
+                Self::write_partition(&mut hashed_partitions, key, depth, &row, &self.spill_config)?;
             }
-            self.hash_map = Some(map);
         }
 
+        let mut probe_partitions: Vec<Option<SpillFile>> = (0..SPILL_PARTITIONS).map(|_| None).collect();
+        self.probe_side = probe_side;
         loop {
-            if self.current_row.is_none() {
-                match self.iterator.next()? {
-                    Some(row) => {
-                        self.current_row = Some(row);
-                    },
-                    None => {
-                        return Ok(None);
-                    }
+            let batch = self.next_probe_batch()?;
+            if batch.is_empty() {
+                break;
+            }
+            for row in batch {
+                let key = (self.first_getter)(&row.fields);
+                Self::write_partition(&mut probe_partitions, key, depth, &row, &self.spill_config)?;
+            }
+        }
+        self.probe_side = Side::Empty;
+
+        for (hashed, probe) in hashed_partitions.into_iter().zip(probe_partitions) {
+            let hashed = match hashed {
+                Some(file) => Side::Spilled(file.into_reader()?),
+                None => Side::Empty
+            };
+            let probe = match probe {
+                Some(file) => Side::Spilled(file.into_reader()?),
+                None => Side::Empty
+            };
+            let needed = match (&hashed, &probe) {
+                (Side::Empty, Side::Empty) => false,
+                (Side::Empty, _) => self.join_kind.keeps_unmatched_left() || self.join_kind == JoinKind::Anti,
+                (_, Side::Empty) => self.join_kind.keeps_unmatched_right(),
+                _ => true
+            };
+            if needed {
+                self.spilled_partitions.push((hashed, probe, depth + 1));
+            }
+        }
+        Ok(())
+    }
+
+    /// Partitions differently on every depth, so that the rows of a spilled partition get spread again.
+    /// Rows with a null in their key cannot match, so they all go to the first partition.
+    fn write_partition(partitions: &mut [Option<SpillFile>], key: Key, depth: u64, row: &Row, spill_config: &SpillConfig) -> DBResult<()> {
+        let index = match matchable_key(key) {
+            Some(key) => {
+                let mut hasher = GxHasher::with_seed(depth as i64);
+                key.hash(&mut hasher);
+                hasher.finish() as usize % SPILL_PARTITIONS
+            },
+            None => 0
+        };
+        let partition = &mut partitions[index];
+        let file = match partition {
+            Some(file) => file,
+            None => partition.insert(SpillFile::create(spill_config)?)
+        };
+        file.write(row)
+    }
+
+    /// Bounds of the rows joined from the inputs
+    fn join_size_hint(&self) -> (usize, Option<usize>) {
+        let hint1 = self.iterator.size_hint();
+        let hint2 = self.hashed_iterator.size_hint();
+        match self.join_kind {
+            JoinKind::Semi | JoinKind::Anti => (0, hint1.1),
+            // Every row of the first input is returned at least once by the left and full outer joins
+            JoinKind::LeftOuter | JoinKind::FullOuter => (
+                hint1.0,
+                hint1.1.zip(hint2.1).map(|(a, b)| a.saturating_mul(b).saturating_add(a).saturating_add(b))
+            ),
+            JoinKind::RightOuter => (
+                0,
+                hint1.1.zip(hint2.1).map(|(a, b)| a.saturating_mul(b).saturating_add(a).saturating_add(b))
+            ),
+            JoinKind::Inner => (
+                0,
+                hint1.1.zip(hint2.1).map(|(a, b)| a.saturating_mul(b))
+            )
+        }
+    }
+
+    /// Adds the hashed rows without a match to the output, for the right and full outer joins
+    fn output_unmatched_hashed_rows(&mut self) {
+        if !self.join_kind.keeps_unmatched_right() {
+            return;
+        }
+        let table = self.hash_table.as_ref().log_unwrap();
+        self.output.extend(table.groups
+            .values()
+            .filter(|(_, matched)| !matched)
+            .flat_map(|(range, _)| table.rows[range.clone()].iter())
+            .chain(table.null_key_rows.iter())
+            .map(|row| pad_left(self.width, row)));
+    }
+
+    /// Probes the hash table with a batch of the first input's rows, adding the joined rows to the output
+    fn probe(&mut self, rows: Vec<Row>) {
+        let table = self.hash_table.as_mut().log_unwrap();
+        for row in rows {
+            let key = matchable_key((self.first_getter)(&row.fields));
+            match (key.and_then(|key| table.groups.get_mut(&key)), self.join_kind) {
+                (Some(_), JoinKind::Semi) | (None, JoinKind::Anti) => self.output.push_back(row),
+                (Some(_), JoinKind::Anti) | (None, JoinKind::Semi) => {},
+                (Some((range, matched)), _) => {
+                    *matched = true;
+                    self.output.extend(table.rows[range.clone()].iter().map(|hashed_row| Row::combine(&row, hashed_row)));
+                },
+                (None, join_kind) => if join_kind.keeps_unmatched_left() {
+                    self.output.push_back(pad_right(&row, self.hashed_width));
                 }
             }
+        }
+    }
 
-            let row = self.current_row.take().log_unwrap();
-            if self.current_vector.is_none() {
-                let map = self.hash_map.as_mut().log_unwrap();
-                let key = (self.first_getter)(&row.fields).into();
-                if let Some(vec) = map.get_mut(&key) {
-                    self.current_row = Some(row);
-                    self.current_vector = Some(vec.clone());
+    /// Builds the hash tables and probes them, until there are joined rows in the output - returns false once the join is finished.
+    /// The hashed rows without a match follow the rest of their partition.
+    fn fill_output(&mut self) -> DBResult<bool> {
+        while self.output.is_empty() {
+            if self.hash_table.is_none() {
+                if !self.started {
+                    self.started = true;
+                    self.build(Side::Input, Side::Input, 0)?;
+                } else if let Some((hashed_side, probe_side, depth)) = self.spilled_partitions.pop() {
+                    self.build(hashed_side, probe_side, depth)?;
+                } else {
+                    return Ok(false);
                 }
+                continue;
+            }
+            let rows = self.next_probe_batch()?;
+            if rows.is_empty() {
+                self.output_unmatched_hashed_rows();
+                self.hash_table = None;
             } else {
-                let vec  = self.current_vector.as_mut().log_unwrap();
-                if let Some(second_row) = vec.pop() {
-                    let combined_row = Row::combine(&row, &second_row);
-                    self.current_row = Some(row);
-                    return Ok(Some(combined_row));
-                } else {
-                    self.current_vector = None;
-                };
+                self.probe(rows);
             }
         }
+        Ok(true)
+    }
+}
+
+/// Reads up to [`BATCH_ROWS`] rows of the side - an empty batch once the side is exhausted
+fn read_batch(side: &mut Side, iterator: &mut impl DBOperator) -> DBResult<Vec<Row>> {
+    match side {
+        Side::Input => Ok(iterator.next_batch()?.into_rows()),
+        Side::Spilled(reader) => {
+            let mut rows = Vec::with_capacity(BATCH_ROWS);
+            while rows.len() < BATCH_ROWS && let Some(row) = reader.next()? {
+                rows.push(row);
+            }
+            Ok(rows)
+        },
+        Side::Empty => Ok(vec![])
+    }
+}
+
+impl<Iter, HashedIter, Key, IterGetter, HashedGetter> DBOperator for HashMatch<Iter, HashedIter, Key, IterGetter, HashedGetter>
+where   Iter: DBOperator,
+        HashedIter: DBOperator,
+        Key: JoinKey,
+        IterGetter: Clone + Fn(&EntryFields) -> Key,
+        HashedGetter: Clone + Fn(&EntryFields) -> Key
+{
+
+    fn next(&mut self) -> DBResult<Option<Row>> {
+        if self.output.is_empty() && !self.fill_output()? {
+            return Ok(None);
+        }
+        Ok(self.output.pop_front())
+    }
+
+    fn next_batch(&mut self) -> DBResult<RowBatch> {
+        if self.output.is_empty() && !self.fill_output()? {
+            return Ok(RowBatch::default());
+        }
+        let count = self.output.len().min(BATCH_ROWS);
+        Ok(RowBatch::new(self.output.drain(..count).collect()))
     }
 
     fn reset(&mut self) {
         self.iterator.reset();
         self.hashed_iterator.reset();
-        self.hash_map = None;
-        self.current_row = None;
-        self.current_vector = None;
+        self.started = false;
+        self.hash_table = None;
+        self.probe_side = Side::Empty;
+        self.spilled_partitions.clear();
+        self.output.clear();
     }
 
+    /// The joined rows not returned yet are added to the bounds
     fn size_hint(&self) -> (usize, Option<usize>) {
-        let hint1 = self.iterator.size_hint();
-        let hint2 = self.hashed_iterator.size_hint();
-        (
-            hint1.0 * hint2.0,
-            hint1.1.zip(hint2.1).map(|(a, b)| a.saturating_mul(b))
-        )
+        let (low, high) = self.join_size_hint();
+        let buffered = self.output.len();
+        (low.saturating_add(buffered), high.and_then(|high| high.checked_add(buffered)))
+    }
+
+    fn storage_config(&self) -> Option<&DatabaseConfig> {
+        self.iterator.storage_config().or_else(|| self.hashed_iterator.storage_config())
     }
 }
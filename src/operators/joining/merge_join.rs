@@ -0,0 +1,162 @@
+use std::marker::PhantomData;
+
+use log_err::LogErrOption;
+
+use crate::errors::query_error::EvaluationError;
+use crate::operators::joining::{matchable_key, JoinKey, JoinKeyValues};
+use crate::{DBOperator, DBResult, EntryFields, Row};
+use crate::storage::storage_config::DatabaseConfig;
+
+/// Joins two inputs sorted ascending on their join keys, in a single pass over both of them.
+/// The right rows sharing a key are buffered, so duplicate keys on both sides produce every pair.
+/// Rows with a null in their key do not match, and are not checked for being ordered.
+/// In debug builds, an input which is not ordered on its key results in an error.
+#[derive(Clone)]
+pub struct MergeJoin<Iter, Iter2, Key, LeftKeyFunction, RightKeyFunction>
+where   Iter: DBOperator,
+        Iter2: DBOperator,
+        Key: JoinKey,
+        LeftKeyFunction: Clone + Fn(&EntryFields) -> Key,
+        RightKeyFunction: Clone + Fn(&EntryFields) -> Key
+{
+    iterator: Iter,
+    second_iterator: Iter2,
+    left_key_function: LeftKeyFunction,
+    right_key_function: RightKeyFunction,
+    current_row: Option<Row>,
+    last_left_key: Option<JoinKeyValues>,
+    /// Right rows with the key of the current left row
+    group: Vec<Row>,
+    group_key: Option<JoinKeyValues>,
+    group_position: usize,
+    /// First right row after the group
+    lookahead: Option<(JoinKeyValues, Row)>,
+    right_started: bool,
+    last_right_key: Option<JoinKeyValues>,
+    key_type: PhantomData<Key>
+}
+
+impl<Iter, Iter2, Key, LeftKeyFunction, RightKeyFunction> MergeJoin<Iter, Iter2, Key, LeftKeyFunction, RightKeyFunction>
+where   Iter: DBOperator,
+        Iter2: DBOperator,
+        Key: JoinKey,
+        LeftKeyFunction: Clone + Fn(&EntryFields) -> Key,
+        RightKeyFunction: Clone + Fn(&EntryFields) -> Key
+{
+    pub fn new(iterator: Iter, second_iterator: Iter2, left_key_function: LeftKeyFunction, right_key_function: RightKeyFunction) -> Self {
+        MergeJoin {
+            iterator,
+            second_iterator,
+            left_key_function,
+            right_key_function,
+            current_row: None,
+            last_left_key: None,
+            group: vec![],
+            group_key: None,
+            group_position: 0,
+            lookahead: None,
+            right_started: false,
+            last_right_key: None,
+            key_type: PhantomData
+        }
+    }
+
+    /// Returns the next right row which can match, with its key
+    fn next_right(&mut self) -> DBResult<Option<(JoinKeyValues, Row)>> {
+        while let Some(row) = self.second_iterator.next()? {
+            let Some(key) = matchable_key((self.right_key_function)(&row.fields)) else {
+                continue;
+            };
+            if cfg!(debug_assertions) {
+                if let Some(last_key) = &self.last_right_key && key < *last_key {
+                    return Err(EvaluationError::from_str("The right input of the merge join is not sorted on its key").into());
+                }
+                self.last_right_key = Some(key.clone());
+            }
+            return Ok(Some((key, row)));
+        }
+        Ok(None)
+    }
+
+    /// Skips the right rows before the key, and buffers the ones matching it
+    fn fill_group(&mut self, key: &JoinKeyValues) -> DBResult<()> {
+        self.group.clear();
+        if !self.right_started {
+            self.lookahead = self.next_right()?;
+            self.right_started = true;
+        }
+        while let Some((right_key, _)) = &self.lookahead && right_key < key {
+            self.lookahead = self.next_right()?;
+        }
+        while let Some((right_key, _)) = &self.lookahead && right_key == key {
+            let (_, row) = self.lookahead.take().log_unwrap();
+            self.group.push(row);
+            self.lookahead = self.next_right()?;
+        }
+        self.group_key = Some(key.clone());
+        Ok(())
+    }
+}
+
+impl<Iter, Iter2, Key, LeftKeyFunction, RightKeyFunction> DBOperator for MergeJoin<Iter, Iter2, Key, LeftKeyFunction, RightKeyFunction>
+where   Iter: DBOperator,
+        Iter2: DBOperator,
+        Key: JoinKey,
+        LeftKeyFunction: Clone + Fn(&EntryFields) -> Key,
+        RightKeyFunction: Clone + Fn(&EntryFields) -> Key
+{
+    fn next(&mut self) -> DBResult<Option<Row>> {
+        loop {
+            if let Some(row) = &self.current_row && let Some(second_row) = self.group.get(self.group_position) {
+                self.group_position += 1;
+                return Ok(Some(Row::combine(row, second_row)));
+            }
+
+            let Some(row) = self.iterator.next()? else {
+                return Ok(None);
+            };
+            let Some(key) = matchable_key((self.left_key_function)(&row.fields)) else {
+                self.current_row = None;
+                continue;
+            };
+            if cfg!(debug_assertions) {
+                if let Some(last_key) = &self.last_left_key && key < *last_key {
+                    return Err(EvaluationError::from_str("The left input of the merge join is not sorted on its key").into());
+                }
+                self.last_left_key = Some(key.clone());
+            }
+            // Rows with a repeated left key are matched against the same group
+            if self.group_key.as_ref() != Some(&key) {
+                self.fill_group(&key)?;
+            }
+            self.group_position = 0;
+            self.current_row = Some(row);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.iterator.reset();
+        self.second_iterator.reset();
+        self.current_row = None;
+        self.last_left_key = None;
+        self.group.clear();
+        self.group_key = None;
+        self.group_position = 0;
+        self.lookahead = None;
+        self.right_started = false;
+        self.last_right_key = None;
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let hint1 = self.iterator.size_hint();
+        let hint2 = self.second_iterator.size_hint();
+        (
+            0,
+            hint1.1.zip(hint2.1).map(|(a, b)| a.saturating_mul(b))
+        )
+    }
+
+    fn storage_config(&self) -> Option<&DatabaseConfig> {
+        self.iterator.storage_config().or_else(|| self.second_iterator.storage_config())
+    }
+}
@@ -1,2 +1,104 @@
 mod nested_loop; pub use nested_loop::NestedLoop;
 mod hash_match; pub use hash_match::HashMatch;
+mod merge_join; pub use merge_join::MergeJoin;
+mod adaptive_join; pub use adaptive_join::{AdaptiveJoin, JoinStrategy};
+
+use smallvec::{smallvec, SmallVec};
+
+use crate::{EntryFields, ObjectField, Row};
+
+/// Values of a join key, compared and hashed together
+pub type JoinKeyValues = SmallVec<[ObjectField; 2]>;
+
+/// Key the rows are joined on - a single value, a tuple of values, or a vector of values.
+/// Keys holding a null never match any other key.
+pub trait JoinKey: Clone {
+    fn into_values(self) -> JoinKeyValues;
+}
+
+impl<T: Into<ObjectField> + Clone> JoinKey for T {
+    fn into_values(self) -> JoinKeyValues {
+        smallvec![self.into()]
+    }
+}
+
+impl JoinKey for Vec<ObjectField> {
+    fn into_values(self) -> JoinKeyValues {
+        self.into()
+    }
+}
+
+impl<A: Into<ObjectField> + Clone, B: Into<ObjectField> + Clone> JoinKey for (A, B) {
+    fn into_values(self) -> JoinKeyValues {
+        smallvec![self.0.into(), self.1.into()]
+    }
+}
+
+impl<A: Into<ObjectField> + Clone, B: Into<ObjectField> + Clone, C: Into<ObjectField> + Clone> JoinKey for (A, B, C) {
+    fn into_values(self) -> JoinKeyValues {
+        smallvec![self.0.into(), self.1.into(), self.2.into()]
+    }
+}
+
+impl<A: Into<ObjectField> + Clone, B: Into<ObjectField> + Clone, C: Into<ObjectField> + Clone, D: Into<ObjectField> + Clone> JoinKey for (A, B, C, D) {
+    fn into_values(self) -> JoinKeyValues {
+        smallvec![self.0.into(), self.1.into(), self.2.into(), self.3.into()]
+    }
+}
+
+/// Values of the key, or [`None`] if any of them is null, so that the key cannot match
+pub(crate) fn matchable_key(key: impl JoinKey) -> Option<JoinKeyValues> {
+    let values = key.into_values();
+    if values.iter().any(|value| matches!(value, ObjectField::Null)) {
+        None
+    } else {
+        Some(values)
+    }
+}
+
+/// Rows returned by a join.
+/// The outer joins pad the missing side with nulls - the width of a side is taken from its rows, so nothing is padded if that side is empty.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum JoinKind {
+    /// Combined matching rows
+    #[default]
+    Inner,
+    /// Combined matching rows, and the left rows without a match
+    LeftOuter,
+    /// Combined matching rows, and the right rows without a match
+    RightOuter,
+    /// Combined matching rows, and the rows of both sides without a match
+    FullOuter,
+    /// Left rows with at least one match, returned once and without the right columns
+    Semi,
+    /// Left rows without a match, without the right columns
+    Anti
+}
+
+impl JoinKind {
+    pub(crate) fn keeps_unmatched_left(&self) -> bool {
+        matches!(self, JoinKind::LeftOuter | JoinKind::FullOuter)
+    }
+
+    pub(crate) fn keeps_unmatched_right(&self) -> bool {
+        matches!(self, JoinKind::RightOuter | JoinKind::FullOuter)
+    }
+}
+
+/// Left row followed by null columns in place of the right ones
+pub(crate) fn pad_right(row: &Row, right_width: usize) -> Row {
+    let nulls: EntryFields = vec![ObjectField::Null; right_width].into();
+    Row {
+        id: row.id,
+        fields: EntryFields::combine(&row.fields, &nulls)
+    }
+}
+
+/// Right row preceded by null columns in place of the left ones
+pub(crate) fn pad_left(left_width: usize, row: &Row) -> Row {
+    let nulls: EntryFields = vec![ObjectField::Null; left_width].into();
+    Row {
+        id: row.id,
+        fields: EntryFields::combine(&nulls, &row.fields)
+    }
+}
@@ -1,39 +1,95 @@
 use log_err::LogErrOption;
 
-use crate::{DBOperator, DBResult, Row};
+use crate::operators::joining::{pad_left, pad_right, JoinKind};
+use crate::{DBOperator, DBResult, EntryFields, Row};
+use crate::storage::storage_config::DatabaseConfig;
 
+/// Joins every pair of rows for which the predicate holds - the second input is rebound and rescanned for every row of the first one,
+/// so a spool over it avoids repeating the scan, and an index spool returns only the rows with the key of the first row.
+/// The right and full outer joins expect the rescans to return the same rows in the same order.
 #[derive(Clone)]
-pub struct NestedLoop<Iter: DBOperator, Iter2: DBOperator> {
+pub struct NestedLoop<Iter, Iter2, Predicate>
+where   Iter: DBOperator,
+        Iter2: DBOperator,
+        Predicate: Clone + Fn(&EntryFields, &EntryFields) -> bool
+{
     iterator: Iter,
     second_iterator: Iter2,
-    first_column: usize,
-    second_column: usize,
+    predicate: Predicate,
+    join_kind: JoinKind,
     current_first_value: Option<Row>,
+    current_matched: bool,
+    /// Position of the current second row, and whether each of the second rows matched any first row
+    second_position: usize,
+    second_matched: Vec<bool>,
+    first_width: usize,
+    second_width: usize,
+    /// Set once the first iterator is exhausted, while the second rows without a match are returned
+    finishing: bool
 }
 
-impl<Iter: DBOperator, Iter2: DBOperator> NestedLoop<Iter, Iter2> {
-    pub fn new(iterator: Iter, second_iterator: Iter2, first_column: usize, second_column: usize) -> Self {
+impl<Iter, Iter2, Predicate> NestedLoop<Iter, Iter2, Predicate>
+where   Iter: DBOperator,
+        Iter2: DBOperator,
+        Predicate: Clone + Fn(&EntryFields, &EntryFields) -> bool
+{
+    pub fn new(iterator: Iter, second_iterator: Iter2, predicate: Predicate, join_kind: JoinKind) -> Self {
         NestedLoop {
             iterator,
             second_iterator,
-            first_column,
-            second_column,
+            predicate,
+            join_kind,
             current_first_value: None,
+            current_matched: false,
+            second_position: 0,
+            second_matched: vec![],
+            first_width: 0,
+            second_width: 0,
+            finishing: false
         }
     }
+
+    /// Returns the next second row which did not match any of the first rows
+    fn next_unmatched(&mut self) -> DBResult<Option<Row>> {
+        while let Some(item) = self.second_iterator.next()? {
+            let position = self.second_position;
+            self.second_position += 1;
+            if !self.second_matched.get(position).copied().unwrap_or(false) {
+                return Ok(Some(pad_left(self.first_width, &item)));
+            }
+        }
+        Ok(None)
+    }
 }
 
-impl<Iter: DBOperator, Iter2: DBOperator> DBOperator for NestedLoop<Iter, Iter2> {
+impl<Iter, Iter2, Predicate> DBOperator for NestedLoop<Iter, Iter2, Predicate>
+where   Iter: DBOperator,
+        Iter2: DBOperator,
+        Predicate: Clone + Fn(&EntryFields, &EntryFields) -> bool
+{
 
     fn next(&mut self) -> DBResult<Option<Row>> {
+        if self.finishing {
+            return self.next_unmatched();
+        }
         loop {
             if self.current_first_value.is_none() {
                 let next_item = self.iterator.next()?;
                 match next_item {
                     Some(data) => {
+                        self.first_width = data.fields.len();
+                        self.second_iterator.rebind(&data.fields);
                         self.current_first_value = Some(data);
+                        self.current_matched = false;
+                        self.second_position = 0;
                     },
                     None => {
+                        if self.join_kind.keeps_unmatched_right() {
+                            self.finishing = true;
+                            self.second_iterator.reset();
+                            self.second_position = 0;
+                            return self.next_unmatched();
+                        }
                         return Ok(None);
                     }
                 };
@@ -42,13 +98,37 @@ impl<Iter: DBOperator, Iter2: DBOperator> DBOperator for NestedLoop<Iter, Iter2>
 
             let second_item = self.second_iterator.next()?;
             if let Some(item) = second_item {
-                if first_value.fields.column(self.first_column) == item.fields.column(self.second_column) {
+                self.second_width = item.fields.len();
+                let position = self.second_position;
+                self.second_position += 1;
+                if (self.predicate)(&first_value.fields, &item.fields) {
+                    self.current_matched = true;
+                    match self.join_kind {
+                        JoinKind::Semi => return Ok(self.current_first_value.take()),
+                        JoinKind::Anti => {
+                            self.current_first_value = None;
+                            continue;
+                        },
+                        _ => {}
+                    }
+                    if self.join_kind.keeps_unmatched_right() {
+                        if self.second_matched.len() <= position {
+                            self.second_matched.resize(position + 1, false);
+                        }
+                        self.second_matched[position] = true;
+                    }
                     let return_value = Row::combine(first_value, &item);
                     return Ok(Some(return_value));
                 };
             } else {
-                self.current_first_value = None;
-                self.second_iterator.reset();
+                let first_value = self.current_first_value.take().log_unwrap();
+                if !self.current_matched {
+                    match self.join_kind {
+                        JoinKind::Anti => return Ok(Some(first_value)),
+                        join_kind if join_kind.keeps_unmatched_left() => return Ok(Some(pad_right(&first_value, self.second_width))),
+                        _ => {}
+                    }
+                }
                 continue;
             }
         }
@@ -58,14 +138,34 @@ impl<Iter: DBOperator, Iter2: DBOperator> DBOperator for NestedLoop<Iter, Iter2>
         self.iterator.reset();
         self.second_iterator.reset();
         self.current_first_value = None;
+        self.current_matched = false;
+        self.second_position = 0;
+        self.second_matched.clear();
+        self.finishing = false;
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
         let hint1 = self.iterator.size_hint();
         let hint2 = self.second_iterator.size_hint();
-        (
-            hint1.0 * hint2.0,
-            hint1.1.zip(hint2.1).map(|(a, b)| a.saturating_mul(b))
-        )
+        match self.join_kind {
+            JoinKind::Semi | JoinKind::Anti => (0, hint1.1),
+            // Every row of the first input is returned at least once by the left and full outer joins
+            JoinKind::LeftOuter | JoinKind::FullOuter => (
+                hint1.0,
+                hint1.1.zip(hint2.1).map(|(a, b)| a.saturating_mul(b).saturating_add(a).saturating_add(b))
+            ),
+            JoinKind::RightOuter => (
+                0,
+                hint1.1.zip(hint2.1).map(|(a, b)| a.saturating_mul(b).saturating_add(a).saturating_add(b))
+            ),
+            JoinKind::Inner => (
+                0,
+                hint1.1.zip(hint2.1).map(|(a, b)| a.saturating_mul(b))
+            )
+        }
+    }
+
+    fn storage_config(&self) -> Option<&DatabaseConfig> {
+        self.iterator.storage_config().or_else(|| self.second_iterator.storage_config())
     }
 }
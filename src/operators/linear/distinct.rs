@@ -0,0 +1,192 @@
+use std::hash::{Hash, Hasher};
+
+use gxhash::{GxHasher, HashSet, HashSetExt};
+
+use crate::{operators::spill_file::{SpillFile, SpillReader}, storage::storage_config::SpillConfig, DBOperator, DBResult, EntryFields, ObjectField, Row};
+use crate::storage::storage_config::DatabaseConfig;
+
+/// Number of files the rows with keys not fitting in the memory are partitioned into
+const SPILL_PARTITIONS: usize = 16;
+
+/// Key function comparing the rows on all of their columns
+pub type RowValuesFunction = fn(&EntryFields) -> Vec<ObjectField>;
+
+/// Key of a row compared on all of its columns
+pub(crate) fn row_values(fields: &EntryFields) -> Vec<ObjectField> {
+    fields.values()
+}
+
+/// Returns the first of the rows sharing a key, by remembering the keys seen in a hash set.
+/// Once the first rows of the keys exceed the memory budget, rows with new keys are spilled to partition files, which are deduplicated after the rows kept in memory.
+#[derive(Clone)]
+pub struct Distinct<Iter: DBOperator, KeyFunction: Clone + Fn(&EntryFields) -> Vec<ObjectField>> {
+    iterator: Iter,
+    key_function: KeyFunction,
+    spill_config: SpillConfig,
+    rows: Option<std::vec::IntoIter<Row>>,
+    /// Spilled partitions still to be deduplicated, along with their partitioning depth
+    spilled_partitions: Vec<(SpillReader, u64)>
+}
+
+impl<Iter: DBOperator, KeyFunction: Clone + Fn(&EntryFields) -> Vec<ObjectField>> Distinct<Iter, KeyFunction> {
+    pub fn new(iterator: Iter, key_function: KeyFunction) -> Self {
+        let spill_config = SpillConfig::of_storage(iterator.storage_config());
+        Distinct {
+            iterator,
+            key_function,
+            spill_config,
+            rows: None,
+            spilled_partitions: vec![]
+        }
+    }
+
+    /// Sets the memory budget of the distinct rows, and the directory the rows over the budget are spilled to.
+    pub fn with_spill_config(mut self, spill_config: SpillConfig) -> Self {
+        self.spill_config = spill_config;
+        self
+    }
+
+    fn deduplicate(&mut self, source: Option<SpillReader>, depth: u64) -> DBResult<()> {
+        let mut keys = HashSet::<Vec<ObjectField>>::new();
+        let mut rows = vec![];
+        let mut memory_size = 0;
+        let mut partitions: Vec<Option<SpillFile>> = (0..SPILL_PARTITIONS).map(|_| None).collect();
+
+        let mut source = source;
+        loop {
+            let next_row = match source.as_mut() {
+                Some(reader) => reader.next()?,
+                None => self.iterator.next()?
+            };
+            let Some(row) = next_row else {
+                break;
+            };
+
+            let key = (self.key_function)(&row.fields);
+            if keys.contains(&key) {
+                continue;
+            }
+            if memory_size < self.spill_config.memory_budget || keys.is_empty() {
+                memory_size += key.iter().map(ObjectField::memory_size).sum::<usize>() + row.fields.byte_size();
+                keys.insert(key);
+                rows.push(row);
+            } else {
+                let partition = &mut partitions[Self::partition(&key, depth)];
+                let file = match partition {
+                    Some(file) => file,
+                    None => partition.insert(SpillFile::create(&self.spill_config)?)
+                };
+                file.write(&row)?;
+            }
+        }
+
+        for file in partitions.into_iter().flatten() {
+            self.spilled_partitions.push((file.into_reader()?, depth + 1));
+        }
+        self.rows = Some(rows.into_iter());
+        Ok(())
+    }
+
+    /// Partitions differently on every depth, so that the keys of a spilled partition get spread again
+    fn partition(key: &Vec<ObjectField>, depth: u64) -> usize {
+        let mut hasher = GxHasher::with_seed(depth as i64);
+        key.hash(&mut hasher);
+        hasher.finish() as usize % SPILL_PARTITIONS
+    }
+}
+
+impl<Iter: DBOperator, KeyFunction: Clone + Fn(&EntryFields) -> Vec<ObjectField>> DBOperator for Distinct<Iter, KeyFunction> {
+    fn next(&mut self) -> DBResult<Option<Row>> {
+        loop {
+            match self.rows.as_mut() {
+                None => self.deduplicate(None, 0)?,
+                Some(rows) => {
+                    if let Some(row) = rows.next() {
+                        return Ok(Some(row));
+                    }
+                    match self.spilled_partitions.pop() {
+                        Some((reader, depth)) => self.deduplicate(Some(reader), depth)?,
+                        None => return Ok(None)
+                    }
+                }
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.iterator.reset();
+        self.rows = None;
+        self.spilled_partitions.clear();
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.rows {
+            None => {
+                let (low, high) = self.iterator.size_hint();
+                (low.min(1), high)
+            },
+            Some(rows) => {
+                let spilled_rows: usize = self.spilled_partitions
+                    .iter()
+                    .map(|(reader, _)| reader.size_hint().0)
+                    .sum();
+                let spilled_low = usize::from(spilled_rows > 0);
+                (rows.len() + spilled_low, Some(rows.len() + spilled_rows))
+            }
+        }
+    }
+
+    fn storage_config(&self) -> Option<&DatabaseConfig> {
+        self.iterator.storage_config()
+    }
+}
+
+/// Returns the first of the rows sharing a key, for an input sorted on the key - comparing every row only to the previous one.
+/// Streams the rows without keeping anything but the last key, but returns duplicates which are not adjacent.
+#[derive(Clone)]
+pub struct SortedDistinct<Iter: DBOperator, KeyFunction: Clone + Fn(&EntryFields) -> Vec<ObjectField>> {
+    iterator: Iter,
+    key_function: KeyFunction,
+    last_key: Option<Vec<ObjectField>>
+}
+
+impl<Iter: DBOperator, KeyFunction: Clone + Fn(&EntryFields) -> Vec<ObjectField>> SortedDistinct<Iter, KeyFunction> {
+    pub fn new(iterator: Iter, key_function: KeyFunction) -> Self {
+        SortedDistinct {
+            iterator,
+            key_function,
+            last_key: None
+        }
+    }
+}
+
+impl<Iter: DBOperator, KeyFunction: Clone + Fn(&EntryFields) -> Vec<ObjectField>> DBOperator for SortedDistinct<Iter, KeyFunction> {
+    fn next(&mut self) -> DBResult<Option<Row>> {
+        while let Some(row) = self.iterator.next()? {
+            let key = (self.key_function)(&row.fields);
+            if self.last_key.as_ref() != Some(&key) {
+                self.last_key = Some(key);
+                return Ok(Some(row));
+            }
+        }
+        Ok(None)
+    }
+
+    fn reset(&mut self) {
+        self.iterator.reset();
+        self.last_key = None;
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (low, high) = self.iterator.size_hint();
+        (low.min(1), high)
+    }
+
+    fn storage_config(&self) -> Option<&DatabaseConfig> {
+        self.iterator.storage_config()
+    }
+
+    fn cost_hint(&self) -> Option<usize> {
+        self.iterator.cost_hint()
+    }
+}
@@ -1,15 +1,67 @@
-use crate::{DBOperator, DBResult, EntryFields, Row};
+use crate::{DBOperator, DBResult, EntryFields, ObjectField, Row, RowBatch};
+use crate::storage::storage_config::DatabaseConfig;
+
+/// Condition of a [`Filter`] - a function of the row's fields, or a [`ColumnPredicate`] over a single column.
+pub trait FilterPredicate: Clone {
+    fn matches(&self, fields: &EntryFields) -> bool;
+
+    /// Returns whether each of the rows of the batch matches
+    fn matches_batch(&self, batch: &RowBatch) -> Vec<bool> {
+        batch
+            .rows()
+            .iter()
+            .map(|row| self.matches(&row.fields))
+            .collect()
+    }
+}
+
+impl<Predicate: Clone + Fn(&EntryFields) -> bool> FilterPredicate for Predicate {
+    fn matches(&self, fields: &EntryFields) -> bool {
+        self(fields)
+    }
+}
+
+/// Condition on the value of a single column - in batch mode it runs over the decoded values of the column,
+/// without looking up the column in the fields of every row. Rows without the column get a null in both modes.
+#[derive(Clone)]
+pub struct ColumnPredicate<Predicate: Clone + Fn(&ObjectField) -> bool> {
+    column: usize,
+    predicate: Predicate
+}
+
+impl<Predicate: Clone + Fn(&ObjectField) -> bool> ColumnPredicate<Predicate> {
+    pub fn new(column: usize, predicate: Predicate) -> Self {
+        ColumnPredicate {
+            column,
+            predicate
+        }
+    }
+}
+
+impl<Predicate: Clone + Fn(&ObjectField) -> bool> FilterPredicate for ColumnPredicate<Predicate> {
+    fn matches(&self, fields: &EntryFields) -> bool {
+        (self.predicate)(&fields.column_or_null(self.column))
+    }
+
+    fn matches_batch(&self, batch: &RowBatch) -> Vec<bool> {
+        batch
+            .column(self.column)
+            .iter()
+            .map(&self.predicate)
+            .collect()
+    }
+}
 
 #[derive(Clone)]
 pub struct Filter<Iter: DBOperator, Predicate>
-where Predicate: Clone + Fn(&EntryFields) -> bool
+where Predicate: FilterPredicate
 {
     iterator: Iter,
     predicate: Predicate
 }
 
 impl <Iter: DBOperator, Predicate> Filter<Iter, Predicate>
-where Predicate: Clone + Fn(&EntryFields) -> bool
+where Predicate: FilterPredicate
 {
     pub fn new(iterator: Iter, predicate: Predicate) -> Self {
         Filter {
@@ -20,14 +72,14 @@ where Predicate: Clone + Fn(&EntryFields) -> bool
 }
 
 impl<Iter: DBOperator, Predicate> DBOperator for Filter<Iter, Predicate>
-where Predicate: Clone + Fn(&EntryFields) -> bool
+where Predicate: FilterPredicate
 {
     fn next(&mut self) -> DBResult<Option<Row>> {
         loop {
             let next_item = self.iterator.next();
             match next_item? {
                 Some(data) => {
-                    if (self.predicate)(&data.fields) {
+                    if self.predicate.matches(&data.fields) {
                         return Ok(Some(data));
                     } else {
                         continue;
@@ -38,11 +90,39 @@ where Predicate: Clone + Fn(&EntryFields) -> bool
         }
     }
 
+    /// Filters the batches of the input, skipping the batches without any matching row.
+    /// The decoded columns of the batch are kept for the next operator.
+    fn next_batch(&mut self) -> DBResult<RowBatch> {
+        loop {
+            let mut batch = self.iterator.next_batch()?;
+            if batch.is_empty() {
+                return Ok(batch);
+            }
+            let selected = self.predicate.matches_batch(&batch);
+            batch.retain_selected(&selected);
+            if !batch.is_empty() {
+                return Ok(batch);
+            }
+        }
+    }
+
     fn reset(&mut self) {
         self.iterator.reset();
     }
 
+    fn rebind(&mut self, outer_row: &EntryFields) {
+        self.iterator.rebind(outer_row);
+    }
+
     fn size_hint(&self) -> (usize, Option<usize>) {
-        self.iterator.size_hint()
+        (0, self.iterator.size_hint().1)
+    }
+
+    fn storage_config(&self) -> Option<&DatabaseConfig> {
+        self.iterator.storage_config()
+    }
+
+    fn cost_hint(&self) -> Option<usize> {
+        self.iterator.cost_hint()
     }
 }
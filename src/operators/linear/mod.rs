@@ -1,4 +1,5 @@
-mod select; pub use select::Select; pub use select::SelectBuilder; pub(crate) use select::SelectField;
-mod filter; pub use filter::Filter;
+mod select; pub use select::{Select, SelectBuilder, RowSelector, ColumnSelector}; pub(crate) use select::SelectField;
+mod filter; pub use filter::{Filter, FilterPredicate, ColumnPredicate};
 mod take; pub use take::Take;
 mod skip; pub use skip::Skip;
+mod distinct; pub use distinct::{Distinct, SortedDistinct, RowValuesFunction}; pub(crate) use distinct::row_values;
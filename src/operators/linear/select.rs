@@ -1,13 +1,61 @@
-use std::{borrow::Borrow, ops::Deref};
+use std::{borrow::Borrow, sync::Arc};
 
+use gxhash::{HashSet, HashSetExt};
 use itertools::Either;
+use log::warn;
 use uuid::Uuid;
 
-use crate::{objects::DB_EPSILON, DBOperator, DBResult, EntryFields, ObjectField, Row};
+use crate::{errors::query_error::EvaluationError, objects::DB_EPSILON, utils::TDigest, DBOperator, DBResult, EntryFields, ObjectField, Row, RowBatch};
+use crate::storage::storage_config::DatabaseConfig;
+
+/// Builds the selected row of a [`Select`] - a function of the [`SelectBuilder`] and the row's fields, or a [`ColumnSelector`].
+pub trait RowSelector: Clone {
+    fn select<'x>(&self, builder: SelectBuilder<'x>, fields: &EntryFields) -> SelectBuilder<'x>;
+
+    /// Columns copied to the selected row, if the selector does nothing else - the batches are then selected from their decoded columns
+    fn columns(&self) -> Option<&[usize]> {
+        None
+    }
+}
+
+impl<Selector> RowSelector for Selector
+where Selector: Clone + for<'x> FnOnce(SelectBuilder<'x>, &EntryFields) -> SelectBuilder<'x>
+{
+    fn select<'x>(&self, builder: SelectBuilder<'x>, fields: &EntryFields) -> SelectBuilder<'x> {
+        (self.clone())(builder, fields)
+    }
+}
+
+/// Selects the values of the columns - in batch mode the rows are built from the decoded values of the columns,
+/// without looking up the columns in the fields of every row. Rows without a column get a null in both modes.
+#[derive(Clone)]
+pub struct ColumnSelector {
+    columns: Arc<[usize]>
+}
+
+impl ColumnSelector {
+    pub fn new(columns: &[usize]) -> Self {
+        ColumnSelector {
+            columns: columns.into()
+        }
+    }
+}
+
+impl RowSelector for ColumnSelector {
+    fn select<'x>(&self, builder: SelectBuilder<'x>, fields: &EntryFields) -> SelectBuilder<'x> {
+        self.columns
+            .iter()
+            .fold(builder, |builder, column| builder.value(fields.column_or_null(*column)))
+    }
+
+    fn columns(&self) -> Option<&[usize]> {
+        Some(&self.columns)
+    }
+}
 
 #[derive(Clone)]
 pub struct Select<Iter: DBOperator, Selector>
-where Selector: Clone + for<'x> FnOnce(SelectBuilder<'x>, &EntryFields) -> SelectBuilder<'x>
+where Selector: RowSelector
 {
     iterator: Iter,
     aggregator: SelectAggregator,
@@ -15,7 +63,7 @@ where Selector: Clone + for<'x> FnOnce(SelectBuilder<'x>, &EntryFields) -> Selec
 }
 
 impl <Iter: DBOperator, Selector> Select<Iter, Selector>
-where Selector: Clone + for<'x> FnOnce(SelectBuilder<'x>, &EntryFields) -> SelectBuilder<'x>
+where Selector: RowSelector
 {
     pub fn new(iterator: Iter, selector: Selector) -> Self {
         Select {
@@ -24,43 +72,86 @@ where Selector: Clone + for<'x> FnOnce(SelectBuilder<'x>, &EntryFields) -> Selec
             selector
         }
     }
+
+    /// Returns the selected row, or the aggregated row once the grouping columns change
+    fn select_row(&mut self, data: &Row) -> DBResult<Option<Row>> {
+        let builder = SelectBuilder::new(&data.fields);
+        let builder = self.selector.select(builder, &data.fields);
+        match builder.get_row() {
+            Either::Left(row) => Ok(Some(row)),
+            Either::Right(builder) => self.aggregator.aggregate(builder)
+        }
+    }
+
+    /// Returns the row still being aggregated, once the input is exhausted
+    fn finish(&mut self) -> Option<Row> {
+        if self.aggregator.is_empty() {
+            return None;
+        }
+        let mut fields = vec![];
+        std::mem::swap(&mut self.aggregator.fields, &mut fields);
+        Some(Row {
+            id: Uuid::new_v4(),
+            fields: fields.into()
+        })
+    }
+}
+
+/// Builds the rows of the columns from the decoded values of the batch - the values become the decoded columns of the new batch
+fn select_columns(batch: &RowBatch, columns: &[usize]) -> RowBatch {
+    let columns: Vec<Vec<ObjectField>> = batch
+        .columns_of(columns)
+        .into_iter()
+        .map(<[ObjectField]>::to_vec)
+        .collect();
+    let mut values = Vec::with_capacity(columns.len());
+    let rows = (0..batch.len())
+        .map(|position| {
+            values.clear();
+            values.extend(columns.iter().map(|column| column[position].clone()));
+            Row {
+                id: Uuid::new_v4(),
+                fields: values.as_slice().into()
+            }
+        })
+        .collect();
+    RowBatch::with_columns(rows, columns)
 }
 
 impl<Iter: DBOperator, Selector> DBOperator for Select<Iter, Selector>
-where Selector: Clone + for<'x> FnOnce(SelectBuilder<'x>, &EntryFields) -> SelectBuilder<'x>
+where Selector: RowSelector
 {
     fn next(&mut self) -> DBResult<Option<Row>> {
         loop {
-            let next_item = self.iterator.next();
-            match next_item? {
+            match self.iterator.next()? {
                 Some(data) => {
-                    let builder = SelectBuilder::new(&data.fields);
-                    let builder = (self.selector.clone())(builder, &data.fields);
-                    match builder.get_row() {
-                        Either::Left(row) => {
-                            return Ok(Some(row));
-                        },
-                        Either::Right(builder) => {
-                            if let Some(row) = self.aggregator.aggregate(builder) {
-                                return Ok(Some(row));
-                            } else {
-                                continue;
-                            }
-                        }
+                    if let Some(row) = self.select_row(&data)? {
+                        return Ok(Some(row));
                     }
                 },
-                None => {
-                    if self.aggregator.is_empty() {
-                        return Ok(None);
-                    } else {
-                        let mut fields = vec![];
-                        std::mem::swap(&mut self.aggregator.fields, &mut fields);
-                        return Ok(Some(Row {
-                            id: Uuid::new_v4(),
-                            fields: fields.into()
-                        }));
-                    }
-                }
+                None => return Ok(self.finish())
+            }
+        }
+    }
+
+    /// Selects the rows of the input's batches, skipping the batches aggregated into the same row.
+    /// A [`ColumnSelector`] builds the rows from the decoded columns of the batch.
+    fn next_batch(&mut self) -> DBResult<RowBatch> {
+        loop {
+            let batch = self.iterator.next_batch()?;
+            if batch.is_empty() {
+                return Ok(RowBatch::new(self.finish().into_iter().collect()));
+            }
+            if let Some(columns) = self.selector.columns() {
+                return Ok(select_columns(&batch, columns));
+            }
+            let rows: Vec<Row> = batch
+                .rows()
+                .iter()
+                .filter_map(|data| self.select_row(data).transpose())
+                .collect::<DBResult<_>>()?;
+            if !rows.is_empty() {
+                return Ok(RowBatch::new(rows));
             }
         }
     }
@@ -70,9 +161,18 @@ where Selector: Clone + for<'x> FnOnce(SelectBuilder<'x>, &EntryFields) -> Selec
         self.aggregator = SelectAggregator::new();
     }
 
+    fn rebind(&mut self, outer_row: &EntryFields) {
+        self.iterator.rebind(outer_row);
+        self.aggregator = SelectAggregator::new();
+    }
+
     fn size_hint(&self) -> (usize, Option<usize>) {
         self.iterator.size_hint()
     }
+
+    fn storage_config(&self) -> Option<&DatabaseConfig> {
+        self.iterator.storage_config()
+    }
 }
 
 #[derive(Clone)]
@@ -87,11 +187,11 @@ impl SelectAggregator {
         }
     }
 
-    fn aggregate(&mut self, builder: SelectBuilder) -> Option<Row> {
+    fn aggregate(&mut self, builder: SelectBuilder) -> DBResult<Option<Row>> {
         if self.fields.is_empty() {
             // Aggregator is empty - the received row becomes the aggregator
             self.fields = builder.fields;
-            None
+            Ok(None)
         } else if self.fields.len() != builder.fields.len()
     || self.fields.iter()
         .zip(builder.fields.iter())
@@ -101,24 +201,15 @@ impl SelectAggregator {
             let mut fields = builder.fields;
             std::mem::swap(&mut self.fields, &mut fields);
 
-            // convert count(bool) to count(i64)
-            for ele in self.fields.iter_mut() {
-                if let SelectField::Count(ObjectField::Bool(b)) = ele {
-                    *ele = SelectField::Count(ObjectField::I64(if *b { 1 } else { 0 }));
-                }
-            }
-
             let row = Row {
                 id: Uuid::new_v4(),
                 fields: fields.into()
             };
-            Some(row)
+            Ok(Some(row))
         } else {
             // All grouping columns match - combine the aggregator with received row
-            for (aggregate, next) in self.fields.iter_mut().zip(builder.fields.into_iter()) {
-                aggregate.combine(next);
-            }
-            None
+            SelectField::combine_all(&mut self.fields, builder.fields)?;
+            Ok(None)
         }
     }
 
@@ -132,6 +223,11 @@ pub struct SelectBuilder<'a> {
     fields: Vec<SelectField>
 }
 
+/// Maximum length of a string or byte array column
+const MAX_FIELD_LENGTH: usize = u8::MAX as usize;
+
+/// A column of a selected row - either a plain value, or a (partial) aggregate.
+/// Aggregates other than COUNT, FIRST, LAST and ARRAY_AGG skip the null values.
 #[derive(Clone)]
 pub enum SelectField {
     Field(ObjectField),
@@ -139,20 +235,54 @@ pub enum SelectField {
     Count(ObjectField),
     Max(ObjectField),
     Min(ObjectField),
+    /// Sum and count of the non-null values
+    Avg(f64, i64),
+    CountDistinct(HashSet<ObjectField>),
+    Variance(Moments),
+    StdDev(Moments),
+    /// Digest of the values, and the quantile to estimate
+    Percentile(TDigest, f64),
+    First(ObjectField),
+    Last(ObjectField),
+    /// Concatenated values so far, and the separator
+    StringAgg(Option<String>, Arc<str>),
+    ArrayAgg(Vec<ObjectField>),
 }
 
-impl Deref for SelectField {
-    type Target = ObjectField;
-    
-    fn deref(&self) -> &Self::Target {
-        match self {
-            SelectField::Field(object_field) => object_field,
-            SelectField::Sum(object_field) => object_field,
-            SelectField::Count(object_field) => object_field,
-            SelectField::Max(object_field) => object_field,
-            SelectField::Min(object_field) => object_field,
+/// Running count, mean and sum of squared differences from the mean - used to compute the variance in a single pass
+#[derive(Clone, Default)]
+pub struct Moments {
+    count: i64,
+    mean: f64,
+    squared_differences: f64
+}
+
+impl Moments {
+    fn of(value: Option<f64>) -> Self {
+        match value {
+            Some(value) => Moments { count: 1, mean: value, squared_differences: 0.0 },
+            None => Moments::default()
+        }
+    }
+
+    fn merge(&self, other: &Moments) -> Moments {
+        let count = self.count + other.count;
+        if count == 0 {
+            return Moments::default();
+        }
+        let delta = other.mean - self.mean;
+        Moments {
+            count,
+            mean: self.mean + delta * other.count as f64 / count as f64,
+            squared_differences: self.squared_differences + other.squared_differences
+                + delta * delta * (self.count as f64 * other.count as f64) / count as f64
         }
     }
+
+    /// Sample variance of the values
+    fn variance(&self) -> Option<f64> {
+        (self.count > 1).then(|| self.squared_differences / (self.count - 1) as f64)
+    }
 }
 
 impl SelectField {
@@ -160,54 +290,201 @@ impl SelectField {
     fn groups(a: &SelectField, b: &SelectField) -> bool {
         match (a, b) {
             (SelectField::Field(field_a), SelectField::Field(field_b)) => field_a == field_b,
-            (SelectField::Count(_), SelectField::Count(_)) => true,
-            (SelectField::Max(_), SelectField::Max(_)) => true,
-            (SelectField::Min(_), SelectField::Min(_)) => true,
-            (SelectField::Sum(_), SelectField::Sum(_)) => true,
-            (_, _) => false,
+            (SelectField::Field(_), _) | (_, SelectField::Field(_)) => false,
+            (a, b) => std::mem::discriminant(a) == std::mem::discriminant(b),
         }
     }
 
-    fn combine(&mut self, next: SelectField) {
-        if let SelectField::Field(_) = self {
-            return;
+    /// Merges the partial aggregates of another row into the ones of the same group, column by column
+    pub(crate) fn combine_all(aggregates: &mut [SelectField], next: Vec<SelectField>) -> DBResult<()> {
+        if aggregates.len() != next.len() {
+            return Err(EvaluationError::from_str("The rows of a group have a different number of aggregates").into());
+        }
+        for (aggregate, next) in aggregates.iter_mut().zip(next) {
+            aggregate.combine(next)?;
         }
-        let field = match (&self, next) {
+        Ok(())
+    }
+
+    /// Merges another partial aggregate of the same kind into this one.
+    /// Merging is associative, so partial aggregates may be combined in any grouping.
+    pub(crate) fn combine(&mut self, next: SelectField) -> DBResult<()> {
+        match (self, next) {
+            (SelectField::Field(_), _) => { },
             (SelectField::Sum(self_field), SelectField::Sum(next_field)) => {
-                SelectField::Sum(match (self_field, next_field) {
-                    (ObjectField::I32(a), ObjectField::I32(b)) => ObjectField::I32(a + b),
-                    (ObjectField::I64(a), ObjectField::I64(b)) => ObjectField::I64(a + b),
-                    (ObjectField::Decimal(a), ObjectField::Decimal(b)) => ObjectField::Decimal(a + b),
-                    (_, _) => unimplemented!(),
-                })
+                *self_field = Self::add(self_field, &next_field);
             },
-            (SelectField::Count(ObjectField::I64(self_i64)), SelectField::Count(ObjectField::Bool(b))) => {
-                SelectField::Count(ObjectField::I64(*self_i64 + b as i64))
+            (SelectField::Count(self_field), SelectField::Count(next_field)) => {
+                *self_field = ObjectField::I64(Self::count_of(self_field) + Self::count_of(&next_field));
             },
             (SelectField::Max(self_field), SelectField::Max(next_field)) => {
-                let field: &ObjectField = self_field;
-                if field < &next_field {
-                    SelectField::Max(next_field)
-                } else {
-                    SelectField::Max(self_field.clone())
+                if self_field.is_null() || (!next_field.is_null() && *self_field < next_field) {
+                    *self_field = next_field;
                 }
             },
             (SelectField::Min(self_field), SelectField::Min(next_field)) => {
-                let field: &ObjectField = self_field;
-                if field > &next_field {
-                    SelectField::Max(next_field)
-                } else {
-                    SelectField::Max(self_field.clone())
+                if self_field.is_null() || (!next_field.is_null() && *self_field > next_field) {
+                    *self_field = next_field;
                 }
             },
-            (_, _) => unreachable!()
-        };
-        *self = field;
+            (SelectField::Avg(self_sum, self_count), SelectField::Avg(next_sum, next_count)) => {
+                *self_sum += next_sum;
+                *self_count += next_count;
+            },
+            (SelectField::CountDistinct(values), SelectField::CountDistinct(next_values)) => {
+                values.extend(next_values);
+            },
+            (SelectField::Variance(moments), SelectField::Variance(next_moments))
+            | (SelectField::StdDev(moments), SelectField::StdDev(next_moments)) => {
+                *moments = moments.merge(&next_moments);
+            },
+            (SelectField::Percentile(digest, _), SelectField::Percentile(next_digest, _)) => {
+                digest.merge(&next_digest);
+            },
+            (SelectField::First(_), SelectField::First(_)) => { },
+            (SelectField::Last(self_field), SelectField::Last(next_field)) => {
+                *self_field = next_field;
+            },
+            (SelectField::StringAgg(value, separator), SelectField::StringAgg(next_value, _)) => {
+                match (value.as_mut(), next_value) {
+                    (Some(value), Some(next_value)) => {
+                        value.push_str(separator);
+                        value.push_str(&next_value);
+                    },
+                    (None, next_value) => *value = next_value,
+                    (Some(_), None) => { }
+                }
+            },
+            (SelectField::ArrayAgg(values), SelectField::ArrayAgg(next_values)) => {
+                values.extend(next_values);
+            },
+            (_, _) => return Err(EvaluationError::from_str("Aggregates of different kinds can not be combined").into())
+        }
+        Ok(())
+    }
+
+    /// Aggregate of the same kind over no rows - COUNT is 0, and the other aggregates are null or empty
+    pub(crate) fn empty(&self) -> SelectField {
+        match self {
+            SelectField::Field(_) => SelectField::Field(ObjectField::Null),
+            SelectField::Sum(_) => SelectField::Sum(ObjectField::Null),
+            SelectField::Count(_) => SelectField::Count(ObjectField::I64(0)),
+            SelectField::Max(_) => SelectField::Max(ObjectField::Null),
+            SelectField::Min(_) => SelectField::Min(ObjectField::Null),
+            SelectField::Avg(..) => SelectField::Avg(0.0, 0),
+            SelectField::CountDistinct(_) => SelectField::CountDistinct(HashSet::new()),
+            SelectField::Variance(_) => SelectField::Variance(Moments::default()),
+            SelectField::StdDev(_) => SelectField::StdDev(Moments::default()),
+            SelectField::Percentile(_, quantile) => SelectField::Percentile(TDigest::default(), *quantile),
+            SelectField::First(_) => SelectField::First(ObjectField::Null),
+            SelectField::Last(_) => SelectField::Last(ObjectField::Null),
+            SelectField::StringAgg(_, separator) => SelectField::StringAgg(None, separator.clone()),
+            SelectField::ArrayAgg(_) => SelectField::ArrayAgg(vec![])
+        }
+    }
+
+    /// Adds two numeric values, promoting the result to the wider of the types - I32, I64, then Decimal.
+    /// Nulls and non-numeric values are skipped.
+    fn add(a: &ObjectField, b: &ObjectField) -> ObjectField {
+        match (a, b) {
+            (ObjectField::I32(a), ObjectField::I32(b)) => a
+                .checked_add(*b)
+                .map_or_else(|| ObjectField::I64(i64::from(*a) + i64::from(*b)), ObjectField::I32),
+            (ObjectField::I32(a), ObjectField::I64(b)) | (ObjectField::I64(b), ObjectField::I32(a)) => Self::add_i64(i64::from(*a), *b),
+            (ObjectField::I64(a), ObjectField::I64(b)) => Self::add_i64(*a, *b),
+            (a, b) => match (Self::numeric_value(a), Self::numeric_value(b)) {
+                (Some(a), Some(b)) => ObjectField::Decimal(a + b),
+                (Some(_), None) => a.clone(),
+                (None, Some(_)) => b.clone(),
+                (None, None) => ObjectField::Null
+            }
+        }
+    }
+
+    /// Adds two I64 values, promoting the result to Decimal on overflow
+    fn add_i64(a: i64, b: i64) -> ObjectField {
+        a.checked_add(b)
+            .map_or_else(|| ObjectField::Decimal(a as f64 + b as f64), ObjectField::I64)
+    }
+
+    fn numeric_value(field: &ObjectField) -> Option<f64> {
+        match field {
+            ObjectField::I32(i) => Some(f64::from(*i)),
+            ObjectField::I64(i) => Some(*i as f64),
+            ObjectField::Decimal(d) => Some(*d),
+            _ => None
+        }
+    }
+
+    fn count_of(field: &ObjectField) -> i64 {
+        match field {
+            ObjectField::Bool(b) => *b as i64,
+            ObjectField::I64(count) => *count,
+            _ => unreachable!()
+        }
+    }
+
+    /// Converts the (partial) aggregate into its final value
+    pub(crate) fn finish(self) -> ObjectField {
+        match self {
+            SelectField::Field(object_field) => object_field,
+            SelectField::Sum(object_field) => object_field,
+            SelectField::Count(object_field) => ObjectField::I64(Self::count_of(&object_field)),
+            SelectField::Max(object_field) => object_field,
+            SelectField::Min(object_field) => object_field,
+            SelectField::Avg(_, 0) => ObjectField::Null,
+            SelectField::Avg(sum, count) => ObjectField::Decimal(sum / count as f64),
+            SelectField::CountDistinct(values) => ObjectField::I64(values.len() as i64),
+            SelectField::Variance(moments) => moments.variance().map_or(ObjectField::Null, ObjectField::Decimal),
+            SelectField::StdDev(moments) => moments.variance().map_or(ObjectField::Null, |variance| ObjectField::Decimal(variance.sqrt())),
+            SelectField::Percentile(mut digest, quantile) => digest.quantile(quantile).map_or(ObjectField::Null, ObjectField::Decimal),
+            SelectField::First(object_field) => object_field,
+            SelectField::Last(object_field) => object_field,
+            SelectField::StringAgg(None, _) => ObjectField::Null,
+            SelectField::StringAgg(Some(mut value), _) => {
+                if value.len() > MAX_FIELD_LENGTH {
+                    warn!("STRING_AGG result truncated to {} bytes", MAX_FIELD_LENGTH);
+                    let mut length = MAX_FIELD_LENGTH;
+                    while !value.is_char_boundary(length) {
+                        length -= 1;
+                    }
+                    value.truncate(length);
+                }
+                value.into()
+            },
+            SelectField::ArrayAgg(mut values) => {
+                let mut array = EntryFields::from(values.as_slice());
+                while array.0.len() > MAX_FIELD_LENGTH {
+                    warn!("ARRAY_AGG result truncated to {} elements", values.len() - 1);
+                    values.pop();
+                    array = EntryFields::from(values.as_slice());
+                }
+                ObjectField::Bytes(array.1[array.0.clone()].into())
+            },
+        }
+    }
+
+    /// Approximate number of bytes held by the aggregate
+    pub(crate) fn memory_size(&self) -> usize {
+        match self {
+            SelectField::Field(object_field)
+            | SelectField::Sum(object_field)
+            | SelectField::Count(object_field)
+            | SelectField::Max(object_field)
+            | SelectField::Min(object_field)
+            | SelectField::First(object_field)
+            | SelectField::Last(object_field) => object_field.memory_size(),
+            SelectField::Avg(..) | SelectField::Variance(_) | SelectField::StdDev(_) => std::mem::size_of::<Self>(),
+            SelectField::CountDistinct(values) => std::mem::size_of::<Self>() + values.capacity() * std::mem::size_of::<ObjectField>(),
+            SelectField::Percentile(digest, _) => std::mem::size_of::<Self>() + digest.len() * std::mem::size_of::<(f64, f64)>(),
+            SelectField::StringAgg(value, separator) => std::mem::size_of::<Self>() + value.as_ref().map_or(0, String::capacity) + separator.len(),
+            SelectField::ArrayAgg(values) => std::mem::size_of::<Self>() + values.iter().map(ObjectField::memory_size).sum::<usize>(),
+        }
     }
 }
 
 impl<'a> SelectBuilder<'a> {
-    fn new(row: &'a EntryFields) -> Self {
+    pub(crate) fn new(row: &'a EntryFields) -> Self {
         SelectBuilder {
             row,
             fields: vec![]
@@ -244,6 +521,86 @@ impl<'a> SelectBuilder<'a> {
         self
     }
 
+    pub fn avg_value<T: Borrow<impl Into<ObjectField> + Clone>>(mut self, value: T) -> Self {
+        let value = SelectField::numeric_value(&value.borrow().clone().into());
+        self.fields.push(match value {
+            Some(value) => SelectField::Avg(value, 1),
+            None => SelectField::Avg(0.0, 0)
+        });
+        self
+    }
+
+    /// Sample variance of the numeric values
+    pub fn variance_value<T: Borrow<impl Into<ObjectField> + Clone>>(mut self, value: T) -> Self {
+        let value = SelectField::numeric_value(&value.borrow().clone().into());
+        self.fields.push(SelectField::Variance(Moments::of(value)));
+        self
+    }
+
+    /// Sample standard deviation of the numeric values
+    pub fn stddev_value<T: Borrow<impl Into<ObjectField> + Clone>>(mut self, value: T) -> Self {
+        let value = SelectField::numeric_value(&value.borrow().clone().into());
+        self.fields.push(SelectField::StdDev(Moments::of(value)));
+        self
+    }
+
+    /// Approximate median of the numeric values
+    pub fn median_value<T: Borrow<impl Into<ObjectField> + Clone>>(self, value: T) -> Self {
+        self.percentile_value(value, 0.5)
+    }
+
+    /// Approximate value at the given percentile (from the range `0.0..=1.0`) of the numeric values
+    pub fn percentile_value<T: Borrow<impl Into<ObjectField> + Clone>>(mut self, value: T, percentile: f64) -> Self {
+        let mut digest = TDigest::default();
+        if let Some(value) = SelectField::numeric_value(&value.borrow().clone().into()) {
+            digest.add(value);
+        }
+        self.fields.push(SelectField::Percentile(digest, percentile));
+        self
+    }
+
+    pub fn first_value<T: Borrow<impl Into<ObjectField> + Clone>>(mut self, value: T) -> Self {
+        self.fields.push(SelectField::First(value.borrow().clone().into()));
+        self
+    }
+
+    pub fn last_value<T: Borrow<impl Into<ObjectField> + Clone>>(mut self, value: T) -> Self {
+        self.fields.push(SelectField::Last(value.borrow().clone().into()));
+        self
+    }
+
+    /// Concatenates the values with the separator - the result is truncated to the maximum string length
+    pub fn string_agg<T: Borrow<impl Into<ObjectField> + Clone>>(mut self, value: T, separator: &str) -> Self {
+        let value = match value.borrow().clone().into() {
+            ObjectField::Bool(b) => Some(b.to_string()),
+            ObjectField::I32(i) => Some(i.to_string()),
+            ObjectField::I64(i) => Some(i.to_string()),
+            ObjectField::Decimal(d) => Some(d.to_string()),
+            ObjectField::Id(uuid) => Some(uuid.to_string()),
+            ObjectField::String(string) => Some(string.to_string()),
+            ObjectField::Bytes(_) | ObjectField::Null => None,
+        };
+        self.fields.push(SelectField::StringAgg(value, separator.into()));
+        self
+    }
+
+    /// Collects the values into a byte array, encoded like the row's fields - see [`ObjectField::as_array`].
+    /// Trailing values not fitting in the maximum byte array length are dropped.
+    pub fn array_agg<T: Borrow<impl Into<ObjectField> + Clone>>(mut self, value: T) -> Self {
+        self.fields.push(SelectField::ArrayAgg(vec![value.borrow().clone().into()]));
+        self
+    }
+
+    pub fn count_distinct<T: Borrow<impl Into<ObjectField> + Clone>>(mut self, value: T) -> Self {
+        let value: ObjectField = value.borrow().clone().into();
+        let mut values = HashSet::new();
+        if !value.is_null() {
+            values.insert(value);
+        }
+        self.fields.push(SelectField::CountDistinct(values));
+        self
+    }
+
     pub fn count(mut self) -> Self {
         self.fields.push(SelectField::Count(true.into()));
         self
@@ -258,10 +615,15 @@ impl<'a> SelectBuilder<'a> {
             ObjectField::Id(uuid) => !uuid.is_nil(),
             ObjectField::Bytes(cow) => !cow.is_empty(),
             ObjectField::String(cow) => !cow.is_empty(),
+            ObjectField::Null => false,
         }.into()));
         self
     }
 
+    pub(crate) fn into_fields(self) -> Vec<SelectField> {
+        self.fields
+    }
+
     pub fn column(self, index: usize) -> Self {
         let column = self.row.column(index);
         self.value(column)
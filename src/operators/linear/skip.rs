@@ -1,4 +1,5 @@
-use crate::{DBOperator, DBResult, Row};
+use crate::{DBOperator, DBResult, EntryFields, Row};
+use crate::storage::storage_config::DatabaseConfig;
 
 #[derive(Clone)]
 pub struct Skip<Iter: DBOperator> {
@@ -38,6 +39,11 @@ impl<Iter: DBOperator> DBOperator for Skip<Iter> {
         self.skipped = 0;
     }
 
+    fn rebind(&mut self, outer_row: &EntryFields) {
+        self.iterator.rebind(outer_row);
+        self.skipped = 0;
+    }
+
     fn size_hint(&self) -> (usize, Option<usize>) {
         let (min_size, max_size) = self.iterator.size_hint();
         (
@@ -45,4 +51,8 @@ impl<Iter: DBOperator> DBOperator for Skip<Iter> {
             max_size.map(|max_size| max_size.saturating_sub(self.skipped))
         )
     }
+
+    fn storage_config(&self) -> Option<&DatabaseConfig> {
+        self.iterator.storage_config()
+    }
 }
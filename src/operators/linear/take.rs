@@ -1,6 +1,7 @@
 use std::{cmp::min};
 
-use crate::{DBOperator, DBResult, Row};
+use crate::{DBOperator, DBResult, EntryFields, Row};
+use crate::storage::storage_config::DatabaseConfig;
 
 #[derive(Clone)]
 pub struct Take<Iter: DBOperator> {
@@ -39,6 +40,11 @@ impl<Iter: DBOperator> DBOperator for Take<Iter> {
         self.taken = 0;
     }
 
+    fn rebind(&mut self, outer_row: &EntryFields) {
+        self.iterator.rebind(outer_row);
+        self.taken = 0;
+    }
+
     fn size_hint(&self) -> (usize, Option<usize>) {
         let iterator_left = self.items - self.taken;
         let (min_size, max_size) = self.iterator.size_hint();
@@ -47,4 +53,8 @@ impl<Iter: DBOperator> DBOperator for Take<Iter> {
             Some(max_size.map_or(iterator_left, |max_size| min(max_size, iterator_left)))
         )
     }
+
+    fn storage_config(&self) -> Option<&DatabaseConfig> {
+        self.iterator.storage_config()
+    }
 }
@@ -1,5 +1,5 @@
 use serde::Deserialize;
-use crate::{storage::log_file::log_entry::Row, DBResult, EntryFields, ObjectField};
+use crate::{storage::{log_file::log_entry::Row, storage_config::DatabaseConfig}, DBResult, EntryFields, ObjectField};
 
 mod deserializing; pub use deserializing::*;
 mod sourcing; pub use sourcing::*;
@@ -7,17 +7,42 @@ mod linear; pub use linear::*;
 mod joining; pub use joining::*;
 mod sorting; pub use sorting::*;
 mod spools; pub use spools::*;
+mod aggregating; pub use aggregating::*;
+mod windowing; pub use windowing::*;
+mod parallel; pub use parallel::*;
+mod spill_file;
+mod row_batch; pub use row_batch::{RowBatch, BATCH_ROWS};
 
+/// Operator of a query plan, returning its rows one by one or in batches.
+/// Cloning an operator copies its state, so the clone continues from the same row - except for the operators running
+/// their inputs on other threads, like [`Exchange`] and [`ParallelPipeline`], whose clones start over from the first row.
 pub trait DBOperator: Sized + Clone {
     /// Retrieves the next row from the operator.
     /// An [`Ok(None)`] here means the operator has reached the end of the stream.
     /// And [`Err(e)`] means an operator has encountered an error - next call to this method _may_ recover. 
     fn next(&mut self) -> DBResult<Option<Row>>;
 
+    /// Retrieves up to [`BATCH_ROWS`] next rows from the operator at once.
+    /// An empty batch means the operator has reached the end of the stream.
+    /// Operators without their own batch implementation return the rows of [`DBOperator::next`] - the rows read before an error are then dropped.
+    fn next_batch(&mut self) -> DBResult<RowBatch> {
+        let mut rows = Vec::with_capacity(self.size_hint().0.clamp(1, BATCH_ROWS));
+        while rows.len() < BATCH_ROWS && let Some(row) = self.next()? {
+            rows.push(row);
+        }
+        Ok(RowBatch::new(rows))
+    }
+
     /// Resets the operator, as if it was newly created.
     /// Some operators may choose to retain the caches they have created - specifically spool operators.
     fn reset(&mut self);
 
+    /// Resets the operator before it is iterated again for the next row of an outer operator, like the inner input of a nested loop.
+    /// Operators depending on the outer row (like [`IndexSpool`]) use it to return only the rows relevant to it.
+    fn rebind(&mut self, _outer_row: &EntryFields) {
+        self.reset();
+    }
+
     /// Returns the bounds on the remaining length of the operator.
     ///
     /// Specifically, `size_hint()` returns a tuple where the first element
@@ -39,14 +64,33 @@ pub trait DBOperator: Sized + Clone {
         None
     }
 
+    /// Returns the configuration of the storage the rows of the operator are read from.
+    /// Operators built on top of it take their spill and execution configuration from it.
+    /// A [`None`] here means the rows do not come from a storage, like the ones of [`ConstantScan`].
+    fn storage_config(&self) -> Option<&DatabaseConfig> {
+        None
+    }
+
     fn select<Selector: Clone + for<'x> FnOnce(SelectBuilder<'x>, &EntryFields) -> SelectBuilder<'x>>(self, selector: Selector) -> Select<Self, Selector> {
         Select::new(self, selector)
     }
 
+    /// Selects the values of the columns - in batch mode straight from the decoded columns of the batches.
+    /// Rows without a column get a null in its place.
+    fn select_columns(self, columns: &[usize]) -> Select<Self, ColumnSelector> {
+        Select::new(self, ColumnSelector::new(columns))
+    }
+
     fn filter<Predicate: Clone + Fn(&EntryFields) -> bool>(self, predicate: Predicate) -> Filter<Self, Predicate> {
         Filter::new(self, predicate)
     }
 
+    /// Keeps the rows for which the predicate on the value of the column holds - in batch mode it runs over the decoded column of the batches.
+    /// Rows without the column are tested with a null.
+    fn filter_column<Predicate: Clone + Fn(&ObjectField) -> bool>(self, column: usize, predicate: Predicate) -> Filter<Self, ColumnPredicate<Predicate>> {
+        Filter::new(self, ColumnPredicate::new(column, predicate))
+    }
+
     fn deserialize<'a, D: Deserialize<'a>>(self) -> Deserializing<'a, Self, D> {
         Deserializing::new(self)
     }
@@ -59,6 +103,73 @@ pub trait DBOperator: Sized + Clone {
         Skip::new(self, items)
     }
 
+    /// Caches the rows on the first pass, replaying them after a reset.
+    fn table_spool(self) -> TableSpool<Self> {
+        TableSpool::new(self)
+    }
+
+    /// Counts the rows on the first pass, replaying the count of rows without columns after a reset.
+    fn row_count_spool(self) -> RowCountSpool<Self> {
+        RowCountSpool::new(self)
+    }
+
+    /// Indexes the rows by `key_function` on the first pass.
+    /// When rebound to an outer row, returns only the rows with the key returned by `outer_key_function` for that row.
+    fn index_spool<Key: JoinKey, KeyFunction: Clone + Fn(&EntryFields) -> Key, OuterKeyFunction: Clone + Fn(&EntryFields) -> Key>(self, key_function: KeyFunction, outer_key_function: OuterKeyFunction) -> IndexSpool<Self, Key, KeyFunction, OuterKeyFunction> {
+        IndexSpool::new(self, key_function, outer_key_function)
+    }
+
+    /// Returns the rows of this input, followed by the rows of the other one - both have to have the same number of columns.
+    fn concat<Iter2: DBOperator>(self, other: Iter2) -> Concatenate<Self, Iter2> {
+        Concatenate::new(self, other)
+    }
+
+    /// Returns the rows of both inputs, keeping the duplicates - the same as [`DBOperator::concat`].
+    fn union_all<Iter2: DBOperator>(self, other: Iter2) -> Concatenate<Self, Iter2> {
+        Concatenate::new(self, other)
+    }
+
+    /// Returns the rows of both inputs, without the rows with the same values as an earlier one.
+    fn union<Iter2: DBOperator>(self, other: Iter2) -> Distinct<Concatenate<Self, Iter2>, RowValuesFunction> {
+        Distinct::new(Concatenate::new(self, other), row_values)
+    }
+
+    /// Returns the rows also present in the other input, each only once.
+    fn intersect<Iter2: DBOperator>(self, other: Iter2) -> SetOperation<Self, Iter2> {
+        SetOperation::new(self, other, SetOperationKind::Intersect)
+    }
+
+    /// Returns the rows also present in the other input, as many times as they are present in both.
+    fn intersect_all<Iter2: DBOperator>(self, other: Iter2) -> SetOperation<Self, Iter2> {
+        SetOperation::new(self, other, SetOperationKind::IntersectAll)
+    }
+
+    /// Returns the rows not present in the other input, each only once.
+    fn except<Iter2: DBOperator>(self, other: Iter2) -> SetOperation<Self, Iter2> {
+        SetOperation::new(self, other, SetOperationKind::Except)
+    }
+
+    /// Returns the rows without as many of their copies as are present in the other input.
+    fn except_all<Iter2: DBOperator>(self, other: Iter2) -> SetOperation<Self, Iter2> {
+        SetOperation::new(self, other, SetOperationKind::ExceptAll)
+    }
+
+    /// Returns the first of the rows with the same values in all the columns.
+    fn distinct(self) -> Distinct<Self, RowValuesFunction> {
+        Distinct::new(self, row_values)
+    }
+
+    /// Returns the first of the rows with the same key returned by `key_function`.
+    fn distinct_by<KeyFunction: Clone + Fn(&EntryFields) -> Vec<ObjectField>>(self, key_function: KeyFunction) -> Distinct<Self, KeyFunction> {
+        Distinct::new(self, key_function)
+    }
+
+    /// Returns the first of the rows with the same key returned by `key_function`, streaming an input sorted on the key.
+    fn sorted_distinct_by<KeyFunction: Clone + Fn(&EntryFields) -> Vec<ObjectField>>(self, key_function: KeyFunction) -> SortedDistinct<Self, KeyFunction> {
+        SortedDistinct::new(self, key_function)
+    }
+
+    /// Retrieves all the rows, in batches.
     fn collect(mut self) -> DBResult<Vec<Row>> {
         if cfg!(debug_assertions) {
             let hint = self.size_hint();
@@ -66,22 +177,79 @@ pub trait DBOperator: Sized + Clone {
         }
         let mut result = Vec::with_capacity(self.size_hint().0.saturating_add(1));
         loop {
-            match self.next()? {
-                Some(data) => result.push(data),
-                None => return Ok(result),
+            let batch = self.next_batch()?;
+            if batch.is_empty() {
+                return Ok(result);
             }
+            result.extend(batch.into_rows());
         }
     }
 
-    fn nested_loop<Iter2: DBOperator>(self, iter2: Iter2, a_column_index: usize, b_column_index: usize) -> NestedLoop<Self, Iter2> {
-        NestedLoop::new(self, iter2, a_column_index, b_column_index)
+    /// Groups the rows by the key returned from `key_function`, aggregating the rows of every group with `aggregates`.
+    /// Returns a row for every group, with the key columns followed by the aggregated ones.
+    /// Without key columns, a single row is returned even for no input rows - the functions are then evaluated on a row of nulls, to find out the aggregates.
+    fn group_by<KeyFunction: Clone + Fn(&EntryFields) -> Vec<ObjectField>, Aggregates: Clone + for<'x> Fn(SelectBuilder<'x>, &EntryFields) -> SelectBuilder<'x>>(self, key_function: KeyFunction, aggregates: Aggregates) -> HashAggregate<Self, KeyFunction, Aggregates> {
+        HashAggregate::new(self, key_function, aggregates)
+    }
+
+    /// Computes the window functions over the rows partitioned by `partition_function` and ordered by `order_function`.
+    /// The results are appended to every row as new columns, in the order of the functions.
+    fn window<PartitionFunction: Clone + Fn(&EntryFields) -> Vec<ObjectField>, OrderFunction: Clone + Fn(&EntryFields) -> Vec<ObjectField>>(self, partition_function: PartitionFunction, order_function: OrderFunction, functions: Vec<WindowFunction>) -> Window<Self, PartitionFunction, OrderFunction> {
+        Window::new(self, partition_function, order_function, functions)
+    }
+
+    /// Runs the pipeline built by `pipeline` on a pool of worker threads, over morsels of the rows - the rows are returned in no particular order.
+    fn parallel<Output: DBOperator, Pipeline: Clone + Send + 'static + Fn(Morsel) -> Output>(self, pipeline: Pipeline) -> ParallelPipeline<Self, Pipeline, Output> {
+        ParallelPipeline::new(self, pipeline)
+    }
+
+    /// Groups the rows returned by the pipeline built by `pipeline` like [`DBOperator::group_by`], running both on a pool of worker threads.
+    fn parallel_group_by<Output: DBOperator, Pipeline: Clone + Send + 'static + Fn(Morsel) -> Output, KeyFunction: Clone + Send + 'static + Fn(&EntryFields) -> Vec<ObjectField>, Aggregates: Clone + Send + 'static + for<'x> Fn(SelectBuilder<'x>, &EntryFields) -> SelectBuilder<'x>>(self, pipeline: Pipeline, key_function: KeyFunction, aggregates: Aggregates) -> ParallelAggregate<Self, Pipeline, Output, KeyFunction, Aggregates> {
+        ParallelAggregate::new(self, pipeline, key_function, aggregates)
     }
 
-    fn hash_match<HashedIter: DBOperator, Key: Into<ObjectField> + Clone, IterGetter: Clone + Fn(&EntryFields) -> Key, HashedGetter: Clone + Fn(&EntryFields) -> Key>(self, hashed_iterator: HashedIter, iter_getter: IterGetter, hashed_getter: HashedGetter) -> HashMatch<Self, HashedIter, Key, IterGetter, HashedGetter> {
-        HashMatch::new(self, hashed_iterator, iter_getter, hashed_getter)
+    /// Joins the rows with the ones of the other input for which the predicate holds - it gets the fields of the row, then of the other input's row.
+    fn nested_loop<Iter2: DBOperator, Predicate: Clone + Fn(&EntryFields, &EntryFields) -> bool>(self, iter2: Iter2, predicate: Predicate, join_kind: JoinKind) -> NestedLoop<Self, Iter2, Predicate> {
+        NestedLoop::new(self, iter2, predicate, join_kind)
     }
 
+    fn hash_match<HashedIter: DBOperator, Key: JoinKey, IterGetter: Clone + Fn(&EntryFields) -> Key, HashedGetter: Clone + Fn(&EntryFields) -> Key>(self, hashed_iterator: HashedIter, iter_getter: IterGetter, hashed_getter: HashedGetter, join_kind: JoinKind) -> HashMatch<Self, HashedIter, Key, IterGetter, HashedGetter> {
+        HashMatch::new(self, hashed_iterator, iter_getter, hashed_getter, join_kind)
+    }
+
+    /// Joins the rows with the ones of the other input on equal keys - both inputs have to be sorted ascending on their keys.
+    fn merge_join<Iter2: DBOperator, Key: JoinKey, LeftKeyFunction: Clone + Fn(&EntryFields) -> Key, RightKeyFunction: Clone + Fn(&EntryFields) -> Key>(self, other: Iter2, left_key: LeftKeyFunction, right_key: RightKeyFunction) -> MergeJoin<Self, Iter2, Key, LeftKeyFunction, RightKeyFunction> {
+        MergeJoin::new(self, other, left_key, right_key)
+    }
+
+    /// Joins the rows with the ones of the other input on equal keys, by a nested loop if the other input is small, or by a hash match otherwise.
+    fn adaptive_join<Iter2: DBOperator, Key: JoinKey, LeftKeyFunction: Clone + Fn(&EntryFields) -> Key, RightKeyFunction: Clone + Fn(&EntryFields) -> Key>(self, other: Iter2, left_key: LeftKeyFunction, right_key: RightKeyFunction, join_kind: JoinKind) -> AdaptiveJoin<Self, Iter2, Key, LeftKeyFunction, RightKeyFunction> {
+        AdaptiveJoin::new(self, other, left_key, right_key, join_kind)
+    }
+
+    /// Sorts the rows in memory by comparing their keys - [`DBOperator::radix_sort`] sorts integer and id keys without comparisons.
     fn in_memory_sort<Key: Clone + Ord, KeyFunction: Clone + Fn(&EntryFields) -> Key>(self, key_function: KeyFunction, sort_direction: SortDirection) -> InMemorySort<Self, Key, KeyFunction> {
         InMemorySort::new(self, key_function, sort_direction)
     }
+
+    /// Sorts the rows in memory by a radix sort of their integer or id keys, without comparing them.
+    fn radix_sort<Key: RadixKey, KeyFunction: Clone + Fn(&EntryFields) -> Key>(self, key_function: KeyFunction, sort_direction: SortDirection) -> RadixSort<Self, Key, KeyFunction> {
+        RadixSort::new(self, key_function, sort_direction)
+    }
+
+    /// Sorts the rows in memory, in the order built with [`OrderBy`].
+    fn order_by(self, order_by: OrderBy) -> InMemorySort<Self, SortKey, impl Clone + Fn(&EntryFields) -> SortKey> {
+        InMemorySort::new(self, order_by.key_function(), SortDirection::Ascending)
+    }
+
+    /// Sorts the rows within a memory budget, spilling sorted runs to disk and merging them lazily.
+    /// The budget defaults to the spill configuration of the storage the rows are read from, and can be changed with [`ExternalSort::with_spill_config`].
+    fn external_sort<Key: Clone + Ord, KeyFunction: Clone + Fn(&EntryFields) -> Key>(self, key_function: KeyFunction, sort_direction: SortDirection) -> ExternalSort<Self, Key, KeyFunction> {
+        ExternalSort::new(self, key_function, sort_direction)
+    }
+
+    /// Returns the first `count` rows in the sort order, holding at most `count` rows in memory.
+    fn top_n<Key: Clone + Ord, KeyFunction: Clone + Fn(&EntryFields) -> Key>(self, key_function: KeyFunction, sort_direction: SortDirection, count: usize) -> TopN<Self, Key, KeyFunction> {
+        TopN::new(self, key_function, sort_direction, count)
+    }
 }
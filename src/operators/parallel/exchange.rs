@@ -0,0 +1,130 @@
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use crate::errors::query_error::EvaluationError;
+use crate::storage::storage_config::ExecutionConfig;
+use crate::utils::GuardExtensions;
+use crate::{DBOperator, DBResult, Row};
+use crate::storage::storage_config::DatabaseConfig;
+
+/// Number of rows the inputs can produce ahead of the consumer, before their threads wait for it
+const EXCHANGE_BUFFER_ROWS: usize = 1024;
+
+/// Runs the inputs on a pool of worker threads, gathering their rows into a single stream, in the order they are produced.
+/// Every worker takes the next input as soon as the previous one runs out of rows.
+/// An error of an input is returned in its place in the stream, and that input stops - the other inputs continue.
+/// Resetting or cloning starts the inputs over - the threads of the previous run stop once they produce another row.
+pub struct Exchange<Iter: DBOperator + Send + 'static> {
+    inputs: Vec<Iter>,
+    execution_config: ExecutionConfig,
+    running: Option<RunningInputs>
+}
+
+struct RunningInputs {
+    receiver: Receiver<DBResult<Row>>,
+    handles: Vec<JoinHandle<()>>
+}
+
+impl<Iter: DBOperator + Send + 'static> Exchange<Iter> {
+    pub fn new(inputs: Vec<Iter>) -> Self {
+        let execution_config = ExecutionConfig::of_storage(inputs.iter().find_map(DBOperator::storage_config));
+        Exchange {
+            inputs,
+            execution_config,
+            running: None
+        }
+    }
+
+    /// Sets the number of the worker threads
+    pub fn with_execution_config(mut self, execution_config: ExecutionConfig) -> Self {
+        self.execution_config = execution_config;
+        self
+    }
+
+    fn start(&self) -> RunningInputs {
+        let (sender, receiver) = sync_channel(EXCHANGE_BUFFER_ROWS);
+        let queue = Arc::new(Mutex::new(self.inputs.clone().into_iter()));
+        let handles = (0..self.execution_config.threads().min(self.inputs.len()))
+            .map(|_| {
+                let queue = queue.clone();
+                let sender = sender.clone();
+                thread::spawn(move || loop {
+                    // Taken in its own statement, so the lock is not held while the input runs
+                    let next_input = queue.lock().not_poisoned().next();
+                    let Some(mut input) = next_input else {
+                        return;
+                    };
+                    loop {
+                        match input.next() {
+                            Ok(Some(row)) => if sender.send(Ok(row)).is_err() {
+                                return;
+                            },
+                            Ok(None) => break,
+                            Err(error) => {
+                                if sender.send(Err(error)).is_err() {
+                                    return;
+                                }
+                                break;
+                            }
+                        }
+                    }
+                })
+            })
+            .collect();
+        RunningInputs { receiver, handles }
+    }
+}
+
+impl<Iter: DBOperator + Send + 'static> Clone for Exchange<Iter> {
+    fn clone(&self) -> Self {
+        Exchange {
+            inputs: self.inputs.clone(),
+            execution_config: self.execution_config.clone(),
+            running: None
+        }
+    }
+}
+
+impl<Iter: DBOperator + Send + 'static> DBOperator for Exchange<Iter> {
+    fn next(&mut self) -> DBResult<Option<Row>> {
+        let running = match &mut self.running {
+            Some(running) => running,
+            None => self.running.insert(self.start())
+        };
+        match running.receiver.recv() {
+            Ok(result) => result.map(Some),
+            // All the inputs have finished - either by running out of rows, or by panicking
+            Err(_) => {
+                let panicked = running.handles
+                    .drain(..)
+                    .any(|handle| handle.join().is_err());
+                if panicked {
+                    return Err(EvaluationError::from_str("An input of the exchange panicked").into());
+                }
+                Ok(None)
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.running = None;
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.running.is_some() {
+            return (0, None);
+        }
+        self.inputs
+            .iter()
+            .map(DBOperator::size_hint)
+            .fold((0, Some(0)), |(low, high), (input_low, input_high)| (
+                low.saturating_add(input_low),
+                high.zip(input_high).and_then(|(a, b)| a.checked_add(b))
+            ))
+    }
+
+    fn storage_config(&self) -> Option<&DatabaseConfig> {
+        self.inputs.iter().find_map(DBOperator::storage_config)
+    }
+}
@@ -0,0 +1,4 @@
+mod exchange; pub use exchange::Exchange;
+mod worker_pool;
+mod parallel_pipeline; pub use parallel_pipeline::{Morsel, ParallelPipeline};
+mod parallel_aggregate; pub use parallel_aggregate::ParallelAggregate;
@@ -0,0 +1,173 @@
+use gxhash::{HashMap, HashMapExt};
+use uuid::Uuid;
+
+use crate::operators::aggregating::empty_group;
+use crate::operators::parallel::worker_pool::{read_morsel, MorselWorker, WorkerPool};
+use crate::storage::storage_config::ExecutionConfig;
+use crate::{DBOperator, DBResult, EntryFields, Morsel, ObjectField, Row, SelectBuilder, SelectField};
+use crate::storage::storage_config::DatabaseConfig;
+
+type Groups = HashMap<Vec<ObjectField>, Vec<SelectField>>;
+
+/// Groups the rows by a key like [`HashAggregate`](crate::HashAggregate), with the pipeline built by `pipeline` and the aggregation running on a pool of worker threads.
+/// Every worker aggregates its morsels into its own partial groups, which are merged once the input is exhausted.
+/// The groups are kept in memory, and aggregates depending on the order of the rows (like first or string aggregation) see the rows in no particular order.
+#[derive(Clone)]
+pub struct ParallelAggregate<Iter, Pipeline, Output, KeyFunction, Aggregates>
+where   Iter: DBOperator,
+        Pipeline: Clone + Send + 'static + Fn(Morsel) -> Output,
+        Output: DBOperator,
+        KeyFunction: Clone + Send + 'static + Fn(&EntryFields) -> Vec<ObjectField>,
+        Aggregates: Clone + Send + 'static + for<'x> Fn(SelectBuilder<'x>, &EntryFields) -> SelectBuilder<'x>
+{
+    iterator: Iter,
+    pipeline: Pipeline,
+    key_function: KeyFunction,
+    aggregates: Aggregates,
+    execution_config: ExecutionConfig,
+    groups: Option<std::vec::IntoIter<(Vec<ObjectField>, Vec<SelectField>)>>
+}
+
+impl<Iter, Pipeline, Output, KeyFunction, Aggregates> ParallelAggregate<Iter, Pipeline, Output, KeyFunction, Aggregates>
+where   Iter: DBOperator,
+        Pipeline: Clone + Send + 'static + Fn(Morsel) -> Output,
+        Output: DBOperator,
+        KeyFunction: Clone + Send + 'static + Fn(&EntryFields) -> Vec<ObjectField>,
+        Aggregates: Clone + Send + 'static + for<'x> Fn(SelectBuilder<'x>, &EntryFields) -> SelectBuilder<'x>
+{
+    pub fn new(iterator: Iter, pipeline: Pipeline, key_function: KeyFunction, aggregates: Aggregates) -> Self {
+        let execution_config = ExecutionConfig::of_storage(iterator.storage_config());
+        ParallelAggregate {
+            iterator,
+            pipeline,
+            key_function,
+            aggregates,
+            execution_config,
+            groups: None
+        }
+    }
+
+    /// Sets the number of the worker threads
+    pub fn with_execution_config(mut self, execution_config: ExecutionConfig) -> Self {
+        self.execution_config = execution_config;
+        self
+    }
+
+    fn aggregate(&mut self) -> DBResult<Groups> {
+        let workers = (0..self.execution_config.threads())
+            .map(|_| AggregateWorker {
+                pipeline: self.pipeline.clone(),
+                key_function: self.key_function.clone(),
+                aggregates: self.aggregates.clone(),
+                groups: Groups::new()
+            })
+            .collect();
+        let mut workers = WorkerPool::start(workers);
+        loop {
+            let morsel = read_morsel(&mut self.iterator)?;
+            if morsel.is_empty() {
+                break;
+            }
+            workers.send(morsel)?;
+        }
+        workers.close();
+
+        let mut groups = Groups::new();
+        while let Some(partial_groups) = workers.receive()? {
+            if groups.is_empty() {
+                groups = partial_groups;
+                continue;
+            }
+            for (key, partial_aggregates) in partial_groups {
+                match groups.get_mut(&key) {
+                    Some(aggregates) => SelectField::combine_all(aggregates, partial_aggregates)?,
+                    None => {
+                        groups.insert(key, partial_aggregates);
+                    }
+                }
+            }
+        }
+        Ok(groups)
+    }
+}
+
+/// Aggregates the rows of all the morsels it processes into partial groups
+struct AggregateWorker<Pipeline, KeyFunction, Aggregates> {
+    pipeline: Pipeline,
+    key_function: KeyFunction,
+    aggregates: Aggregates,
+    groups: Groups
+}
+
+impl<Pipeline, Output, KeyFunction, Aggregates> MorselWorker for AggregateWorker<Pipeline, KeyFunction, Aggregates>
+where   Pipeline: Send + 'static + Fn(Morsel) -> Output,
+        Output: DBOperator,
+        KeyFunction: Send + 'static + Fn(&EntryFields) -> Vec<ObjectField>,
+        Aggregates: Send + 'static + for<'x> Fn(SelectBuilder<'x>, &EntryFields) -> SelectBuilder<'x>
+{
+    type Output = Groups;
+
+    fn process(&mut self, morsel: Vec<Row>) -> DBResult<Option<Groups>> {
+        let mut rows = (self.pipeline)(Morsel::new(morsel));
+        while let Some(row) = rows.next()? {
+            let key = (self.key_function)(&row.fields);
+            let fields = (self.aggregates)(SelectBuilder::new(&row.fields), &row.fields).into_fields();
+            match self.groups.get_mut(&key) {
+                Some(aggregates) => SelectField::combine_all(aggregates, fields)?,
+                None => {
+                    self.groups.insert(key, fields);
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    fn finish(self) -> DBResult<Option<Groups>> {
+        Ok(Some(self.groups))
+    }
+}
+
+impl<Iter, Pipeline, Output, KeyFunction, Aggregates> DBOperator for ParallelAggregate<Iter, Pipeline, Output, KeyFunction, Aggregates>
+where   Iter: DBOperator,
+        Pipeline: Clone + Send + 'static + Fn(Morsel) -> Output,
+        Output: DBOperator,
+        KeyFunction: Clone + Send + 'static + Fn(&EntryFields) -> Vec<ObjectField>,
+        Aggregates: Clone + Send + 'static + for<'x> Fn(SelectBuilder<'x>, &EntryFields) -> SelectBuilder<'x>
+{
+    /// If a worker or the input fails, the input is reset, so the next call aggregates all the rows again
+    fn next(&mut self) -> DBResult<Option<Row>> {
+        if self.groups.is_none() {
+            let groups = self.aggregate().inspect_err(|_| self.iterator.reset())?;
+            let mut groups = groups.into_iter().collect::<Vec<_>>();
+            if groups.is_empty() {
+                groups.extend(empty_group(&self.key_function, &self.aggregates));
+            }
+            self.groups = Some(groups.into_iter());
+        }
+        let Some((key, aggregates)) = self.groups.as_mut().and_then(Iterator::next) else {
+            return Ok(None);
+        };
+        let mut fields = key;
+        fields.extend(aggregates.into_iter().map(SelectField::finish));
+        Ok(Some(Row {
+            id: Uuid::new_v4(),
+            fields: fields.into()
+        }))
+    }
+
+    fn reset(&mut self) {
+        self.iterator.reset();
+        self.groups = None;
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.groups {
+            None => (0, None),
+            Some(groups) => (groups.len(), Some(groups.len()))
+        }
+    }
+
+    fn storage_config(&self) -> Option<&DatabaseConfig> {
+        self.iterator.storage_config()
+    }
+}
@@ -0,0 +1,186 @@
+use crate::operators::parallel::worker_pool::{read_morsel, MorselWorker, WorkerPool};
+use crate::errors::query_error::EvaluationError;
+use crate::storage::storage_config::ExecutionConfig;
+use crate::{DBOperator, DBResult, Row};
+use crate::storage::storage_config::DatabaseConfig;
+
+/// Rows of the input handed to a worker at once - the source of the pipeline the worker runs
+#[derive(Clone)]
+pub struct Morsel {
+    rows: Vec<Row>,
+    position: usize
+}
+
+impl Morsel {
+    pub(crate) fn new(rows: Vec<Row>) -> Self {
+        Morsel {
+            rows,
+            position: 0
+        }
+    }
+}
+
+impl DBOperator for Morsel {
+    fn next(&mut self) -> DBResult<Option<Row>> {
+        let row = self.rows.get(self.position).cloned();
+        if row.is_some() {
+            self.position += 1;
+        }
+        Ok(row)
+    }
+
+    fn reset(&mut self) {
+        self.position = 0;
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.rows.len() - self.position;
+        (remaining, Some(remaining))
+    }
+}
+
+/// Splits the input into morsels, running the pipeline built by `pipeline` over each of them on a pool of worker threads.
+/// The input is read on the calling thread, so only the pipeline has to be [`Send`] - the rows are returned in no particular order.
+/// Resetting or cloning starts the input over, with new workers - the workers of the previous run stop once they finish their morsel.
+pub struct ParallelPipeline<Iter, Pipeline, Output>
+where   Iter: DBOperator,
+        Pipeline: Clone + Send + 'static + Fn(Morsel) -> Output,
+        Output: DBOperator
+{
+    iterator: Iter,
+    pipeline: Pipeline,
+    execution_config: ExecutionConfig,
+    workers: Option<WorkerPool<Vec<Row>>>,
+    /// Morsels sent to the workers, which did not return yet
+    in_flight: usize,
+    input_finished: bool,
+    output: std::vec::IntoIter<Row>
+}
+
+impl<Iter, Pipeline, Output> ParallelPipeline<Iter, Pipeline, Output>
+where   Iter: DBOperator,
+        Pipeline: Clone + Send + 'static + Fn(Morsel) -> Output,
+        Output: DBOperator
+{
+    pub fn new(iterator: Iter, pipeline: Pipeline) -> Self {
+        let execution_config = ExecutionConfig::of_storage(iterator.storage_config());
+        ParallelPipeline {
+            iterator,
+            pipeline,
+            execution_config,
+            workers: None,
+            in_flight: 0,
+            input_finished: false,
+            output: vec![].into_iter()
+        }
+    }
+
+    /// Sets the number of the worker threads
+    pub fn with_execution_config(mut self, execution_config: ExecutionConfig) -> Self {
+        self.execution_config = execution_config;
+        self
+    }
+}
+
+/// Runs the pipeline over every morsel, returning its rows
+struct PipelineWorker<Pipeline> {
+    pipeline: Pipeline
+}
+
+impl<Pipeline, Output> MorselWorker for PipelineWorker<Pipeline>
+where   Pipeline: Send + 'static + Fn(Morsel) -> Output,
+        Output: DBOperator
+{
+    type Output = Vec<Row>;
+
+    fn process(&mut self, morsel: Vec<Row>) -> DBResult<Option<Vec<Row>>> {
+        (self.pipeline)(Morsel::new(morsel)).collect().map(Some)
+    }
+
+    fn finish(self) -> DBResult<Option<Vec<Row>>> {
+        Ok(None)
+    }
+}
+
+impl<Iter, Pipeline, Output> Clone for ParallelPipeline<Iter, Pipeline, Output>
+where   Iter: DBOperator,
+        Pipeline: Clone + Send + 'static + Fn(Morsel) -> Output,
+        Output: DBOperator
+{
+    fn clone(&self) -> Self {
+        let mut iterator = self.iterator.clone();
+        iterator.reset();
+        ParallelPipeline {
+            iterator,
+            pipeline: self.pipeline.clone(),
+            execution_config: self.execution_config.clone(),
+            workers: None,
+            in_flight: 0,
+            input_finished: false,
+            output: vec![].into_iter()
+        }
+    }
+}
+
+impl<Iter, Pipeline, Output> DBOperator for ParallelPipeline<Iter, Pipeline, Output>
+where   Iter: DBOperator,
+        Pipeline: Clone + Send + 'static + Fn(Morsel) -> Output,
+        Output: DBOperator
+{
+    fn next(&mut self) -> DBResult<Option<Row>> {
+        loop {
+            if let Some(row) = self.output.next() {
+                return Ok(Some(row));
+            }
+            let threads = self.execution_config.threads();
+            let workers = match &mut self.workers {
+                Some(workers) => workers,
+                None => self.workers.insert(WorkerPool::start(
+                    (0..threads).map(|_| PipelineWorker { pipeline: self.pipeline.clone() }).collect()
+                ))
+            };
+
+            // Keeps every worker busy, with a morsel queued for it
+            while !self.input_finished && self.in_flight < threads * 2 {
+                let morsel = read_morsel(&mut self.iterator)?;
+                if morsel.is_empty() {
+                    self.input_finished = true;
+                    workers.close();
+                } else {
+                    workers.send(morsel)?;
+                    self.in_flight += 1;
+                }
+            }
+            if self.in_flight == 0 {
+                return Ok(None);
+            }
+            match workers.receive() {
+                Ok(Some(rows)) => {
+                    self.in_flight -= 1;
+                    self.output = rows.into_iter();
+                },
+                Ok(None) => return Err(EvaluationError::from_str("The workers of the parallel pipeline stopped before returning all the morsels").into()),
+                Err(error) => {
+                    self.in_flight = self.in_flight.saturating_sub(1);
+                    return Err(error);
+                }
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.iterator.reset();
+        self.workers = None;
+        self.in_flight = 0;
+        self.input_finished = false;
+        self.output = vec![].into_iter();
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+
+    fn storage_config(&self) -> Option<&DatabaseConfig> {
+        self.iterator.storage_config()
+    }
+}
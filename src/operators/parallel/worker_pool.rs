@@ -0,0 +1,105 @@
+use std::sync::mpsc::{channel, sync_channel, Receiver, Sender, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use crate::errors::query_error::EvaluationError;
+use crate::utils::GuardExtensions;
+use crate::{DBOperator, DBResult, Row};
+
+/// Number of rows the input is split into morsels of, for the workers to process
+pub(crate) const MORSEL_ROWS: usize = 4096;
+
+/// Processing of the morsels on a worker thread
+pub(crate) trait MorselWorker: Send + 'static {
+    type Output: Send + 'static;
+
+    /// Processes a morsel, with an output to return right away, if any
+    fn process(&mut self, morsel: Vec<Row>) -> DBResult<Option<Self::Output>>;
+
+    /// Returns the output left, once there are no more morsels
+    fn finish(self) -> DBResult<Option<Self::Output>>;
+}
+
+/// Worker threads taking the morsels from a shared queue, as soon as they are done with the previous one
+pub(crate) struct WorkerPool<Output: Send + 'static> {
+    morsels: Option<SyncSender<Vec<Row>>>,
+    results: Receiver<DBResult<Output>>,
+    handles: Vec<JoinHandle<()>>
+}
+
+impl<Output: Send + 'static> WorkerPool<Output> {
+    pub(crate) fn start<Worker: MorselWorker<Output = Output>>(workers: Vec<Worker>) -> Self {
+        let (morsel_sender, morsel_receiver) = sync_channel::<Vec<Row>>(workers.len());
+        let morsel_receiver = Arc::new(Mutex::new(morsel_receiver));
+        let (result_sender, results) = channel();
+        let handles = workers
+            .into_iter()
+            .map(|worker| {
+                let morsels = morsel_receiver.clone();
+                let results = result_sender.clone();
+                thread::spawn(move || Self::run(worker, &morsels, &results))
+            })
+            .collect();
+        WorkerPool {
+            morsels: Some(morsel_sender),
+            results,
+            handles
+        }
+    }
+
+    fn run<Worker: MorselWorker<Output = Output>>(mut worker: Worker, morsels: &Mutex<Receiver<Vec<Row>>>, results: &Sender<DBResult<Output>>) {
+        loop {
+            let morsel = morsels.lock().not_poisoned().recv();
+            let Ok(morsel) = morsel else {
+                break;
+            };
+            if let Some(result) = worker.process(morsel).transpose() && results.send(result).is_err() {
+                return;
+            }
+        }
+        if let Some(result) = worker.finish().transpose() {
+            let _ = results.send(result);
+        }
+    }
+
+    /// Queues the morsel, waiting while all the workers are busy
+    pub(crate) fn send(&self, morsel: Vec<Row>) -> DBResult<()> {
+        let sent = self.morsels
+            .as_ref()
+            .is_some_and(|morsels| morsels.send(morsel).is_ok());
+        if !sent {
+            return Err(EvaluationError::from_str("The workers of the parallel operator have stopped").into());
+        }
+        Ok(())
+    }
+
+    /// Lets the workers finish, once they process the queued morsels
+    pub(crate) fn close(&mut self) {
+        self.morsels = None;
+    }
+
+    /// Waits for the next output of the workers - [`None`] once all of them finished
+    pub(crate) fn receive(&mut self) -> DBResult<Option<Output>> {
+        match self.results.recv() {
+            Ok(result) => result.map(Some),
+            Err(_) => {
+                let panicked = self.handles
+                    .drain(..)
+                    .any(|handle| handle.join().is_err());
+                if panicked {
+                    return Err(EvaluationError::from_str("A worker of the parallel operator panicked").into());
+                }
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// Reads up to [`MORSEL_ROWS`] rows of the input - an empty morsel means the input is exhausted
+pub(crate) fn read_morsel(iterator: &mut impl DBOperator) -> DBResult<Vec<Row>> {
+    let mut morsel = Vec::with_capacity(MORSEL_ROWS);
+    while morsel.len() < MORSEL_ROWS && let Some(row) = iterator.next()? {
+        morsel.push(row);
+    }
+    Ok(morsel)
+}
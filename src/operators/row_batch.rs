@@ -0,0 +1,129 @@
+use std::cell::OnceCell;
+
+use log_err::LogErrOption;
+
+use crate::{ObjectField, Row};
+
+/// Maximum number of rows returned at once by [`DBOperator::next_batch`](crate::DBOperator::next_batch)
+pub const BATCH_ROWS: usize = 1024;
+
+/// Rows returned at once by [`DBOperator::next_batch`](crate::DBOperator::next_batch).
+/// The values of a column are decoded on the first access to it, into a vector with the value of every row - the other columns are not decoded.
+/// Rows without the column have a null in its place, like in [`DBOperator::filter_column`](crate::DBOperator::filter_column).
+#[derive(Clone, Default)]
+pub struct RowBatch {
+    rows: Vec<Row>,
+    /// Decoded values of every column, up to the width of the widest row
+    columns: OnceCell<Vec<OnceCell<Vec<ObjectField>>>>,
+    /// Values of the columns beyond the widest row
+    nulls: OnceCell<Vec<ObjectField>>
+}
+
+impl RowBatch {
+    pub fn new(rows: Vec<Row>) -> Self {
+        RowBatch {
+            rows,
+            columns: OnceCell::new(),
+            nulls: OnceCell::new()
+        }
+    }
+
+    /// Batch of rows whose columns are already decoded - every column holds a value for each of the rows
+    pub(crate) fn with_columns(rows: Vec<Row>, columns: Vec<Vec<ObjectField>>) -> Self {
+        RowBatch {
+            rows,
+            columns: OnceCell::from(columns.into_iter().map(OnceCell::from).collect::<Vec<_>>()),
+            nulls: OnceCell::new()
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn rows(&self) -> &[Row] {
+        &self.rows
+    }
+
+    pub fn into_rows(self) -> Vec<Row> {
+        self.rows
+    }
+
+    fn column_cells(&self) -> &[OnceCell<Vec<ObjectField>>] {
+        self.columns.get_or_init(|| {
+            let width = self.rows.iter().map(|row| row.fields.len()).max().unwrap_or(0);
+            (0..width).map(|_| OnceCell::new()).collect()
+        })
+    }
+
+    /// Number of the columns of the widest row
+    pub fn width(&self) -> usize {
+        self.column_cells().len()
+    }
+
+    /// Values of all the columns, a vector per column
+    pub fn columns(&self) -> Vec<&[ObjectField]> {
+        self.columns_of(&(0..self.width()).collect::<Vec<_>>())
+    }
+
+    /// Values of the column of every row
+    pub fn column(&self, index: usize) -> &[ObjectField] {
+        self.columns_of(&[index])[0]
+    }
+
+    /// Values of the columns of every row - the columns not decoded yet are decoded together, in a single pass over every row
+    pub fn columns_of(&self, indexes: &[usize]) -> Vec<&[ObjectField]> {
+        let cells = self.column_cells();
+        let mut selected = vec![false; cells.len()];
+        for &index in indexes {
+            if let Some(cell) = cells.get(index) && cell.get().is_none() {
+                selected[index] = true;
+            }
+        }
+        if selected.contains(&true) {
+            let mut columns: Vec<Vec<ObjectField>> = selected
+                .iter()
+                .map(|&selected| if selected { Vec::with_capacity(self.rows.len()) } else { vec![] })
+                .collect();
+            for (position, row) in self.rows.iter().enumerate() {
+                row.fields.selected_values(&selected, |index, value| columns[index].push(value));
+                for (column, _) in columns.iter_mut().zip(&selected).filter(|(_, selected)| **selected) {
+                    if column.len() == position {
+                        column.push(ObjectField::Null);
+                    }
+                }
+            }
+            for ((cell, column), selected) in cells.iter().zip(columns).zip(selected) {
+                if selected {
+                    let _ = cell.set(column);
+                }
+            }
+        }
+        indexes
+            .iter()
+            .map(|&index| match cells.get(index) {
+                Some(cell) => cell.get().log_unwrap().as_slice(),
+                None => self.nulls.get_or_init(|| vec![ObjectField::Null; self.rows.len()]).as_slice()
+            })
+            .collect()
+    }
+
+    /// Keeps only the selected rows - the decoded columns, if any, keep only the values of the selected rows
+    pub(crate) fn retain_selected(&mut self, selected: &[bool]) {
+        let mut selection = selected.iter();
+        self.rows.retain(|_| selection.next().copied().unwrap_or(false));
+        self.nulls = OnceCell::new();
+        if let Some(columns) = self.columns.get_mut() {
+            let width = self.rows.iter().map(|row| row.fields.len()).max().unwrap_or(0);
+            columns.truncate(width);
+            for column in columns.iter_mut().filter_map(OnceCell::get_mut) {
+                let mut selection = selected.iter();
+                column.retain(|_| selection.next().copied().unwrap_or(false));
+            }
+        }
+    }
+}
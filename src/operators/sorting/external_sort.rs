@@ -0,0 +1,213 @@
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+use crate::{operators::spill_file::{SpillFile, SpillReader}, storage::storage_config::SpillConfig, DBOperator, DBResult, EntryFields, Row, SortDirection};
+use crate::storage::storage_config::DatabaseConfig;
+
+/// Maximum number of runs merged at once - more runs are first merged into longer ones
+const MAX_MERGE_WIDTH: usize = 64;
+
+/// Sorts the rows within the memory budget: sorted runs of rows are spilled to disk, then lazily k-way merged.
+/// If the whole input fits in the budget, it is sorted in memory only. The sort is stable.
+#[derive(Clone)]
+pub struct ExternalSort<Iter, Key, KeyFunction>
+where   Iter: DBOperator,
+        Key: Ord + Clone,
+        KeyFunction: Clone + Fn(&EntryFields) -> Key
+{
+    iterator: Iter,
+    key_function: KeyFunction,
+    sort_direction: SortDirection,
+    spill_config: SpillConfig,
+    state: SortState<Key>
+}
+
+#[derive(Clone)]
+enum SortState<Key: Ord + Clone> {
+    NotStarted,
+    InMemory(std::vec::IntoIter<Row>),
+    Merging(Merge<Key>)
+}
+
+/// K-way merge of sorted runs
+#[derive(Clone)]
+struct Merge<Key: Ord + Clone> {
+    runs: Vec<SpillReader>,
+    heap: BinaryHeap<MergeEntry<Key>>
+}
+
+/// Next row of a run - the heap pops the entry coming first in the sort order, and from the earliest run on ties
+#[derive(Clone)]
+struct MergeEntry<Key: Ord + Clone> {
+    key: Key,
+    run: usize,
+    descending: bool,
+    row: Row
+}
+
+impl<Key: Ord + Clone> PartialEq for MergeEntry<Key> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<Key: Ord + Clone> Eq for MergeEntry<Key> {}
+
+impl<Key: Ord + Clone> PartialOrd for MergeEntry<Key> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<Key: Ord + Clone> Ord for MergeEntry<Key> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let key_order = if self.descending {
+            self.key.cmp(&other.key)
+        } else {
+            other.key.cmp(&self.key)
+        };
+        key_order.then_with(|| other.run.cmp(&self.run))
+    }
+}
+
+impl<Iter, Key, KeyFunction> ExternalSort<Iter, Key, KeyFunction>
+where   Iter: DBOperator,
+        Key: Ord + Clone,
+        KeyFunction: Clone + Fn(&EntryFields) -> Key
+{
+    pub fn new(iterator: Iter, key_function: KeyFunction, sort_direction: SortDirection) -> Self {
+        let spill_config = SpillConfig::of_storage(iterator.storage_config());
+        ExternalSort {
+            iterator,
+            key_function,
+            sort_direction,
+            spill_config,
+            state: SortState::NotStarted
+        }
+    }
+
+    /// Sets the memory budget of a single sorted run, and the directory the runs are spilled to.
+    pub fn with_spill_config(mut self, spill_config: SpillConfig) -> Self {
+        self.spill_config = spill_config;
+        self
+    }
+
+    fn sort_run(&self, run: &mut [(Key, Row)]) {
+        if self.sort_direction == SortDirection::Ascending {
+            run.sort_by(|a, b| a.0.cmp(&b.0));
+        } else {
+            run.sort_by(|a, b| b.0.cmp(&a.0));
+        }
+    }
+
+    fn create_runs(&mut self) -> DBResult<SortState<Key>> {
+        let mut runs = vec![];
+        let mut run = vec![];
+        let mut run_size = 0;
+        while let Some(row) = self.iterator.next()? {
+            run_size += row.fields.byte_size();
+            run.push(((self.key_function)(&row.fields), row));
+            if run_size >= self.spill_config.memory_budget {
+                self.sort_run(&mut run);
+                let mut file = SpillFile::create(&self.spill_config)?;
+                for (_, row) in run.drain(..) {
+                    file.write(&row)?;
+                }
+                runs.push(file.into_reader()?);
+                run_size = 0;
+            }
+        }
+        self.sort_run(&mut run);
+        if runs.is_empty() {
+            let rows: Vec<Row> = run.into_iter().map(|(_, row)| row).collect();
+            return Ok(SortState::InMemory(rows.into_iter()));
+        }
+        if !run.is_empty() {
+            let mut file = SpillFile::create(&self.spill_config)?;
+            for (_, row) in run {
+                file.write(&row)?;
+            }
+            runs.push(file.into_reader()?);
+        }
+
+        while runs.len() > MAX_MERGE_WIDTH {
+            let rest = runs.split_off(MAX_MERGE_WIDTH);
+            let mut merge = self.start_merge(runs)?;
+            let mut file = SpillFile::create(&self.spill_config)?;
+            while let Some(row) = self.next_merged(&mut merge)? {
+                file.write(&row)?;
+            }
+            // The merged run holds the earliest rows, so it stays first to keep the sort stable
+            runs = std::iter::once(file.into_reader()?).chain(rest).collect();
+        }
+        Ok(SortState::Merging(self.start_merge(runs)?))
+    }
+
+    fn start_merge(&self, mut runs: Vec<SpillReader>) -> DBResult<Merge<Key>> {
+        let mut heap = BinaryHeap::with_capacity(runs.len());
+        for (run, reader) in runs.iter_mut().enumerate() {
+            if let Some(row) = reader.next()? {
+                heap.push(self.merge_entry(run, row));
+            }
+        }
+        Ok(Merge { runs, heap })
+    }
+
+    fn merge_entry(&self, run: usize, row: Row) -> MergeEntry<Key> {
+        MergeEntry {
+            key: (self.key_function)(&row.fields),
+            run,
+            descending: self.sort_direction == SortDirection::Descending,
+            row
+        }
+    }
+
+    fn next_merged(&self, merge: &mut Merge<Key>) -> DBResult<Option<Row>> {
+        let Some(entry) = merge.heap.pop() else {
+            return Ok(None);
+        };
+        if let Some(row) = merge.runs[entry.run].next()? {
+            merge.heap.push(self.merge_entry(entry.run, row));
+        }
+        Ok(Some(entry.row))
+    }
+}
+
+impl<Iter, Key, KeyFunction> DBOperator for ExternalSort<Iter, Key, KeyFunction>
+where   Iter: DBOperator,
+        Key: Ord + Clone,
+        KeyFunction: Clone + Fn(&EntryFields) -> Key
+{
+    fn next(&mut self) -> DBResult<Option<Row>> {
+        if let SortState::NotStarted = self.state {
+            self.state = self.create_runs()?;
+        }
+        let mut state = std::mem::replace(&mut self.state, SortState::NotStarted);
+        let result = match &mut state {
+            SortState::NotStarted => Ok(None),
+            SortState::InMemory(rows) => Ok(rows.next()),
+            SortState::Merging(merge) => self.next_merged(merge)
+        };
+        self.state = state;
+        result
+    }
+
+    fn reset(&mut self) {
+        self.iterator.reset();
+        self.state = SortState::NotStarted;
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.state {
+            SortState::NotStarted => self.iterator.size_hint(),
+            SortState::InMemory(rows) => (rows.len(), Some(rows.len())),
+            SortState::Merging(merge) => {
+                let remaining = merge.heap.len() + merge.runs.iter().map(|run| run.size_hint().0).sum::<usize>();
+                (remaining, Some(remaining))
+            }
+        }
+    }
+
+    fn storage_config(&self) -> Option<&DatabaseConfig> {
+        self.iterator.storage_config()
+    }
+}
@@ -3,6 +3,7 @@ use std::collections::VecDeque;
 use log_err::LogErrOption;
 
 use crate::{DBOperator, DBResult, EntryFields, Row, SortDirection};
+use crate::storage::storage_config::DatabaseConfig;
 
 #[derive(Clone)]
 pub struct InMemorySort<Iter, Key, KeyFunction>
@@ -39,27 +40,24 @@ where   Iter: DBOperator,
 
     fn next(&mut self) -> DBResult<Option<Row>> {
         if self.sorted_data.is_none() {
-            let mut data = VecDeque::<Row>::with_capacity(self.iterator.size_hint().0 + 1);
-            loop {
-                match self.iterator.next()? {
-                    Some(row) => {
-                        data.push_back(row);
-                    },
-                    None => {
-                        data.make_contiguous().sort_unstable_by_key(|row| (self.key_function)(&row.fields));
-                        break;
-                    }
-                }
-            };
-            self.sorted_data = Some(data);
+            let mut rows = Vec::<Row>::with_capacity(self.iterator.size_hint().0 + 1);
+            while let Some(row) = self.iterator.next()? {
+                rows.push(row);
+            }
+            // The key of every row is computed once, instead of on every comparison
+            let mut keyed: Vec<(Key, Row)> = rows
+                .into_iter()
+                .map(|row| ((self.key_function)(&row.fields), row))
+                .collect();
+            match self.sort_direction {
+                SortDirection::Ascending => keyed.sort_by(|a, b| a.0.cmp(&b.0)),
+                SortDirection::Descending => keyed.sort_by(|a, b| b.0.cmp(&a.0))
+            }
+            let sorted: Vec<Row> = keyed.into_iter().map(|(_, row)| row).collect();
+            self.sorted_data = Some(VecDeque::from(sorted));
         }
 
-        let sorted = self.sorted_data.as_mut().log_unwrap();
-        if self.sort_direction == SortDirection::Ascending {
-            Ok(sorted.pop_back())
-        } else {
-            Ok(sorted.pop_front())
-        }
+        Ok(self.sorted_data.as_mut().log_unwrap().pop_front())
     }
 
     fn reset(&mut self) {
@@ -70,4 +68,8 @@ where   Iter: DBOperator,
     fn size_hint(&self) -> (usize, Option<usize>) {
         self.iterator.size_hint()
     }
+
+    fn storage_config(&self) -> Option<&DatabaseConfig> {
+        self.iterator.storage_config()
+    }
 }
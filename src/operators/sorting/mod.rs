@@ -1,6 +1,10 @@
 mod in_memory_sort; pub use in_memory_sort::InMemorySort;
+mod radix_sort; pub use radix_sort::{RadixSort, RadixKey};
+mod external_sort; pub use external_sort::ExternalSort;
+mod top_n; pub use top_n::TopN;
+mod order_by; pub use order_by::{OrderBy, SortKey, NullsOrder, Collation};
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum SortDirection {
     Ascending,
     Descending
@@ -0,0 +1,225 @@
+use std::{cmp::Ordering, iter::Peekable, sync::Arc};
+
+use smallvec::SmallVec;
+
+use crate::{EntryFields, ObjectField, SortDirection};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NullsOrder {
+    /// Nulls come before all the other values, regardless of the direction
+    #[default]
+    First,
+    /// Nulls come after all the other values, regardless of the direction
+    Last
+}
+
+/// Ordering of the `String` values - the other values are always compared by their value
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Collation {
+    /// Byte-wise comparison
+    #[default]
+    Binary,
+    CaseInsensitive,
+    /// Digit sequences are compared by their numeric value, so that "file2" comes before "file10"
+    Natural,
+    NaturalCaseInsensitive
+}
+
+/// Ordering of a single sort key
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct KeyOrder {
+    direction: SortDirection,
+    nulls: NullsOrder,
+    collation: Collation
+}
+
+#[derive(Clone)]
+enum KeySource {
+    Column(usize),
+    Expression(Arc<dyn Fn(&EntryFields) -> ObjectField>)
+}
+
+/// Builder of a multi-key sort order - every key has its own direction, nulls ordering and collation.
+/// The keys extracted by [`OrderBy::key_function`] compare in the built order, so they can be sorted ascending by any sorting operator.
+#[derive(Clone, Default)]
+pub struct OrderBy {
+    sources: Vec<KeySource>,
+    /// Shared with every extracted key
+    orders: Arc<[KeyOrder]>
+}
+
+impl OrderBy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a key sorting on the column's values
+    pub fn column(self, index: usize, direction: SortDirection) -> Self {
+        self.key(KeySource::Column(index), direction)
+    }
+
+    /// Adds a key sorting on a value computed from the row
+    pub fn expression(self, expression: impl Fn(&EntryFields) -> ObjectField + 'static, direction: SortDirection) -> Self {
+        self.key(KeySource::Expression(Arc::new(expression)), direction)
+    }
+
+    fn key(mut self, source: KeySource, direction: SortDirection) -> Self {
+        self.sources.push(source);
+        let order = KeyOrder {
+            direction,
+            nulls: NullsOrder::default(),
+            collation: Collation::default()
+        };
+        self.orders = self.orders.iter().copied().chain([order]).collect();
+        self
+    }
+
+    /// Sets the nulls ordering of the last added key
+    pub fn nulls(mut self, nulls: NullsOrder) -> Self {
+        if let Some(order) = Arc::make_mut(&mut self.orders).last_mut() {
+            order.nulls = nulls;
+        }
+        self
+    }
+
+    /// Sets the collation of the last added key
+    pub fn collation(mut self, collation: Collation) -> Self {
+        if let Some(order) = Arc::make_mut(&mut self.orders).last_mut() {
+            order.collation = collation;
+        }
+        self
+    }
+
+    /// Extracts the sort key of the row
+    pub fn sort_key(&self, row: &EntryFields) -> SortKey {
+        SortKey {
+            values: self.sources
+                .iter()
+                .map(|source| match source {
+                    KeySource::Column(index) => row.column(*index),
+                    KeySource::Expression(expression) => expression(row)
+                })
+                .collect(),
+            orders: self.orders.clone()
+        }
+    }
+
+    /// Returns a key function for the sorting operators - the keys have to be sorted ascending
+    pub fn key_function(&self) -> impl Clone + Fn(&EntryFields) -> SortKey + use<> {
+        let order_by = self.clone();
+        move |row: &EntryFields| order_by.sort_key(row)
+    }
+}
+
+/// Key of a row, comparing in the order built by [`OrderBy`]
+#[derive(Clone)]
+pub struct SortKey {
+    values: SmallVec<[ObjectField; 2]>,
+    orders: Arc<[KeyOrder]>
+}
+
+impl SortKey {
+    fn compare_values(a: &ObjectField, b: &ObjectField, order: &KeyOrder) -> Ordering {
+        let ordering = match (a, b) {
+            (ObjectField::Null, ObjectField::Null) => return Ordering::Equal,
+            (ObjectField::Null, _) => return if order.nulls == NullsOrder::First { Ordering::Less } else { Ordering::Greater },
+            (_, ObjectField::Null) => return if order.nulls == NullsOrder::First { Ordering::Greater } else { Ordering::Less },
+            (ObjectField::String(a), ObjectField::String(b)) => match order.collation {
+                Collation::Binary => a.cmp(b),
+                Collation::CaseInsensitive => a.chars().flat_map(char::to_lowercase).cmp(b.chars().flat_map(char::to_lowercase)),
+                Collation::Natural => natural_compare(a.chars(), b.chars()),
+                Collation::NaturalCaseInsensitive => natural_compare(a.chars().flat_map(char::to_lowercase), b.chars().flat_map(char::to_lowercase))
+            },
+            (a, b) => a.cmp(b)
+        };
+        match order.direction {
+            SortDirection::Ascending => ordering,
+            SortDirection::Descending => ordering.reverse()
+        }
+    }
+}
+
+/// Compares the strings, treating the sequences of digits as numbers
+fn natural_compare(a: impl Iterator<Item = char>, b: impl Iterator<Item = char>) -> Ordering {
+    let (mut a, mut b) = (a.peekable(), b.peekable());
+    loop {
+        match (a.peek().copied(), b.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a_char), Some(b_char)) if a_char.is_ascii_digit() && b_char.is_ascii_digit() => {
+                let a_number = take_number(&mut a);
+                let b_number = take_number(&mut b);
+                let ordering = a_number.len()
+                    .cmp(&b_number.len())
+                    .then_with(|| a_number.cmp(&b_number));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            },
+            (Some(a_char), Some(b_char)) => {
+                if a_char != b_char {
+                    return a_char.cmp(&b_char);
+                }
+                a.next();
+                b.next();
+            }
+        }
+    }
+}
+
+/// Takes the sequence of digits, without the leading zeros
+fn take_number(chars: &mut Peekable<impl Iterator<Item = char>>) -> String {
+    let mut number = String::new();
+    while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+        if !(number.is_empty() && digit == '0') {
+            number.push(digit);
+        }
+    }
+    number
+}
+
+impl PartialEq for SortKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for SortKey {}
+
+impl PartialOrd for SortKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SortKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.values
+            .iter()
+            .zip(other.values.iter())
+            .zip(self.orders.iter())
+            .map(|((a, b), order)| SortKey::compare_values(a, b, order))
+            .find(|ordering| *ordering != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use serial_test::parallel;
+
+    use super::natural_compare;
+
+    #[test]
+    #[parallel]
+    fn test_natural_compare() {
+        assert_eq!(natural_compare("file2".chars(), "file10".chars()), Ordering::Less);
+        assert_eq!(natural_compare("file010".chars(), "file10".chars()), Ordering::Equal);
+        assert_eq!(natural_compare("a1b2".chars(), "a1b10".chars()), Ordering::Less);
+        assert_eq!(natural_compare("b".chars(), "a100".chars()), Ordering::Greater);
+        assert_eq!(natural_compare("file".chars(), "file1".chars()), Ordering::Less);
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::VecDeque;
+
+use log_err::LogErrOption;
+use uuid::Uuid;
+
+use crate::storage::storage_config::DatabaseConfig;
+use crate::{DBOperator, DBResult, EntryFields, Row, SortDirection};
+
+/// Key sortable without comparisons - mapped to an unsigned integer of the same order
+pub trait RadixKey: Ord + Clone {
+    /// Number of bytes of the mapped key
+    const BYTES: usize;
+
+    fn radix_value(&self) -> u128;
+}
+
+impl RadixKey for i32 {
+    const BYTES: usize = 4;
+
+    fn radix_value(&self) -> u128 {
+        u128::from((*self as u32) ^ (1 << 31))
+    }
+}
+
+impl RadixKey for i64 {
+    const BYTES: usize = 8;
+
+    fn radix_value(&self) -> u128 {
+        u128::from((*self as u64) ^ (1 << 63))
+    }
+}
+
+impl RadixKey for u32 {
+    const BYTES: usize = 4;
+
+    fn radix_value(&self) -> u128 {
+        u128::from(*self)
+    }
+}
+
+impl RadixKey for u64 {
+    const BYTES: usize = 8;
+
+    fn radix_value(&self) -> u128 {
+        u128::from(*self)
+    }
+}
+
+impl RadixKey for Uuid {
+    const BYTES: usize = 16;
+
+    fn radix_value(&self) -> u128 {
+        self.as_u128()
+    }
+}
+
+/// Sorts the rows in memory by a radix sort of their keys, without comparing them - the sort is stable.
+#[derive(Clone)]
+pub struct RadixSort<Iter, Key, KeyFunction>
+where   Iter: DBOperator,
+        Key: RadixKey,
+        KeyFunction: Clone + Fn(&EntryFields) -> Key
+{
+    iterator: Iter,
+    key_function: KeyFunction,
+    sort_direction: SortDirection,
+    sorted_data: Option<VecDeque<Row>>
+}
+
+impl<Iter, Key, KeyFunction> RadixSort<Iter, Key, KeyFunction>
+where   Iter: DBOperator,
+        Key: RadixKey,
+        KeyFunction: Clone + Fn(&EntryFields) -> Key
+{
+    pub fn new(iterator: Iter, key_function: KeyFunction, sort_direction: SortDirection) -> Self {
+        RadixSort {
+            iterator,
+            key_function,
+            sort_direction,
+            sorted_data: None
+        }
+    }
+}
+
+impl<Iter, Key, KeyFunction> DBOperator for RadixSort<Iter, Key, KeyFunction>
+where   Iter: DBOperator,
+        Key: RadixKey,
+        KeyFunction: Clone + Fn(&EntryFields) -> Key
+{
+    fn next(&mut self) -> DBResult<Option<Row>> {
+        if self.sorted_data.is_none() {
+            let mut rows = Vec::<Row>::with_capacity(self.iterator.size_hint().0 + 1);
+            while let Some(row) = self.iterator.next()? {
+                rows.push(row);
+            }
+            let values = rows.iter().map(|row| (self.key_function)(&row.fields).radix_value()).collect();
+            self.sorted_data = Some(VecDeque::from(radix_sort_rows(rows, values, Key::BYTES, self.sort_direction)));
+        }
+
+        Ok(self.sorted_data.as_mut().log_unwrap().pop_front())
+    }
+
+    fn reset(&mut self) {
+        self.iterator.reset();
+        self.sorted_data = None;
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.sorted_data {
+            Some(rows) => (rows.len(), Some(rows.len())),
+            None => self.iterator.size_hint()
+        }
+    }
+
+    fn storage_config(&self) -> Option<&DatabaseConfig> {
+        self.iterator.storage_config()
+    }
+}
+
+/// Stable LSD radix sort of the rows by the radix values of their keys, one byte per pass.
+/// Passes over bytes equal for all the keys are skipped.
+pub(crate) fn radix_sort_rows(rows: Vec<Row>, values: Vec<u128>, bytes: usize, sort_direction: SortDirection) -> Vec<Row> {
+    let mask = if bytes >= 16 { u128::MAX } else { (1u128 << (bytes * 8)) - 1 };
+    let mut order: Vec<(u128, usize)> = values
+        .into_iter()
+        .map(|value| match sort_direction {
+            SortDirection::Ascending => value,
+            SortDirection::Descending => !value & mask
+        })
+        .zip(0..)
+        .collect();
+    let mut buffer = order.clone();
+
+    for byte in 0..bytes {
+        let shift = byte * 8;
+        let mut offsets = [0usize; 256];
+        for (value, _) in &order {
+            offsets[((value >> shift) & 0xFF) as usize] += 1;
+        }
+        if offsets.contains(&order.len()) {
+            continue;
+        }
+        let mut offset = 0;
+        for count in offsets.iter_mut() {
+            (*count, offset) = (offset, offset + *count);
+        }
+        for entry in &order {
+            let digit = ((entry.0 >> shift) & 0xFF) as usize;
+            buffer[offsets[digit]] = *entry;
+            offsets[digit] += 1;
+        }
+        std::mem::swap(&mut order, &mut buffer);
+    }
+
+    let mut rows: Vec<Option<Row>> = rows.into_iter().map(Some).collect();
+    order
+        .into_iter()
+        .filter_map(|(_, index)| rows[index].take())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serial_test::parallel;
+    use uuid::Uuid;
+
+    use crate::{ObjectField, Row, SortDirection};
+
+    use super::{radix_sort_rows, RadixKey};
+
+    #[test]
+    #[parallel]
+    fn test_radix_sort() {
+        let keys: Vec<i64> = vec![5, -3, 1 << 40, 0, -3, i64::MIN, 7];
+        let rows: Vec<Row> = keys
+            .iter()
+            .enumerate()
+            .map(|(index, _)| Row { id: Uuid::from_u128(index as u128), fields: vec![ObjectField::I32(index as i32)].into() })
+            .collect();
+
+        let values: Vec<u128> = keys.iter().map(RadixKey::radix_value).collect();
+        let bytes = i64::BYTES;
+        let sorted = radix_sort_rows(rows.clone(), values.clone(), bytes, SortDirection::Ascending);
+        let order: Vec<u128> = sorted.iter().map(|row| row.id.as_u128()).collect();
+        assert_eq!(order, [5, 1, 4, 3, 0, 6, 2]);
+
+        let sorted = radix_sort_rows(rows, values, bytes, SortDirection::Descending);
+        let order: Vec<u128> = sorted.iter().map(|row| row.id.as_u128()).collect();
+        assert_eq!(order, [2, 6, 0, 3, 1, 4, 5]);
+
+    }
+}
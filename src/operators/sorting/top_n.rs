@@ -0,0 +1,128 @@
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+use crate::{DBOperator, DBResult, EntryFields, Row, SortDirection};
+use crate::storage::storage_config::DatabaseConfig;
+
+/// Returns the first N rows in the sort order, keeping only N rows in memory.
+/// Rows with equal keys keep their input order.
+#[derive(Clone)]
+pub struct TopN<Iter, Key, KeyFunction>
+where   Iter: DBOperator,
+        Key: Ord + Clone,
+        KeyFunction: Clone + Fn(&EntryFields) -> Key
+{
+    iterator: Iter,
+    key_function: KeyFunction,
+    sort_direction: SortDirection,
+    count: usize,
+    sorted_data: Option<std::vec::IntoIter<Row>>
+}
+
+/// Kept row - the heap's top is the row which is the first to be evicted
+#[derive(Clone)]
+struct HeapEntry<Key: Ord + Clone> {
+    key: Key,
+    sequence: usize,
+    descending: bool,
+    row: Row
+}
+
+impl<Key: Ord + Clone> PartialEq for HeapEntry<Key> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<Key: Ord + Clone> Eq for HeapEntry<Key> {}
+
+impl<Key: Ord + Clone> PartialOrd for HeapEntry<Key> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<Key: Ord + Clone> Ord for HeapEntry<Key> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let key_order = if self.descending {
+            other.key.cmp(&self.key)
+        } else {
+            self.key.cmp(&other.key)
+        };
+        key_order.then_with(|| self.sequence.cmp(&other.sequence))
+    }
+}
+
+impl<Iter, Key, KeyFunction> TopN<Iter, Key, KeyFunction>
+where   Iter: DBOperator,
+        Key: Ord + Clone,
+        KeyFunction: Clone + Fn(&EntryFields) -> Key
+{
+    pub fn new(iterator: Iter, key_function: KeyFunction, sort_direction: SortDirection, count: usize) -> Self {
+        TopN {
+            iterator,
+            key_function,
+            sort_direction,
+            count,
+            sorted_data: None
+        }
+    }
+
+    fn select_rows(&mut self) -> DBResult<Vec<Row>> {
+        if self.count == 0 {
+            return Ok(vec![]);
+        }
+        let mut heap = BinaryHeap::<HeapEntry<Key>>::with_capacity(self.count.min(self.iterator.size_hint().0) + 1);
+        let mut sequence = 0;
+        while let Some(row) = self.iterator.next()? {
+            let entry = HeapEntry {
+                key: (self.key_function)(&row.fields),
+                sequence,
+                descending: self.sort_direction == SortDirection::Descending,
+                row
+            };
+            sequence += 1;
+            if heap.len() < self.count {
+                heap.push(entry);
+            } else if let Some(mut top) = heap.peek_mut() && entry < *top {
+                *top = entry;
+            }
+        }
+        Ok(heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|entry| entry.row)
+            .collect())
+    }
+}
+
+impl<Iter, Key, KeyFunction> DBOperator for TopN<Iter, Key, KeyFunction>
+where   Iter: DBOperator,
+        Key: Ord + Clone,
+        KeyFunction: Clone + Fn(&EntryFields) -> Key
+{
+    fn next(&mut self) -> DBResult<Option<Row>> {
+        if self.sorted_data.is_none() {
+            self.sorted_data = Some(self.select_rows()?.into_iter());
+        }
+        Ok(self.sorted_data.as_mut().and_then(Iterator::next))
+    }
+
+    fn reset(&mut self) {
+        self.iterator.reset();
+        self.sorted_data = None;
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.sorted_data {
+            Some(rows) => (rows.len(), Some(rows.len())),
+            None => {
+                let (low, high) = self.iterator.size_hint();
+                (low.min(self.count), Some(high.map_or(self.count, |high| high.min(self.count))))
+            }
+        }
+    }
+
+    fn storage_config(&self) -> Option<&DatabaseConfig> {
+        self.iterator.storage_config()
+    }
+}
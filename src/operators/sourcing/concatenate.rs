@@ -0,0 +1,80 @@
+use crate::errors::query_error::EvaluationError;
+use crate::{DBOperator, DBResult, Row};
+use crate::storage::storage_config::DatabaseConfig;
+
+/// Returns all the rows of the first input, followed by all the rows of the second one.
+/// Rows with a different number of columns than the first returned row result in an error.
+#[derive(Clone)]
+pub struct Concatenate<Iter: DBOperator, Iter2: DBOperator> {
+    iterator: Iter,
+    second_iterator: Iter2,
+    first_finished: bool,
+    width: Option<usize>
+}
+
+impl<Iter: DBOperator, Iter2: DBOperator> Concatenate<Iter, Iter2> {
+    pub fn new(iterator: Iter, second_iterator: Iter2) -> Self {
+        Concatenate {
+            iterator,
+            second_iterator,
+            first_finished: false,
+            width: None
+        }
+    }
+}
+
+impl<Iter: DBOperator, Iter2: DBOperator> DBOperator for Concatenate<Iter, Iter2> {
+    fn next(&mut self) -> DBResult<Option<Row>> {
+        let row = if self.first_finished {
+            self.second_iterator.next()?
+        } else {
+            match self.iterator.next()? {
+                Some(row) => Some(row),
+                None => {
+                    self.first_finished = true;
+                    self.second_iterator.next()?
+                }
+            }
+        };
+        if let Some(row) = &row {
+            let width = *self.width.get_or_insert(row.fields.len());
+            if row.fields.len() != width {
+                return Err(EvaluationError::from_string(format!(
+                    "Concatenated rows have different numbers of columns - {} and {}", width, row.fields.len()
+                )).into());
+            }
+        }
+        Ok(row)
+    }
+
+    fn reset(&mut self) {
+        self.iterator.reset();
+        self.second_iterator.reset();
+        self.first_finished = false;
+        self.width = None;
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let hint2 = self.second_iterator.size_hint();
+        if self.first_finished {
+            return hint2;
+        }
+        let hint1 = self.iterator.size_hint();
+        (
+            hint1.0.saturating_add(hint2.0),
+            hint1.1.zip(hint2.1).and_then(|(a, b)| a.checked_add(b))
+        )
+    }
+
+    fn storage_config(&self) -> Option<&DatabaseConfig> {
+        self.iterator.storage_config().or_else(|| self.second_iterator.storage_config())
+    }
+
+    fn cost_hint(&self) -> Option<usize> {
+        let cost2 = self.second_iterator.cost_hint();
+        if self.first_finished {
+            return cost2;
+        }
+        self.iterator.cost_hint().zip(cost2).and_then(|(a, b)| a.checked_add(b))
+    }
+}
@@ -0,0 +1,54 @@
+use uuid::Uuid;
+
+use crate::{DBOperator, DBResult, ObjectField, Row};
+
+/// Returns the rows given up front, like a lookup table built in code instead of read from a collection.
+#[derive(Clone)]
+pub struct ConstantScan {
+    rows: Vec<Row>,
+    position: usize
+}
+
+impl ConstantScan {
+    /// Creates the rows from the values of their columns, each of them with a new id
+    pub fn new(rows: Vec<Vec<ObjectField>>) -> Self {
+        ConstantScan {
+            rows: rows
+                .into_iter()
+                .map(|fields| Row {
+                    id: Uuid::new_v4(),
+                    fields: fields.into()
+                })
+                .collect(),
+            position: 0
+        }
+    }
+}
+
+/// Returns the rows given up front - see [`ConstantScan`]
+pub fn constant_scan(rows: Vec<Vec<ObjectField>>) -> ConstantScan {
+    ConstantScan::new(rows)
+}
+
+impl DBOperator for ConstantScan {
+    fn next(&mut self) -> DBResult<Option<Row>> {
+        let row = self.rows.get(self.position).cloned();
+        if row.is_some() {
+            self.position += 1;
+        }
+        Ok(row)
+    }
+
+    fn reset(&mut self) {
+        self.position = 0;
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.rows.len() - self.position;
+        (remaining, Some(remaining))
+    }
+
+    fn cost_hint(&self) -> Option<usize> {
+        Some(self.rows.len() - self.position)
+    }
+}
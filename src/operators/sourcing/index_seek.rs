@@ -0,0 +1,63 @@
+use uuid::Uuid;
+
+use crate::collection::Collection;
+use crate::collection::indexes::IndexKey;
+use crate::query::condition::Condition;
+use crate::{DBOperator, DBResult, ObjectField, Row};
+use crate::storage::storage_config::DatabaseConfig;
+
+/// Retrieves the rows found in a secondary index of the collection.
+/// Index entries of row versions not visible from the transaction, no longer having the indexed key,
+/// or no longer satisfying the predicate of a partial index, are skipped.
+#[derive(Clone)]
+pub struct IndexSeek<'a> {
+    collection: &'a Collection,
+    key: IndexKey,
+    predicate: Option<Condition>,
+    entries: Vec<(Vec<ObjectField>, Uuid)>,
+    current_index: usize,
+    current_transaction_id: Uuid
+}
+
+impl<'a> IndexSeek<'a> {
+    pub(crate) fn new(collection: &'a Collection, transaction_id: Uuid, key: IndexKey, predicate: Option<Condition>, entries: Vec<(Vec<ObjectField>, Uuid)>) -> Self {
+        Self {
+            collection,
+            key,
+            predicate,
+            entries,
+            current_index: 0,
+            current_transaction_id: transaction_id
+        }
+    }
+}
+
+impl<'a> DBOperator for IndexSeek<'a> {
+    fn next(&mut self) -> DBResult<Option<Row>> {
+        while let Some((key, id)) = self.entries.get(self.current_index) {
+            self.current_index += 1;
+            if let Some(row) = self.collection.get(self.current_transaction_id, *id)?
+                && &self.key.extract(&row.fields) == key
+                && self.predicate.as_ref().map_or(Ok(true), |predicate| predicate.is_satisfied(&row.fields))? {
+                return Ok(Some(row));
+            }
+        }
+        Ok(None)
+    }
+
+    fn reset(&mut self) {
+        self.current_index = 0;
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.entries.len() - self.current_index))
+    }
+
+    fn storage_config(&self) -> Option<&DatabaseConfig> {
+        Some(&self.collection.config.storage_config)
+    }
+
+    fn cost_hint(&self) -> Option<usize> {
+        Some(self.entries.len() - self.current_index)
+    }
+}
@@ -1 +1,7 @@
 mod table_scan; pub use table_scan::TableScan;
+mod table_seek; pub use table_seek::TableSeek;
+mod index_seek; pub use index_seek::IndexSeek;
+mod constant_scan; pub use constant_scan::{ConstantScan, constant_scan};
+mod concatenate; pub use concatenate::Concatenate;
+mod set_operation; pub use set_operation::{SetOperation, SetOperationKind};
+mod parallel_table_scan; pub use parallel_table_scan::ParallelTableScan;
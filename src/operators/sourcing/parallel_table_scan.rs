@@ -0,0 +1,212 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use gxhash::{HashMap, HashMapExt, HashSet, HashSetExt};
+use uuid::Uuid;
+
+use crate::collection::Collection;
+use crate::errors::storage_error::StorageError;
+use crate::storage::log_file::log_entry::{EntityEntry, LogEntry, TransactionEntry};
+use crate::storage::log_file::LogFile;
+use crate::{DBOperator, DBResult, Exchange, Row};
+use crate::storage::storage_config::{DatabaseConfig, ExecutionConfig};
+
+/// Scans the log files of the collection concurrently, returning the same rows as [`TableScan`](crate::TableScan) in no particular order.
+/// The files are first loaded and read in parallel for the committed transactions, then for the newest visible entry of every row id within the file.
+/// The worker threads then return the rows of each file, which have no visible entry in any newer file.
+#[derive(Clone)]
+pub struct ParallelTableScan<'a> {
+    collection: &'a Collection,
+    transaction_id: Uuid,
+    execution_config: ExecutionConfig,
+    exchange: Option<Exchange<LogFileScan>>
+}
+
+impl<'a> ParallelTableScan<'a> {
+    pub fn new(collection: &'a Collection, transaction_id: Uuid) -> Self {
+        ParallelTableScan {
+            collection,
+            transaction_id,
+            execution_config: collection.config.storage_config.execution.clone(),
+            exchange: None
+        }
+    }
+
+    /// Sets the number of the worker threads
+    pub fn with_execution_config(mut self, execution_config: ExecutionConfig) -> Self {
+        self.execution_config = execution_config;
+        self
+    }
+
+    fn build(&self) -> DBResult<Exchange<LogFileScan>> {
+        let file_count = self.collection.last_file_index + 1;
+        let threads = self.execution_config.threads();
+        let transaction_id = self.transaction_id;
+        let get_file = self.collection.file_loader();
+        let loaded = for_each_file(file_count, threads, |index| {
+            let file = get_file(index)?.ok_or(StorageError::Inconsistency())?;
+            let transactions = committed_transactions_of(&file, transaction_id)?;
+            Ok((file, transactions))
+        })?;
+        let mut committed_transactions = HashSet::new();
+        committed_transactions.insert(Uuid::nil());
+        let files: Vec<_> = loaded
+            .into_iter()
+            .map(|(file, transactions)| {
+                committed_transactions.extend(transactions);
+                file
+            })
+            .collect();
+
+        let visible = for_each_file(file_count, threads, |index| visible_entries_of(&files[index], transaction_id, &committed_transactions))?;
+        let (visited_ids, positions): (Vec<_>, Vec<_>) = visible.into_iter().unzip();
+        // The newer files go first, so every id keeps the index of the newest file with its visible entry
+        let mut newest_files = HashMap::with_capacity(self.collection.statistics.live_rows);
+        for (file_index, ids) in visited_ids.into_iter().enumerate().rev() {
+            for id in ids {
+                newest_files.entry(id).or_insert(file_index);
+            }
+        }
+        let newest_files = Arc::new(newest_files);
+        let inputs = files
+            .into_iter()
+            .zip(positions)
+            .enumerate()
+            .map(|(file_index, (file, positions))| LogFileScan {
+                file,
+                file_index,
+                positions: positions.into(),
+                newest_files: newest_files.clone(),
+                position: 0
+            })
+            .collect();
+        Ok(Exchange::new(inputs).with_execution_config(self.execution_config.clone()))
+    }
+}
+
+/// Runs the function on every file index, on at most `threads` threads - each of them takes the next index as soon as it is done with the previous one.
+/// The outputs are returned in the order of the files.
+fn for_each_file<Output: Send>(file_count: usize, threads: usize, function: impl Fn(usize) -> DBResult<Output> + Sync) -> DBResult<Vec<Output>> {
+    let next_index = AtomicUsize::new(0);
+    let mut outputs: Vec<(usize, DBResult<Output>)> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads.min(file_count))
+            .map(|_| scope.spawn(|| {
+                let mut outputs = vec![];
+                loop {
+                    let index = next_index.fetch_add(1, Ordering::Relaxed);
+                    if index >= file_count {
+                        return outputs;
+                    }
+                    outputs.push((index, function(index)));
+                }
+            }))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+            .collect()
+    });
+    outputs.sort_unstable_by_key(|(index, _)| *index);
+    outputs
+        .into_iter()
+        .map(|(_, output)| output)
+        .collect()
+}
+
+fn committed_transactions_of(file: &LogFile, transaction_id: Uuid) -> DBResult<Vec<Uuid>> {
+    Ok(file.read()?
+        .iter()
+        .filter_map(|entry| match entry {
+            LogEntry::Transaction(id, TransactionEntry::Committed) if *id <= transaction_id => Some(*id),
+            _ => None
+        })
+        .collect())
+}
+
+/// Returns the ids with a visible entry in the file, and the positions of the rows among them which were not deleted
+fn visible_entries_of(file: &LogFile, transaction_id: Uuid, committed_transactions: &HashSet<Uuid>) -> DBResult<(HashSet<Uuid>, Vec<usize>)> {
+    let entries = file.read()?;
+    let mut visited_ids = HashSet::new();
+    let mut positions = vec![];
+    for (position, entry) in entries.iter().enumerate().rev() {
+        let LogEntry::Entity(entry_transaction, entity) = entry else {
+            continue;
+        };
+        if *entry_transaction > transaction_id || !committed_transactions.contains(entry_transaction) {
+            continue;
+        }
+        match entity {
+            EntityEntry::Updated(row) => if visited_ids.insert(row.id) {
+                positions.push(position);
+            },
+            EntityEntry::Deleted(id) => {
+                visited_ids.insert(*id);
+            }
+        }
+    }
+    Ok((visited_ids, positions))
+}
+
+impl<'a> DBOperator for ParallelTableScan<'a> {
+    fn next(&mut self) -> DBResult<Option<Row>> {
+        let exchange = match &mut self.exchange {
+            Some(exchange) => exchange,
+            None => self.exchange.insert(self.build()?)
+        };
+        exchange.next()
+    }
+
+    /// Keeps the positions of the visible rows - only the threads returning them start over
+    /// Drops the positions read from the files, so that the next call reads the files of the collection again
+    fn reset(&mut self) {
+        self.exchange = None;
+    }
+
+    /// Before the files are read, there is no lower bound, as rows written by transactions not visible from the scan are skipped.
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.exchange {
+            Some(exchange) => exchange.size_hint(),
+            None => (0, Some(self.collection.written_ids()))
+        }
+    }
+
+    fn storage_config(&self) -> Option<&DatabaseConfig> {
+        Some(&self.collection.config.storage_config)
+    }
+}
+
+/// Returns the rows of a single log file at the given positions, unless a newer file has a visible entry with their id
+#[derive(Clone)]
+struct LogFileScan {
+    file: Arc<LogFile>,
+    file_index: usize,
+    positions: Arc<[usize]>,
+    /// Index of the newest file with a visible entry, for every row id
+    newest_files: Arc<HashMap<Uuid, usize>>,
+    position: usize
+}
+
+impl DBOperator for LogFileScan {
+    fn next(&mut self) -> DBResult<Option<Row>> {
+        let entries = self.file.read()?;
+        while let Some(&entry_position) = self.positions.get(self.position) {
+            self.position += 1;
+            let Some(LogEntry::Entity(_, EntityEntry::Updated(row))) = entries.get(entry_position) else {
+                continue;
+            };
+            if self.newest_files.get(&row.id) == Some(&self.file_index) {
+                return Ok(Some(row.clone()));
+            }
+        }
+        Ok(None)
+    }
+
+    fn reset(&mut self) {
+        self.position = 0;
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.positions.len() - self.position))
+    }
+}
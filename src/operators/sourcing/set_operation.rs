@@ -0,0 +1,93 @@
+use gxhash::{HashMap, HashMapExt};
+
+use crate::{DBOperator, DBResult, Row};
+use crate::storage::storage_config::DatabaseConfig;
+
+/// Which rows of the first input are returned, based on the rows of the second one
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SetOperationKind {
+    /// Rows present in both inputs, each only once
+    Intersect,
+    /// Rows present in both inputs, as many times as they are in both
+    IntersectAll,
+    /// Rows not present in the second input, each only once
+    Except,
+    /// Rows of the first input, less as many of their copies as are in the second input
+    ExceptAll
+}
+
+/// Compares the rows of the first input to the rows of the second one, hashed by the encoded bytes of their columns.
+/// Rows are equal if all their columns are of the same type and value - nulls are equal to each other.
+/// The rows of the first input are returned in their order, the first of the duplicates with its id.
+#[derive(Clone)]
+pub struct SetOperation<Iter: DBOperator, Iter2: DBOperator> {
+    iterator: Iter,
+    second_iterator: Iter2,
+    kind: SetOperationKind,
+    /// Remaining count of every row of the second input - and for the distinct variants, the rows already handled
+    counts: Option<HashMap<Box<[u8]>, usize>>
+}
+
+impl<Iter: DBOperator, Iter2: DBOperator> SetOperation<Iter, Iter2> {
+    pub fn new(iterator: Iter, second_iterator: Iter2, kind: SetOperationKind) -> Self {
+        SetOperation {
+            iterator,
+            second_iterator,
+            kind,
+            counts: None
+        }
+    }
+
+    fn build(&mut self) -> DBResult<HashMap<Box<[u8]>, usize>> {
+        let mut counts = HashMap::<Box<[u8]>, usize>::new();
+        while let Some(row) = self.second_iterator.next()? {
+            *counts.entry(row.fields.data().into()).or_default() += 1;
+        }
+        Ok(counts)
+    }
+}
+
+impl<Iter: DBOperator, Iter2: DBOperator> DBOperator for SetOperation<Iter, Iter2> {
+    fn next(&mut self) -> DBResult<Option<Row>> {
+        if self.counts.is_none() {
+            self.counts = Some(self.build()?);
+        }
+        let Some(counts) = self.counts.as_mut() else {
+            return Ok(None);
+        };
+        while let Some(row) = self.iterator.next()? {
+            let data = row.fields.data();
+            let returned = match (self.kind, counts.get_mut(data)) {
+                (SetOperationKind::Intersect, Some(count)) => std::mem::take(count) > 0,
+                (SetOperationKind::IntersectAll | SetOperationKind::ExceptAll, Some(count)) if *count > 0 => {
+                    *count -= 1;
+                    self.kind == SetOperationKind::IntersectAll
+                },
+                (SetOperationKind::Intersect, None) | (SetOperationKind::IntersectAll, _) | (SetOperationKind::Except, Some(_)) => false,
+                (SetOperationKind::Except, None) => {
+                    counts.insert(data.into(), 0);
+                    true
+                },
+                (SetOperationKind::ExceptAll, _) => true
+            };
+            if returned {
+                return Ok(Some(row));
+            }
+        }
+        Ok(None)
+    }
+
+    fn reset(&mut self) {
+        self.iterator.reset();
+        self.second_iterator.reset();
+        self.counts = None;
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.iterator.size_hint().1)
+    }
+
+    fn storage_config(&self) -> Option<&DatabaseConfig> {
+        self.iterator.storage_config().or_else(|| self.second_iterator.storage_config())
+    }
+}
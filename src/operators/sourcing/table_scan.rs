@@ -11,7 +11,8 @@ use crate::errors::DatabaseError;
 use crate::storage::log_file::log_entry::{EntityEntry, LogEntry, TransactionEntry};
 use crate::storage::log_file::LogFile;
 use crate::utils::{RwLockReadGuardian};
-use crate::{DBOperator, DBResult, Row};
+use crate::{DBOperator, DBResult, Row, RowBatch, BATCH_ROWS};
+use crate::storage::storage_config::DatabaseConfig;
 
 pub struct TableScan<'a> {
     collection: &'a Collection,
@@ -46,7 +47,7 @@ impl<'a> Clone for TableScan<'a> {
 impl<'a> TableScan<'a> {
 
     pub fn new(collection: &'a Collection, transaction_id: Uuid) -> Self {
-        let approx_entries = collection.statistics.approximate_entries();
+        let approx_entries = collection.statistics.live_rows;
         let mut transactions = HashSet::new();
         transactions.insert(Uuid::nil());
         Self {
@@ -59,6 +60,58 @@ impl<'a> TableScan<'a> {
             current_transaction_id: transaction_id
         }
     }
+
+    /// Moves to the previous log file, once the current one is exhausted - returns false if there is none
+    fn next_file(&mut self) -> bool {
+        self.current_file_ref = None;
+        if self.current_file_index == 0 {
+            return false;
+        }
+        self.current_file_entry = 0;
+        self.current_file_index -= 1;
+        true
+    }
+
+    /// Loads the current log file - returns false if it does not exist
+    fn load_file(&mut self) -> DBResult<bool> {
+        let Some(file) = self.collection.get_file(self.current_file_index)? else {
+            return Ok(false);
+        };
+        let yoke = Yoke::try_attach_to_cart(file, |file| {
+            match file.read() {
+                Ok(lock) => Ok(RwLockReadGuardian(lock)),
+                Err(_) => Err(DatabaseError::Storage(StorageError::Inconsistency()))
+            }
+        })?;
+        self.current_file_ref = Some(yoke);
+        Ok(true)
+    }
+}
+
+/// Returns the row of the entry, if it is the newest version of the row visible from the transaction
+fn visit(entry: &LogEntry, current_transaction_id: &Uuid, visited_ids: &mut HashSet<Uuid>, committed_transactions: &mut HashSet<Uuid>) -> Option<Row> {
+    match entry {
+        LogEntry::Entity(transaction_id, EntityEntry::Updated(row)) => {
+            if transaction_id <= current_transaction_id && committed_transactions.contains(transaction_id)
+                && visited_ids.insert(row.id) {
+                    return Some(Row { id: row.id, fields: row.fields.clone() });
+                }
+        },
+        LogEntry::Entity(transaction_id, EntityEntry::Deleted(entry_id)) => {
+            if transaction_id <= current_transaction_id && committed_transactions.contains(transaction_id) {
+                visited_ids.insert(*entry_id);
+            }
+        },
+        LogEntry::Transaction(transaction_id, TransactionEntry::Committed) => {
+            if transaction_id <= current_transaction_id {
+                committed_transactions.insert(*transaction_id);
+            }
+        },
+        LogEntry::Transaction(_, TransactionEntry::Rollbacked) => {
+
+        }
+    }
+    None
 }
 
 impl<'a> DBOperator for TableScan<'a> {
@@ -68,58 +121,42 @@ impl<'a> DBOperator for TableScan<'a> {
                 self.current_file_entry += 1;
                 let yoke = file.get();
                 if let Some(entry) = yoke.get(yoke.len().wrapping_sub(self.current_file_entry)) {
-                    match entry {
-                        LogEntry::Entity(transaction_id, EntityEntry::Updated(row)) => {
-                            if transaction_id <= &self.current_transaction_id && self.committed_transactions.contains(transaction_id)
-                                && self.visited_ids.insert(row.id) {
-                                    let entry_id = row.id;
-                                    let fields = row.fields.clone();
-                                    return Ok(Some(Row { id: entry_id, fields }));
-                                }
-                        },
-                        LogEntry::Entity(transaction_id, EntityEntry::Deleted(entry_id)) => {
-                            if transaction_id <= &self.current_transaction_id && self.committed_transactions.contains(transaction_id) {
-                                self.visited_ids.insert(*entry_id);
-                            }
-                        },
-                        LogEntry::Transaction(transaction_id, TransactionEntry::Committed) => {
-                            if transaction_id <= &self.current_transaction_id {
-                                self.committed_transactions.insert(*transaction_id);
-                            }
-                        },
-                        LogEntry::Transaction(_, TransactionEntry::Rollbacked) => {
-
-                        }
+                    if let Some(row) = visit(entry, &self.current_transaction_id, &mut self.visited_ids, &mut self.committed_transactions) {
+                        return Ok(Some(row));
                     }
-                } else {
-                    self.current_file_ref = None;
-                    if self.current_file_index == 0 {
-                        return Ok(None);
-                    }
-                    self.current_file_entry = 0;
-                    self.current_file_index -= 1;
+                } else if !self.next_file() {
+                    return Ok(None);
+                }
+            } else if !self.load_file()? {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Reads the entries of the current log file in a tight loop, until the batch is full.
+    /// If the next log file cannot be loaded, the rows read so far are returned, and the error on the next call.
+    fn next_batch(&mut self) -> DBResult<RowBatch> {
+        let mut rows = Vec::with_capacity(BATCH_ROWS);
+        while rows.len() < BATCH_ROWS {
+            if let Some(file) = self.current_file_ref.as_ref() {
+                let entries = file.get();
+                while rows.len() < BATCH_ROWS && let Some(entry) = entries.get(entries.len().wrapping_sub(self.current_file_entry + 1)) {
+                    self.current_file_entry += 1;
+                    rows.extend(visit(entry, &self.current_transaction_id, &mut self.visited_ids, &mut self.committed_transactions));
+                }
+                if rows.len() < BATCH_ROWS && !self.next_file() {
+                    break;
                 }
             } else {
-                match self.collection.get_file(self.current_file_index) {
-                    Ok(Some(file)) => {
-                        let yoke = Yoke::try_attach_to_cart(file, |file| {
-                            match file.read() {
-                                Ok(lock) => Ok(RwLockReadGuardian(lock)),
-                                Err(_) => Err(DatabaseError::Storage(StorageError::Inconsistency()))
-                            }
-                        });
-                        match yoke {
-                            Ok(yoke) => {
-                                self.current_file_ref = Some(yoke);
-                            },
-                            Err(e) => return Err(e)
-                        }
-                    },
-                    Ok(None) => return Ok(None),
-                    Err(e) => return Err(e)
+                match self.load_file() {
+                    Ok(true) => {},
+                    Ok(false) => break,
+                    Err(error) if rows.is_empty() => return Err(error),
+                    Err(_) => break
                 }
             }
         }
+        Ok(RowBatch::new(rows))
     }
 
     fn reset(&mut self) {
@@ -131,11 +168,21 @@ impl<'a> DBOperator for TableScan<'a> {
         self.current_file_entry = 0;
     }
 
+    /// The upper bound is the number of row ids not yet visited.
+    /// There is no lower bound, as rows written by transactions not visible from the scan are skipped.
     fn size_hint(&self) -> (usize, Option<usize>) {
-        let files_left = self.current_file_index + 1;
-        (
-            files_left,
-            Some((files_left * self.collection.config.storage_config.log_file.max_entries).saturating_sub(self.current_file_entry))
-        )
+        let not_visited = self.collection.written_ids().saturating_sub(self.visited_ids.len());
+        (0, Some(not_visited))
+    }
+
+    fn storage_config(&self) -> Option<&DatabaseConfig> {
+        Some(&self.collection.config.storage_config)
+    }
+
+    /// The cost is the estimate of the log entries left to read.
+    fn cost_hint(&self) -> Option<usize> {
+        let files = self.collection.last_file_index + 1;
+        let entries_per_file = self.collection.statistics.approximate_total_entries / files;
+        Some((entries_per_file * (self.current_file_index + 1)).saturating_sub(self.current_file_entry))
     }
 }
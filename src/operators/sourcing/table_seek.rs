@@ -0,0 +1,54 @@
+use uuid::Uuid;
+
+use crate::collection::Collection;
+use crate::{DBOperator, DBResult, Row};
+use crate::storage::storage_config::DatabaseConfig;
+
+/// Retrieves the rows with the given ids using the primary index of the collection.
+/// Ids without a row visible from the transaction are skipped.
+#[derive(Clone)]
+pub struct TableSeek<'a> {
+    collection: &'a Collection,
+    ids: Vec<Uuid>,
+    current_index: usize,
+    current_transaction_id: Uuid
+}
+
+impl<'a> TableSeek<'a> {
+    pub fn new(collection: &'a Collection, transaction_id: Uuid, ids: Vec<Uuid>) -> Self {
+        Self {
+            collection,
+            ids,
+            current_index: 0,
+            current_transaction_id: transaction_id
+        }
+    }
+}
+
+impl<'a> DBOperator for TableSeek<'a> {
+    fn next(&mut self) -> DBResult<Option<Row>> {
+        while let Some(id) = self.ids.get(self.current_index) {
+            self.current_index += 1;
+            if let Some(row) = self.collection.get(self.current_transaction_id, *id)? {
+                return Ok(Some(row));
+            }
+        }
+        Ok(None)
+    }
+
+    fn reset(&mut self) {
+        self.current_index = 0;
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.ids.len() - self.current_index))
+    }
+
+    fn storage_config(&self) -> Option<&DatabaseConfig> {
+        Some(&self.collection.config.storage_config)
+    }
+
+    fn cost_hint(&self) -> Option<usize> {
+        Some(self.ids.len() - self.current_index)
+    }
+}
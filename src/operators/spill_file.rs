@@ -0,0 +1,123 @@
+use std::{fs::{self, File}, io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write}, path::PathBuf, sync::Arc};
+
+use log::warn;
+use log_err::LogErrResult;
+use uuid::Uuid;
+
+use crate::{errors::storage_error::CompressionError, storage::storage_config::SpillConfig, DBOperator, DBResult, EntryFields, Row};
+
+/// Temporary file holding the rows an operator could not keep in memory.
+/// Rows are stored as: the length-encoded size of the fields, the row id and the fields themselves.
+pub(crate) struct SpillFile {
+    path: Arc<SpillPath>,
+    writer: BufWriter<File>,
+    rows: usize
+}
+
+/// Path of a spill file - the file is removed once the last reader referencing it is dropped.
+struct SpillPath(PathBuf);
+
+impl Drop for SpillPath {
+    fn drop(&mut self) {
+        if let Err(error) = fs::remove_file(&self.0) {
+            warn!("Cannot remove the spill file {}: {}", self.0.display(), error);
+        }
+    }
+}
+
+impl SpillFile {
+    pub(crate) fn create(config: &SpillConfig) -> DBResult<Self> {
+        let directory = config.directory();
+        fs::create_dir_all(&directory)?;
+        let path = directory.join(format!("{}.spill", Uuid::new_v4()));
+        let file = File::create(&path)?;
+        Ok(SpillFile {
+            path: Arc::new(SpillPath(path)),
+            writer: BufWriter::new(file),
+            rows: 0
+        })
+    }
+
+    pub(crate) fn write(&mut self, row: &Row) -> DBResult<()> {
+        let fields = row.fields.data();
+        self.writer.write_all(vint64::encode(fields.len() as u64).as_ref())?;
+        self.writer.write_all(row.id.as_bytes())?;
+        self.writer.write_all(fields)?;
+        self.rows += 1;
+        Ok(())
+    }
+
+    /// Finishes writing the file, and returns an operator reading the rows back in the order they were written.
+    pub(crate) fn into_reader(mut self) -> DBResult<SpillReader> {
+        self.writer.flush()?;
+        let reader = BufReader::new(File::open(&self.path.0)?);
+        Ok(SpillReader {
+            path: self.path,
+            reader,
+            position: 0,
+            rows: self.rows,
+            read_rows: 0
+        })
+    }
+}
+
+pub(crate) struct SpillReader {
+    path: Arc<SpillPath>,
+    reader: BufReader<File>,
+    position: u64,
+    rows: usize,
+    read_rows: usize
+}
+
+impl Clone for SpillReader {
+    fn clone(&self) -> Self {
+        let mut file = File::open(&self.path.0).log_expect("The spill file is removed only after the last reader is dropped");
+        file.seek(SeekFrom::Start(self.position)).log_expect("The spill file cannot be seeked");
+        SpillReader {
+            path: self.path.clone(),
+            reader: BufReader::new(file),
+            position: self.position,
+            rows: self.rows,
+            read_rows: self.read_rows
+        }
+    }
+}
+
+impl DBOperator for SpillReader {
+    fn next(&mut self) -> DBResult<Option<Row>> {
+        if self.read_rows == self.rows {
+            return Ok(None);
+        }
+        let mut length = [0u8; 9];
+        self.reader.read_exact(&mut length[..1])?;
+        let length_bytes = vint64::decoded_len(length[0]);
+        self.reader.read_exact(&mut length[1..length_bytes])?;
+        let mut encoded_length = &length[..length_bytes];
+        let fields_length = vint64::decode(&mut encoded_length)
+            .map_err(|_| CompressionError::from_str("Malformed spill file entry length"))? as usize;
+
+        let mut id = [0u8; 16];
+        self.reader.read_exact(&mut id)?;
+        let mut fields = vec![0u8; fields_length];
+        self.reader.read_exact(&mut fields)?;
+
+        self.position += (length_bytes + id.len() + fields_length) as u64;
+        self.read_rows += 1;
+        let fields: Arc<[u8]> = fields.into();
+        Ok(Some(Row {
+            id: Uuid::from_bytes(id),
+            fields: EntryFields(0..fields.len(), fields)
+        }))
+    }
+
+    fn reset(&mut self) {
+        self.reader.seek(SeekFrom::Start(0)).log_expect("The spill file cannot be seeked");
+        self.position = 0;
+        self.read_rows = 0;
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.rows - self.read_rows;
+        (remaining, Some(remaining))
+    }
+}
@@ -0,0 +1,132 @@
+use std::sync::Arc;
+
+use gxhash::{HashMap, HashMapExt};
+
+use crate::operators::joining::{matchable_key, JoinKey, JoinKeyValues};
+use crate::{DBOperator, DBResult, EntryFields, Row};
+use crate::storage::storage_config::DatabaseConfig;
+
+/// Builds a temporary hash index of the input rows on the first pass.
+/// Once rebound to an outer row, returns only the rows with the key of the outer row - until then, it returns all the rows.
+/// Keys holding a null never match.
+#[derive(Clone)]
+pub struct IndexSpool<Iter, Key, KeyFunction, OuterKeyFunction>
+where   Iter: DBOperator,
+        Key: JoinKey,
+        KeyFunction: Clone + Fn(&EntryFields) -> Key,
+        OuterKeyFunction: Clone + Fn(&EntryFields) -> Key
+{
+    iterator: Iter,
+    key_function: KeyFunction,
+    outer_key_function: OuterKeyFunction,
+    rows: Vec<Row>,
+    /// Positions of the rows of every key
+    index: Option<HashMap<JoinKeyValues, Arc<[usize]>>>,
+    cursor: Cursor
+}
+
+#[derive(Clone)]
+enum Cursor {
+    /// Position among all the rows
+    All(usize),
+    /// Positions of the rows matching the outer row, and the position among them
+    Matches(Arc<[usize]>, usize),
+    /// Key of the outer row, looked up once the index is built
+    Seek(Option<JoinKeyValues>)
+}
+
+impl<Iter, Key, KeyFunction, OuterKeyFunction> IndexSpool<Iter, Key, KeyFunction, OuterKeyFunction>
+where   Iter: DBOperator,
+        Key: JoinKey,
+        KeyFunction: Clone + Fn(&EntryFields) -> Key,
+        OuterKeyFunction: Clone + Fn(&EntryFields) -> Key
+{
+    pub fn new(iterator: Iter, key_function: KeyFunction, outer_key_function: OuterKeyFunction) -> Self {
+        IndexSpool {
+            iterator,
+            key_function,
+            outer_key_function,
+            rows: vec![],
+            index: None,
+            cursor: Cursor::All(0)
+        }
+    }
+
+    fn build_index(&mut self) -> DBResult<()> {
+        let mut index = HashMap::<JoinKeyValues, Vec<usize>>::new();
+        while let Some(row) = self.iterator.next()? {
+            if let Some(key) = matchable_key((self.key_function)(&row.fields)) {
+                index.entry(key).or_default().push(self.rows.len());
+            }
+            self.rows.push(row);
+        }
+        self.index = Some(index
+            .into_iter()
+            .map(|(key, positions)| (key, positions.into()))
+            .collect());
+        Ok(())
+    }
+}
+
+impl<Iter, Key, KeyFunction, OuterKeyFunction> DBOperator for IndexSpool<Iter, Key, KeyFunction, OuterKeyFunction>
+where   Iter: DBOperator,
+        Key: JoinKey,
+        KeyFunction: Clone + Fn(&EntryFields) -> Key,
+        OuterKeyFunction: Clone + Fn(&EntryFields) -> Key
+{
+    fn next(&mut self) -> DBResult<Option<Row>> {
+        if self.index.is_none() {
+            self.build_index()?;
+        }
+        if let Cursor::Seek(key) = &self.cursor {
+            let positions = key
+                .as_ref()
+                .and_then(|key| self.index.as_ref().and_then(|index| index.get(key)))
+                .cloned()
+                .unwrap_or_else(|| Arc::from([]));
+            self.cursor = Cursor::Matches(positions, 0);
+        }
+        match &mut self.cursor {
+            Cursor::All(position) => {
+                let row = self.rows.get(*position).cloned();
+                *position += 1;
+                Ok(row)
+            },
+            Cursor::Matches(positions, position) => {
+                let row = positions.get(*position).map(|row_position| self.rows[*row_position].clone());
+                *position += 1;
+                Ok(row)
+            },
+            Cursor::Seek(_) => Ok(None)
+        }
+    }
+
+    /// Keeps the index - the rows are returned from the start, all of them
+    fn reset(&mut self) {
+        self.cursor = Cursor::All(0);
+    }
+
+    /// Keeps the index - only the rows with the outer row's key are returned from now on
+    fn rebind(&mut self, outer_row: &EntryFields) {
+        self.cursor = Cursor::Seek(matchable_key((self.outer_key_function)(outer_row)));
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.index.is_none() {
+            return self.iterator.size_hint();
+        }
+        let remaining = match &self.cursor {
+            Cursor::All(position) => self.rows.len().saturating_sub(*position),
+            Cursor::Matches(positions, position) => positions.len().saturating_sub(*position),
+            Cursor::Seek(key) => key
+                .as_ref()
+                .and_then(|key| self.index.as_ref().and_then(|index| index.get(key)))
+                .map_or(0, |positions| positions.len())
+        };
+        (remaining, Some(remaining))
+    }
+
+    fn storage_config(&self) -> Option<&DatabaseConfig> {
+        self.iterator.storage_config()
+    }
+}
@@ -0,0 +1,3 @@
+mod table_spool; pub use table_spool::TableSpool;
+mod row_count_spool; pub use row_count_spool::RowCountSpool;
+mod index_spool; pub use index_spool::IndexSpool;
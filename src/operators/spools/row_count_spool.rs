@@ -0,0 +1,73 @@
+use uuid::Uuid;
+
+use crate::{DBOperator, DBResult, ObjectField, Row};
+use crate::storage::storage_config::DatabaseConfig;
+
+/// Counts the rows of the input on the first pass, returning them without their columns, and replays the count after a reset.
+/// Useful for checking the existence of rows repeatedly, without keeping the rows themselves.
+#[derive(Clone)]
+pub struct RowCountSpool<Iter: DBOperator> {
+    iterator: Iter,
+    count: usize,
+    position: usize,
+    complete: bool
+}
+
+impl<Iter: DBOperator> RowCountSpool<Iter> {
+    pub fn new(iterator: Iter) -> Self {
+        RowCountSpool {
+            iterator,
+            count: 0,
+            position: 0,
+            complete: false
+        }
+    }
+
+    fn empty_row() -> Row {
+        Row {
+            id: Uuid::nil(),
+            fields: Vec::<ObjectField>::new().into()
+        }
+    }
+}
+
+impl<Iter: DBOperator> DBOperator for RowCountSpool<Iter> {
+    fn next(&mut self) -> DBResult<Option<Row>> {
+        if self.position < self.count {
+            self.position += 1;
+            return Ok(Some(Self::empty_row()));
+        }
+        if self.complete {
+            return Ok(None);
+        }
+        match self.iterator.next()? {
+            Some(_) => {
+                self.count += 1;
+                self.position += 1;
+                Ok(Some(Self::empty_row()))
+            },
+            None => {
+                self.complete = true;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Keeps the counted rows - only the replay starts over
+    fn reset(&mut self) {
+        self.position = 0;
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let counted = self.count - self.position;
+        if self.complete {
+            return (counted, Some(counted));
+        }
+        let (low, high) = self.iterator.size_hint();
+        (counted + low, high.map(|high| counted + high))
+    }
+
+    fn storage_config(&self) -> Option<&DatabaseConfig> {
+        self.iterator.storage_config()
+    }
+}
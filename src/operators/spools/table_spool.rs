@@ -0,0 +1,72 @@
+use crate::{DBOperator, DBResult, Row};
+use crate::storage::storage_config::DatabaseConfig;
+
+/// Caches the rows of the input on the first pass, and replays them after a reset without reading the input again.
+/// A reset in the middle of the first pass replays the cached rows, then continues reading the input.
+#[derive(Clone)]
+pub struct TableSpool<Iter: DBOperator> {
+    iterator: Iter,
+    rows: Vec<Row>,
+    position: usize,
+    complete: bool
+}
+
+impl<Iter: DBOperator> TableSpool<Iter> {
+    pub fn new(iterator: Iter) -> Self {
+        TableSpool {
+            iterator,
+            rows: vec![],
+            position: 0,
+            complete: false
+        }
+    }
+}
+
+impl<Iter: DBOperator> DBOperator for TableSpool<Iter> {
+    fn next(&mut self) -> DBResult<Option<Row>> {
+        if let Some(row) = self.rows.get(self.position) {
+            self.position += 1;
+            return Ok(Some(row.clone()));
+        }
+        if self.complete {
+            return Ok(None);
+        }
+        match self.iterator.next()? {
+            Some(row) => {
+                self.rows.push(row.clone());
+                self.position += 1;
+                Ok(Some(row))
+            },
+            None => {
+                self.complete = true;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Keeps the cached rows - only the replay starts over
+    fn reset(&mut self) {
+        self.position = 0;
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let cached = self.rows.len() - self.position;
+        if self.complete {
+            return (cached, Some(cached));
+        }
+        let (low, high) = self.iterator.size_hint();
+        (cached + low, high.map(|high| cached + high))
+    }
+
+    fn storage_config(&self) -> Option<&DatabaseConfig> {
+        self.iterator.storage_config()
+    }
+
+    fn cost_hint(&self) -> Option<usize> {
+        if self.complete {
+            Some(self.rows.len() - self.position)
+        } else {
+            self.iterator.cost_hint()
+        }
+    }
+}
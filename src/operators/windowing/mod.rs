@@ -0,0 +1 @@
+mod window; pub use window::{Window, WindowFunction, WindowFrame, FrameUnit, FrameBound};
@@ -0,0 +1,342 @@
+use std::cmp::Ordering;
+
+use crate::{DBOperator, DBResult, EntryFields, ObjectField, Row};
+use crate::storage::storage_config::DatabaseConfig;
+
+/// Function computed over the window of every row - the partition the row belongs to, ordered by the order key.
+#[derive(Clone, Debug)]
+pub enum WindowFunction {
+    /// Position of the row within its partition, starting at 1
+    RowNumber,
+    /// Position of the first row with the same order key, starting at 1 - ties leave gaps
+    Rank,
+    /// Number of distinct order keys up to and including the row's - ties leave no gaps
+    DenseRank,
+    /// Value of the column `offset` rows before the current row, or the default one outside the partition
+    Lag { column: usize, offset: usize, default: ObjectField },
+    /// Value of the column `offset` rows after the current row, or the default one outside the partition
+    Lead { column: usize, offset: usize, default: ObjectField },
+    /// Sum of the non-null numeric values of the column within the frame
+    Sum { column: usize, frame: WindowFrame },
+    /// Average of the non-null numeric values of the column within the frame
+    Avg { column: usize, frame: WindowFrame },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrameUnit {
+    /// Offsets count the rows
+    Rows,
+    /// Offsets are applied to the value of the first order key column, and rows with equal order keys are peers
+    Range
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrameBound {
+    UnboundedPreceding,
+    Preceding(usize),
+    CurrentRow,
+    Following(usize),
+    UnboundedFollowing
+}
+
+/// Rows of the partition an aggregating window function is computed over, relative to the current row
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WindowFrame {
+    pub unit: FrameUnit,
+    pub start: FrameBound,
+    pub end: FrameBound
+}
+
+impl WindowFrame {
+    pub fn rows(start: FrameBound, end: FrameBound) -> Self {
+        WindowFrame { unit: FrameUnit::Rows, start, end }
+    }
+
+    pub fn range(start: FrameBound, end: FrameBound) -> Self {
+        WindowFrame { unit: FrameUnit::Range, start, end }
+    }
+}
+
+impl Default for WindowFrame {
+    /// From the start of the partition to the last peer of the current row - a running total
+    fn default() -> Self {
+        WindowFrame::range(FrameBound::UnboundedPreceding, FrameBound::CurrentRow)
+    }
+}
+
+/// Computes the window functions for every row, appending the results as new columns in the order of the functions.
+/// The input is materialized, and the rows are returned ordered by their partition key, then by their order key.
+#[derive(Clone)]
+pub struct Window<Iter, PartitionFunction, OrderFunction>
+where   Iter: DBOperator,
+        PartitionFunction: Clone + Fn(&EntryFields) -> Vec<ObjectField>,
+        OrderFunction: Clone + Fn(&EntryFields) -> Vec<ObjectField>
+{
+    iterator: Iter,
+    partition_function: PartitionFunction,
+    order_function: OrderFunction,
+    functions: Vec<WindowFunction>,
+    output: Option<std::vec::IntoIter<Row>>
+}
+
+/// Materialized input row, along with its keys
+struct WindowRow {
+    partition: Vec<ObjectField>,
+    order: Vec<ObjectField>,
+    row: Row
+}
+
+/// Running sums of the numeric column values, used to compute the aggregate of any frame in constant time
+struct PrefixSums {
+    integers: Vec<i128>,
+    wide_counts: Vec<usize>,
+    decimals: Vec<f64>,
+    decimal_counts: Vec<usize>,
+    counts: Vec<usize>
+}
+
+impl PrefixSums {
+    fn new(rows: &[WindowRow], column: usize) -> Self {
+        let mut sums = PrefixSums {
+            integers: Vec::with_capacity(rows.len() + 1),
+            wide_counts: Vec::with_capacity(rows.len() + 1),
+            decimals: Vec::with_capacity(rows.len() + 1),
+            decimal_counts: Vec::with_capacity(rows.len() + 1),
+            counts: Vec::with_capacity(rows.len() + 1)
+        };
+        let (mut integer, mut wide_count, mut decimal, mut decimal_count, mut count) = (0i128, 0, 0f64, 0, 0);
+        sums.push(integer, wide_count, decimal, decimal_count, count);
+        for row in rows {
+            match row.row.fields.column(column) {
+                ObjectField::I32(i) => { integer += i128::from(i); count += 1; },
+                ObjectField::I64(i) => { integer += i128::from(i); wide_count += 1; count += 1; },
+                ObjectField::Decimal(d) => { decimal += d; decimal_count += 1; count += 1; },
+                _ => { }
+            }
+            sums.push(integer, wide_count, decimal, decimal_count, count);
+        }
+        sums
+    }
+
+    fn push(&mut self, integer: i128, wide_count: usize, decimal: f64, decimal_count: usize, count: usize) {
+        self.integers.push(integer);
+        self.wide_counts.push(wide_count);
+        self.decimals.push(decimal);
+        self.decimal_counts.push(decimal_count);
+        self.counts.push(count);
+    }
+
+    /// Sum of the frame, typed like [`crate::SelectField::Sum`] - I32 for I32 values, promoted to I64, then Decimal, when it does not fit
+    fn sum(&self, start: usize, end: usize) -> ObjectField {
+        if start >= end || self.counts[end] == self.counts[start] {
+            return ObjectField::Null;
+        }
+        let integer = self.integers[end] - self.integers[start];
+        if self.decimal_counts[end] == self.decimal_counts[start] {
+            match i32::try_from(integer) {
+                Ok(integer) if self.wide_counts[end] == self.wide_counts[start] => ObjectField::I32(integer),
+                _ => i64::try_from(integer).map_or_else(|_| ObjectField::Decimal(integer as f64), ObjectField::I64)
+            }
+        } else {
+            ObjectField::Decimal(integer as f64 + self.decimals[end] - self.decimals[start])
+        }
+    }
+
+    fn avg(&self, start: usize, end: usize) -> ObjectField {
+        let count = if start >= end { 0 } else { self.counts[end] - self.counts[start] };
+        if count == 0 {
+            return ObjectField::Null;
+        }
+        let sum = (self.integers[end] - self.integers[start]) as f64 + self.decimals[end] - self.decimals[start];
+        ObjectField::Decimal(sum / count as f64)
+    }
+}
+
+impl<Iter, PartitionFunction, OrderFunction> Window<Iter, PartitionFunction, OrderFunction>
+where   Iter: DBOperator,
+        PartitionFunction: Clone + Fn(&EntryFields) -> Vec<ObjectField>,
+        OrderFunction: Clone + Fn(&EntryFields) -> Vec<ObjectField>
+{
+    pub fn new(iterator: Iter, partition_function: PartitionFunction, order_function: OrderFunction, functions: Vec<WindowFunction>) -> Self {
+        Window {
+            iterator,
+            partition_function,
+            order_function,
+            functions,
+            output: None
+        }
+    }
+
+    fn compute(&mut self) -> DBResult<Vec<Row>> {
+        let mut rows = Vec::with_capacity(self.iterator.size_hint().0);
+        while let Some(row) = self.iterator.next()? {
+            rows.push(WindowRow {
+                partition: (self.partition_function)(&row.fields),
+                order: (self.order_function)(&row.fields),
+                row
+            });
+        }
+        rows.sort_by(|a, b| a.partition.cmp(&b.partition).then_with(|| a.order.cmp(&b.order)));
+
+        let mut output = Vec::with_capacity(rows.len());
+        let mut partition_start = 0;
+        while partition_start < rows.len() {
+            let partition_end = partition_start + rows[partition_start..]
+                .iter()
+                .position(|row| row.partition != rows[partition_start].partition)
+                .unwrap_or(rows.len() - partition_start);
+            let partition = &rows[partition_start..partition_end];
+
+            let mut columns: Vec<Vec<ObjectField>> = vec![Vec::with_capacity(self.functions.len()); partition.len()];
+            for function in &self.functions {
+                for (position, value) in Self::evaluate(function, partition).into_iter().enumerate() {
+                    columns[position].push(value);
+                }
+            }
+            for (row, columns) in partition.iter().zip(columns) {
+                output.push(Row {
+                    id: row.row.id,
+                    fields: EntryFields::combine(&row.row.fields, &columns.into())
+                });
+            }
+            partition_start = partition_end;
+        }
+        Ok(output)
+    }
+
+    /// Computes the function for every row of a sorted partition
+    fn evaluate(function: &WindowFunction, partition: &[WindowRow]) -> Vec<ObjectField> {
+        match function {
+            WindowFunction::RowNumber => (1..=partition.len() as i64).map(ObjectField::I64).collect(),
+            WindowFunction::Rank => {
+                let mut rank = 0;
+                (0..partition.len())
+                    .map(|position| {
+                        if position == 0 || partition[position].order != partition[position - 1].order {
+                            rank = position as i64 + 1;
+                        }
+                        ObjectField::I64(rank)
+                    })
+                    .collect()
+            },
+            WindowFunction::DenseRank => {
+                let mut rank = 0;
+                (0..partition.len())
+                    .map(|position| {
+                        if position == 0 || partition[position].order != partition[position - 1].order {
+                            rank += 1;
+                        }
+                        ObjectField::I64(rank)
+                    })
+                    .collect()
+            },
+            WindowFunction::Lag { column, offset, default } => (0..partition.len())
+                .map(|position| position
+                    .checked_sub(*offset)
+                    .map_or_else(|| default.clone(), |other| partition[other].row.fields.column(*column)))
+                .collect(),
+            WindowFunction::Lead { column, offset, default } => (0..partition.len())
+                .map(|position| partition
+                    .get(position + offset)
+                    .map_or_else(|| default.clone(), |other| other.row.fields.column(*column)))
+                .collect(),
+            WindowFunction::Sum { column, frame } | WindowFunction::Avg { column, frame } => {
+                let sums = PrefixSums::new(partition, *column);
+                (0..partition.len())
+                    .map(|position| {
+                        let (start, end) = Self::frame_bounds(frame, partition, position);
+                        if let WindowFunction::Sum { .. } = function {
+                            sums.sum(start, end)
+                        } else {
+                            sums.avg(start, end)
+                        }
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Returns the range of partition positions, `start..end`, within the frame of the row
+    fn frame_bounds(frame: &WindowFrame, partition: &[WindowRow], position: usize) -> (usize, usize) {
+        let start = match (frame.unit, frame.start) {
+            (_, FrameBound::UnboundedPreceding) => 0,
+            (_, FrameBound::UnboundedFollowing) => partition.len(),
+            (FrameUnit::Rows, FrameBound::Preceding(offset)) => position.saturating_sub(offset),
+            (FrameUnit::Rows, FrameBound::CurrentRow) => position,
+            (FrameUnit::Rows, FrameBound::Following(offset)) => (position + offset).min(partition.len()),
+            (FrameUnit::Range, FrameBound::CurrentRow) => partition.partition_point(|row| row.order < partition[position].order),
+            (FrameUnit::Range, FrameBound::Preceding(offset)) => Self::range_start(partition, position, -(offset as f64)),
+            (FrameUnit::Range, FrameBound::Following(offset)) => Self::range_start(partition, position, offset as f64),
+        };
+        let end = match (frame.unit, frame.end) {
+            (_, FrameBound::UnboundedPreceding) => 0,
+            (_, FrameBound::UnboundedFollowing) => partition.len(),
+            (FrameUnit::Rows, FrameBound::Preceding(offset)) => (position + 1).saturating_sub(offset),
+            (FrameUnit::Rows, FrameBound::CurrentRow) => position + 1,
+            (FrameUnit::Rows, FrameBound::Following(offset)) => (position + offset + 1).min(partition.len()),
+            (FrameUnit::Range, FrameBound::CurrentRow) => partition.partition_point(|row| row.order <= partition[position].order),
+            (FrameUnit::Range, FrameBound::Preceding(offset)) => Self::range_end(partition, position, -(offset as f64)),
+            (FrameUnit::Range, FrameBound::Following(offset)) => Self::range_end(partition, position, offset as f64),
+        };
+        (start, end.max(start))
+    }
+
+    /// First position whose first order key is at least the current one moved by the offset
+    fn range_start(partition: &[WindowRow], position: usize, offset: f64) -> usize {
+        let Some(bound) = Self::range_key(&partition[position]).map(|key| key + offset) else {
+            return partition.partition_point(|row| row.order < partition[position].order);
+        };
+        partition.partition_point(|row| Self::compare_range_key(row, bound) == Ordering::Less)
+    }
+
+    /// Position after the last one whose first order key is at most the current one moved by the offset
+    fn range_end(partition: &[WindowRow], position: usize, offset: f64) -> usize {
+        let Some(bound) = Self::range_key(&partition[position]).map(|key| key + offset) else {
+            return partition.partition_point(|row| row.order <= partition[position].order);
+        };
+        partition.partition_point(|row| Self::compare_range_key(row, bound) != Ordering::Greater)
+    }
+
+    fn range_key(row: &WindowRow) -> Option<f64> {
+        match row.order.first()? {
+            ObjectField::I32(i) => Some(f64::from(*i)),
+            ObjectField::I64(i) => Some(*i as f64),
+            ObjectField::Decimal(d) => Some(*d),
+            _ => None
+        }
+    }
+
+    /// Nulls and other non-numeric keys sort before the numeric ones
+    fn compare_range_key(row: &WindowRow, bound: f64) -> Ordering {
+        Self::range_key(row).map_or(Ordering::Less, |key| key.total_cmp(&bound))
+    }
+}
+
+impl<Iter, PartitionFunction, OrderFunction> DBOperator for Window<Iter, PartitionFunction, OrderFunction>
+where   Iter: DBOperator,
+        PartitionFunction: Clone + Fn(&EntryFields) -> Vec<ObjectField>,
+        OrderFunction: Clone + Fn(&EntryFields) -> Vec<ObjectField>
+{
+    fn next(&mut self) -> DBResult<Option<Row>> {
+        if self.output.is_none() {
+            self.output = Some(self.compute()?.into_iter());
+        }
+        Ok(self.output.as_mut().and_then(Iterator::next))
+    }
+
+    fn reset(&mut self) {
+        self.iterator.reset();
+        self.output = None;
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.output {
+            Some(output) => (output.len(), Some(output.len())),
+            None => self.iterator.size_hint()
+        }
+    }
+
+    fn storage_config(&self) -> Option<&DatabaseConfig> {
+        self.iterator.storage_config()
+    }
+}
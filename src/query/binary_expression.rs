@@ -1,15 +1,19 @@
 use std::fmt::{Display, Write};
 
-use super::{condition::{Condition, Normalizable}, unary_expression::{UnaryExpressionType, UnaryExpression}};
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+use crate::{errors::query_error::EvaluationError, utils::DBResult, EntryFields, ObjectField};
+
+use super::{condition::{Condition, Evaluable, Normalizable}, unary_expression::{UnaryExpressionType, UnaryExpression}};
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct BinaryExpression {
     pub expression_type: BinaryExpressionType,
     pub first: Box<Condition>,
     pub second: Box<Condition>
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum BinaryExpressionType {
     EqualTo,
     NotEqualTo,
@@ -60,6 +64,57 @@ impl Normalizable for BinaryExpression {
     }
 }
 
+impl Evaluable for BinaryExpression {
+    fn evaluate(&self, row: &EntryFields) -> DBResult<ObjectField> {
+        let first = self.first.evaluate(row)?;
+        let second = self.second.evaluate(row)?;
+        let result = match self.expression_type {
+            BinaryExpressionType::EqualTo => first == second,
+            BinaryExpressionType::NotEqualTo => first != second,
+            BinaryExpressionType::LessThan => first < second,
+            BinaryExpressionType::LessThanOrEqualTo => first <= second,
+            BinaryExpressionType::GreaterThan => first > second,
+            BinaryExpressionType::GreaterThanOrEqualTo => first >= second,
+            BinaryExpressionType::Like | BinaryExpressionType::NotLike => {
+                let (ObjectField::String(text), ObjectField::String(pattern)) = (&first, &second) else {
+                    return Err(EvaluationError::from_string(format!("Cannot match {} against the pattern {}", first, second)).into());
+                };
+                let text: Vec<char> = text.chars().collect();
+                let pattern: Vec<char> = pattern.chars().collect();
+                is_like(&text, &pattern) == (self.expression_type == BinaryExpressionType::Like)
+            }
+        };
+        Ok(result.into())
+    }
+}
+
+/// Matches the text against a LIKE pattern - `%` matches any sequence of characters, `_` matches a single character
+fn is_like(text: &[char], pattern: &[char]) -> bool {
+    let (mut text_index, mut pattern_index) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while text_index < text.len() {
+        match pattern.get(pattern_index) {
+            Some('%') => {
+                backtrack = Some((pattern_index, text_index));
+                pattern_index += 1;
+            },
+            Some(c) if *c == '_' || *c == text[text_index] => {
+                pattern_index += 1;
+                text_index += 1;
+            },
+            _ => match backtrack {
+                Some((star_index, star_text_index)) => {
+                    pattern_index = star_index + 1;
+                    text_index = star_text_index + 1;
+                    backtrack = Some((star_index, star_text_index + 1));
+                },
+                None => return false
+            }
+        }
+    }
+    pattern[pattern_index..].iter().all(|c| *c == '%')
+}
+
 impl Display for BinaryExpression {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self.first.as_ref() {
@@ -1,6 +1,8 @@
-use std::{fmt::{Display, Write}, iter, collections::BTreeSet};
+use std::{fmt::{Display, Write}, iter, collections::BTreeSet, sync::Arc};
 
-use crate::objects::ObjectField;
+use serde::{Deserialize, Serialize};
+
+use crate::{errors::query_error::EvaluationError, objects::ObjectField, utils::DBResult, EntryFields};
 
 use super::{unary_expression::UnaryExpression, binary_expression::BinaryExpression};
 
@@ -8,7 +10,12 @@ pub trait Normalizable {
     fn normalize(self) -> Condition;
 }
 
-#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub trait Evaluable {
+    /// Evaluates the expression against the row
+    fn evaluate(&self, row: &EntryFields) -> DBResult<ObjectField>;
+}
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum Condition {
     Or(BTreeSet<Condition>),
     And(BTreeSet<Condition>),
@@ -16,10 +23,72 @@ pub enum Condition {
     Unary(UnaryExpression),
     Binary(BinaryExpression),
 
-    Column(&'static str),
+    /// Column referenced by name - rows do not carry column names, so it cannot be evaluated.
+    /// The name is shared rather than `&'static str`, so that the names loaded with the index definitions are not leaked.
+    Column(Arc<str>),
+    /// Column referenced by its position in the row
+    ColumnIndex(usize),
     Value(ObjectField)
 }
 
+impl Condition {
+    /// Evaluates the condition against the row, expecting a boolean result
+    pub fn is_satisfied(&self, row: &EntryFields) -> DBResult<bool> {
+        self.evaluate(row)?.truthy()
+    }
+
+    /// Returns the conjuncts of the condition, after normalizing it
+    pub fn conjuncts(&self) -> BTreeSet<Condition> {
+        match self.clone().normalize() {
+            Condition::And(conditions) => conditions,
+            condition => iter::once(condition).collect()
+        }
+    }
+}
+
+impl ObjectField {
+    pub(crate) fn truthy(&self) -> DBResult<bool> {
+        match self {
+            ObjectField::Bool(b) => Ok(*b),
+            other => Err(EvaluationError::from_string(format!("Expected a boolean value, got {}", other)).into())
+        }
+    }
+}
+
+impl Evaluable for Condition {
+    fn evaluate(&self, row: &EntryFields) -> DBResult<ObjectField> {
+        match self {
+            Condition::Or(conditions) => {
+                for condition in conditions {
+                    if condition.is_satisfied(row)? {
+                        return Ok(true.into());
+                    }
+                }
+                Ok(false.into())
+            },
+            Condition::And(conditions) => {
+                for condition in conditions {
+                    if !condition.is_satisfied(row)? {
+                        return Ok(false.into());
+                    }
+                }
+                Ok(true.into())
+            },
+            Condition::Unary(expression) => expression.evaluate(row),
+            Condition::Binary(expression) => expression.evaluate(row),
+            Condition::Column(name) => Err(EvaluationError::from_string(format!("Column \"{}\" cannot be resolved - use a column index instead", name)).into()),
+            Condition::ColumnIndex(index) => {
+                if *index < row.len() {
+                    Ok(row.column(*index))
+                } else {
+                    Err(EvaluationError::from_string(format!("Column #{} is outside of the row", index)).into())
+                }
+            },
+            Condition::Value(value) => Ok(value.clone()),
+        }
+    }
+}
+
 impl Normalizable for Condition {
     fn normalize(self) -> Condition {
         match self {
@@ -77,6 +146,7 @@ impl Normalizable for Condition {
 
             Condition::Value(_) => self,
             Condition::Column(_) => self,
+            Condition::ColumnIndex(_) => self,
         }
     }
 }
@@ -118,6 +188,7 @@ impl Display for Condition {
             },
             Condition::Unary(expr) => expr.fmt(f),
             Condition::Column(s) => f.write_str(s),
+            Condition::ColumnIndex(i) => write!(f, "#{}", i),
             Condition::Binary(expr) => expr.fmt(f),
             Condition::Value(value) => value.fmt(f),
         }
@@ -144,8 +215,8 @@ mod tests {
                 Condition::Binary(
                     BinaryExpression {
                         expression_type: BinaryExpressionType::NotEqualTo,
-                        first: Condition::Column("a").into(),
-                        second: Condition::Column("b").into()
+                        first: Condition::Column("a".into()).into(),
+                        second: Condition::Column("b".into()).into()
                     }
                 )
             ]
@@ -166,7 +237,7 @@ mod tests {
                 expression_type: UnaryExpressionType::Not,
                 term: Box::new(Condition::Unary(UnaryExpression {
                     expression_type: UnaryExpressionType::Not,
-                    term: Condition::Column("a").into(),
+                    term: Condition::Column("a".into()).into(),
                 }))
             }
         );
@@ -185,9 +256,9 @@ mod tests {
             UnaryExpression {
                 expression_type: UnaryExpressionType::Not,
                 term: Box::new(Condition::Or(set![
-                    Condition::Column("a"),
-                    Condition::Column("b"),
-                    Condition::Column("c"),
+                    Condition::Column("a".into()),
+                    Condition::Column("b".into()),
+                    Condition::Column("c".into()),
                 ]))
             }
         );
@@ -206,9 +277,9 @@ mod tests {
             UnaryExpression {
                 expression_type: UnaryExpressionType::Not,
                 term: Box::new(Condition::And(set![
-                    Condition::Column("a"),
-                    Condition::Column("b"),
-                    Condition::Column("c"),
+                    Condition::Column("a".into()),
+                    Condition::Column("b".into()),
+                    Condition::Column("c".into()),
                 ]))
             }
         );
@@ -228,15 +299,15 @@ mod tests {
                 Condition::Binary(
                     BinaryExpression {
                         expression_type: BinaryExpressionType::EqualTo,
-                        first: Condition::Column("a").into(),
-                        second: Condition::Column("b").into()
+                        first: Condition::Column("a".into()).into(),
+                        second: Condition::Column("b".into()).into()
                     }
                 ),
                 Condition::Binary(
                     BinaryExpression {
                         expression_type: BinaryExpressionType::LessThan,
-                        first: Condition::Column("c").into(),
-                        second: Condition::Column("d").into()
+                        first: Condition::Column("c".into()).into(),
+                        second: Condition::Column("d".into()).into()
                     }
                 )
             ]
@@ -257,8 +328,8 @@ mod tests {
                 Condition::Binary(
                     BinaryExpression {
                         expression_type: BinaryExpressionType::EqualTo,
-                        first: Condition::Column("a").into(),
-                        second: Condition::Column("b").into()
+                        first: Condition::Column("a".into()).into(),
+                        second: Condition::Column("b".into()).into()
                     }
                 ),
                 Condition::Or(
@@ -266,15 +337,15 @@ mod tests {
                         Condition::Binary(
                             BinaryExpression {
                                 expression_type: BinaryExpressionType::LessThan,
-                                first: Condition::Column("c").into(),
-                                second: Condition::Column("d").into()
+                                first: Condition::Column("c".into()).into(),
+                                second: Condition::Column("d".into()).into()
                             }
                         ),
                         Condition::Binary(
                             BinaryExpression {
                                 expression_type: BinaryExpressionType::GreaterThan,
-                                first: Condition::Column("f").into(),
-                                second: Condition::Column("g").into()
+                                first: Condition::Column("f".into()).into(),
+                                second: Condition::Column("g".into()).into()
                             }
                         )
                     ]
@@ -298,8 +369,8 @@ mod tests {
                 Condition::Binary(
                     BinaryExpression {
                         expression_type: BinaryExpressionType::EqualTo,
-                        first: Condition::Column("a").into(),
-                        second: Condition::Column("b").into()
+                        first: Condition::Column("a".into()).into(),
+                        second: Condition::Column("b".into()).into()
                     }
                 ),
                 Condition::And(
@@ -307,15 +378,15 @@ mod tests {
                         Condition::Binary(
                             BinaryExpression {
                                 expression_type: BinaryExpressionType::LessThan,
-                                first: Condition::Column("c").into(),
-                                second: Condition::Column("d").into()
+                                first: Condition::Column("c".into()).into(),
+                                second: Condition::Column("d".into()).into()
                             }
                         ),
                         Condition::Binary(
                             BinaryExpression {
                                 expression_type: BinaryExpressionType::GreaterThan,
-                                first: Condition::Column("f").into(),
-                                second: Condition::Column("g").into()
+                                first: Condition::Column("f".into()).into(),
+                                second: Condition::Column("g".into()).into()
                             }
                         )
                     ]
@@ -336,14 +407,14 @@ mod tests {
     fn condition_normalization_test_and_or_three() {
         let c = Condition::Or(
             set![
-                Condition::Column("a"),
+                Condition::Column("a".into()),
                 Condition::And(
                     set![
-                        Condition::Column("b"),
-                        Condition::Column("c")
+                        Condition::Column("b".into()),
+                        Condition::Column("c".into())
                     ]
                 ),
-                Condition::Column("d")
+                Condition::Column("d".into())
             ]
         );
 
@@ -362,14 +433,14 @@ mod tests {
             term: Box::new(
                 Condition::Or(
                     set![
-                        Condition::Column("a"),
+                        Condition::Column("a".into()),
                         Condition::And(
                             set![
-                                Condition::Column("b"),
-                                Condition::Column("c")
+                                Condition::Column("b".into()),
+                                Condition::Column("c".into())
                             ]
                         ),
-                        Condition::Column("d")
+                        Condition::Column("d".into())
                     ]
                 )
             )
@@ -1,14 +1,16 @@
 use std::{marker::PhantomData, collections::BTreeSet};
 
+use log::debug;
 use serde::Deserialize;
 use uuid::Uuid;
 
-use crate::{set, utils::DBResult};
+use crate::{errors::DatabaseError, objects::ObjectDeserializer, operators::{IndexSeek, TableScan}, set, utils::DBResult, Row};
 
-use self::condition::{Condition, Normalizable};
+use self::condition::Condition;
 
 use super::collection::Collection;
 use crate::operators::DBOperator;
+use crate::storage::storage_config::DatabaseConfig;
 
 pub mod condition;
 pub mod binary_expression;
@@ -37,19 +39,75 @@ impl<'a, Item: Deserialize<'a> + 'a> Query<'a, Item> {
     }
 
     pub fn filter(mut self, condition: Condition) -> Self {
-        let condition = condition.normalize();
-        match condition {
-            Condition::And(conditions) => {
-                self.conditions.extend(conditions);
-            },
-            c => {
-                self.conditions.insert(c);
-            },
-        };
+        self.conditions.extend(condition.conjuncts());
         self
     }
 
+    /// Executes the query.
+    /// If a partial index covering all the rows matching the filter exists, it is scanned instead of the whole collection.
     pub fn collect(self) -> DBResult<Vec<Item>> {
-        self.on.table_scan(self.transaction_id).deserialize::<Item>().collect()
+        let mut source = match self.on.applicable_indexes(&self.conditions).find(|index| index.predicate.is_some()) {
+            Some(index) => {
+                debug!("Query on collection {} uses the partial index {}", self.on.name(), index.name);
+                QuerySource::Index(self.on.index_scan(self.transaction_id, &index.name)?)
+            },
+            None => QuerySource::Table(self.on.table_scan(self.transaction_id))
+        };
+
+        let mut result = vec![];
+        while let Some(row) = source.next()? {
+            if self.matches(&row)? {
+                let mut deserializer = ObjectDeserializer::new(row.fields);
+                let item = Item::deserialize(&mut deserializer)
+                    .map_err(|err| DatabaseError::Query(err.into()))?;
+                result.push(item);
+            }
+        }
+        Ok(result)
+    }
+
+    fn matches(&self, row: &Row) -> DBResult<bool> {
+        for condition in &self.conditions {
+            if !condition.is_satisfied(&row.fields)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+#[derive(Clone)]
+enum QuerySource<'a> {
+    Table(TableScan<'a>),
+    Index(IndexSeek<'a>)
+}
+
+impl<'a> DBOperator for QuerySource<'a> {
+    fn next(&mut self) -> DBResult<Option<Row>> {
+        match self {
+            QuerySource::Table(scan) => scan.next(),
+            QuerySource::Index(seek) => seek.next()
+        }
+    }
+
+    fn reset(&mut self) {
+        match self {
+            QuerySource::Table(scan) => scan.reset(),
+            QuerySource::Index(seek) => seek.reset()
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            QuerySource::Table(scan) => scan.size_hint(),
+            QuerySource::Index(seek) => seek.size_hint()
+        }
+    }
+
+    fn storage_config(&self) -> Option<&DatabaseConfig> {
+        match self {
+            QuerySource::Table(scan) => scan.storage_config(),
+            QuerySource::Index(seek) => seek.storage_config()
+        }
     }
 }
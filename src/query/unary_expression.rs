@@ -1,14 +1,18 @@
 use std::fmt::{Display, Write};
 
-use super::condition::{Condition, Normalizable};
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+use crate::{errors::query_error::EvaluationError, utils::DBResult, EntryFields, ObjectField};
+
+use super::condition::{Condition, Evaluable, Normalizable};
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct UnaryExpression {
     pub expression_type: UnaryExpressionType,
     pub term: Box<Condition>
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum UnaryExpressionType {
     Length,
     Not
@@ -59,6 +63,20 @@ impl Normalizable for UnaryExpression {
     }
 }
 
+impl Evaluable for UnaryExpression {
+    fn evaluate(&self, row: &EntryFields) -> DBResult<ObjectField> {
+        let term = self.term.evaluate(row)?;
+        match self.expression_type {
+            UnaryExpressionType::Length => match term {
+                ObjectField::String(s) => Ok(ObjectField::I64(s.chars().count() as i64)),
+                ObjectField::Bytes(b) => Ok(ObjectField::I64(b.len() as i64)),
+                other => Err(EvaluationError::from_string(format!("Cannot take the length of {}", other)).into())
+            },
+            UnaryExpressionType::Not => Ok((!term.truthy()?).into())
+        }
+    }
+}
+
 impl Display for UnaryExpression {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self.expression_type {
@@ -1,4 +1,4 @@
-use std::{fs::{self, File}, io::{Read, Write}, ops::{Deref, DerefMut}, rc::Rc, sync::RwLock};
+use std::{fs::{self, File}, io::{Read, Write}, ops::{Deref, DerefMut}, sync::{Arc, RwLock}};
 
 use crate::{errors::DatabaseError, utils::{DBResult, GuardExtensions, SplittableByLengthEncoding}};
 use self::log_entry::LogEntry;
@@ -44,7 +44,7 @@ impl LogFile {
     }
 
     fn deserialize(file: Vec<u8>, file_index: usize) -> DBResult<LogFile> {
-        let file: Rc<[u8]> = file.into_boxed_slice().into();
+        let file: Arc<[u8]> = file.into_boxed_slice().into();
         let vector: DBResult<Vec<LogEntry>> = file
             .split_by_length_encoding()
             .map(|(rc, range)| LogEntry::decompress(rc, range))
@@ -1,4 +1,4 @@
-use std::{fmt::Display, ops::{Deref, Range}, rc::Rc};
+use std::{fmt::Display, ops::{Deref, Range}, sync::Arc};
 
 use log_err::LogErrResult;
 use uuid::Uuid;
@@ -19,7 +19,7 @@ impl Deref for DeserializedFields {
 }
 
 #[derive(Clone)]
-pub struct EntryFields(pub(crate) Range<usize>, pub(crate) Rc<[u8]>);
+pub struct EntryFields(pub(crate) Range<usize>, pub(crate) Arc<[u8]>);
 
 impl From<Vec<ObjectField>> for EntryFields {
     fn from(fields: Vec<ObjectField>) -> Self {
@@ -29,46 +29,11 @@ impl From<Vec<ObjectField>> for EntryFields {
 
 impl From<Vec<SelectField>> for EntryFields {
     fn from(fields: Vec<SelectField>) -> Self {
-        let mut data = Vec::with_capacity(1 + fields.iter().map(|f| 1 + match &**f {
-            ObjectField::Bool(_) => 1,
-            ObjectField::I32(_) => 4,
-            ObjectField::I64(_) => 8,
-            ObjectField::Decimal(_) => 8,
-            ObjectField::Id(_) => 16,
-            ObjectField::Bytes(b) => 1 + b.len(),
-            ObjectField::String(b) => 1 + b.len()
-        }).sum::<usize>());
-        data.push(fields.len() as u8);
-        
-        data.extend(fields.iter().map(|f| match &**f {
-            ObjectField::Bool(_) => FieldType::Bool,
-            ObjectField::I32(_) => FieldType::I32,
-            ObjectField::I64(_) => FieldType::I64,
-            ObjectField::Decimal(_) => FieldType::Decimal,
-            ObjectField::Id(_) => FieldType::Id,
-            ObjectField::Bytes(_) => FieldType::Bytes,
-            ObjectField::String(_) => FieldType::String,
-        } as u8));
-        
-        for field in fields {
-            match &*field {
-                ObjectField::Bool(b) => data.push(if *b { 1 } else { 0 }),
-                ObjectField::I32(i) => data.extend(i.to_le_bytes()),
-                ObjectField::I64(i) => data.extend(i.to_le_bytes()),
-                ObjectField::Decimal(f) => data.extend(f.to_le_bytes()),
-                ObjectField::Id(id) => data.extend(id.as_bytes()),
-                ObjectField::Bytes(bytes) => {
-                    data.push(bytes.len() as u8);
-                    data.extend(bytes.iter());
-                },
-                ObjectField::String(str) => {
-                    data.push(str.len() as u8);
-                    data.extend(str.as_bytes())
-                },
-            };
-        }
-        let len = data.len();
-        EntryFields(0..len, data.into_boxed_slice().into())
+        fields
+            .into_iter()
+            .map(SelectField::finish)
+            .collect::<Vec<_>>()
+            .into()
     }
 }
 
@@ -81,7 +46,8 @@ impl From<&[ObjectField]> for EntryFields {
             ObjectField::Decimal(_) => 8,
             ObjectField::Id(_) => 16,
             ObjectField::Bytes(b) => 1 + b.len(),
-            ObjectField::String(b) => 1 + b.len()
+            ObjectField::String(b) => 1 + b.len(),
+            ObjectField::Null => 0
         }).sum::<usize>());
         data.push(fields.len() as u8);
         
@@ -93,6 +59,7 @@ impl From<&[ObjectField]> for EntryFields {
             ObjectField::Id(_) => FieldType::Id,
             ObjectField::Bytes(_) => FieldType::Bytes,
             ObjectField::String(_) => FieldType::String,
+            ObjectField::Null => FieldType::Null,
         } as u8));
         
         for field in fields {
@@ -110,6 +77,7 @@ impl From<&[ObjectField]> for EntryFields {
                     data.push(str.len() as u8);
                     data.extend(str.as_bytes())
                 },
+                ObjectField::Null => { },
             };
         }
         let len = data.len();
@@ -139,56 +107,112 @@ impl EntryFields {
             FieldType::Decimal => ObjectField::Decimal(f64::from_le_bytes(bytes.try_into().log_unwrap())),
             FieldType::Id => ObjectField::Id(Uuid::from_bytes_le(bytes.try_into().log_unwrap())),
             FieldType::String => ObjectField::String(unsafe { str::from_utf8_unchecked(bytes) }.into()),
-            FieldType::Bytes => ObjectField::Bytes(bytes.into())
+            FieldType::Bytes => ObjectField::Bytes(bytes.into()),
+            FieldType::Null => ObjectField::Null
         }
     }
 
+    /// The encoded column count, types and values - equal for rows with the same values
+    pub(crate) fn data(&self) -> &[u8] {
+        &self.1[self.0.clone()]
+    }
+
     pub(crate) fn column_bytes(&self) -> &[u8] {
         &self.1[self.0.start + self.len() + 1..self.0.end]
     }
 
     pub(crate) fn into_yoke_vector(self) -> Yoke<DeserializedFields, Box<EntryFields>> {
         let that = Box::new(self);
-        Yoke::attach_to_cart(that, |data| {
-            let count = data.len();
-            let types = data.column_types();
-            let data = data.column_bytes();
-
-            let mut result = Vec::with_capacity(count);
-            let mut current_index: usize = 0;
-            let mut current_pointer = 0;
-            while current_index != count {
-                let field_type = &types[current_index];
-                let d = &data[current_pointer..];
-
-                result.push(match field_type {
+        Yoke::attach_to_cart(that, |data| DeserializedFields(data.values()))
+    }
+
+    /// Decodes the values of all the columns, in a single pass over the data
+    pub fn values(&self) -> Vec<ObjectField> {
+        let count = self.len();
+        let types = self.column_types();
+        let data = self.column_bytes();
+
+        let mut result = Vec::with_capacity(count);
+        let mut current_index: usize = 0;
+        let mut current_pointer = 0;
+        while current_index != count {
+            let field_type = &types[current_index];
+            let d = &data[current_pointer..];
+
+            result.push(match field_type {
+                FieldType::Bool => ObjectField::Bool(d[0] > 0),
+                FieldType::I32 => ObjectField::I32(i32::from_le_bytes(d[..4].try_into().log_unwrap())),
+                FieldType::I64 => ObjectField::I64(i64::from_le_bytes(d[..8].try_into().log_unwrap())),
+                FieldType::Decimal => ObjectField::Decimal(f64::from_le_bytes(d[..8].try_into().log_unwrap())),
+                FieldType::Id => ObjectField::Id(Uuid::from_bytes_le(d[..16].try_into().log_unwrap())),
+                FieldType::String => {
+                    let str = unsafe { str::from_utf8_unchecked(&d[1..][..d[0] as usize]) };
+                    ObjectField::String(str.into())
+                },
+                FieldType::Bytes => {
+                    let bytes = &d[1..][..d[0] as usize];
+                    ObjectField::Bytes(bytes.into())
+                },
+                FieldType::Null => ObjectField::Null
+            });
+
+            current_pointer += match field_type {
+                FieldType::Bool => 1,
+                FieldType::I32 => 4,
+                FieldType::I64 => 8,
+                FieldType::Decimal => 8,
+                FieldType::Id => 16,
+                FieldType::String | FieldType::Bytes => 1 + d[0] as usize,
+                FieldType::Null => 0,
+            };
+            current_index += 1;
+        }
+        result
+    }
+
+    /// Decodes the values of the selected columns, in a single pass over the data - the other columns are skipped without decoding them.
+    /// The function gets the index of every selected column and its value.
+    pub(crate) fn selected_values(&self, selected: &[bool], mut function: impl FnMut(usize, ObjectField)) {
+        let Some(last_selected) = selected.iter().rposition(|selected| *selected) else {
+            return;
+        };
+        let types = self.column_types();
+        let data = self.column_bytes();
+
+        let mut current_pointer = 0;
+        for (current_index, field_type) in types.iter().enumerate().take(last_selected + 1) {
+            let d = &data[current_pointer..];
+            if selected[current_index] {
+                function(current_index, match field_type {
                     FieldType::Bool => ObjectField::Bool(d[0] > 0),
                     FieldType::I32 => ObjectField::I32(i32::from_le_bytes(d[..4].try_into().log_unwrap())),
                     FieldType::I64 => ObjectField::I64(i64::from_le_bytes(d[..8].try_into().log_unwrap())),
                     FieldType::Decimal => ObjectField::Decimal(f64::from_le_bytes(d[..8].try_into().log_unwrap())),
                     FieldType::Id => ObjectField::Id(Uuid::from_bytes_le(d[..16].try_into().log_unwrap())),
-                    FieldType::String => {
-                        let str = unsafe { str::from_utf8_unchecked(&d[1..][..d[0] as usize]) };
-                        ObjectField::String(str.into())
-                    },
-                    FieldType::Bytes => {
-                        let bytes = &d[1..][..d[0] as usize];
-                        ObjectField::Bytes(bytes.into())
-                    }
+                    FieldType::String => ObjectField::String(unsafe { str::from_utf8_unchecked(&d[1..][..d[0] as usize]) }.into()),
+                    FieldType::Bytes => ObjectField::Bytes(d[1..][..d[0] as usize].into()),
+                    FieldType::Null => ObjectField::Null
                 });
-
-                current_pointer += match field_type {
-                    FieldType::Bool => 1,
-                    FieldType::I32 => 4,
-                    FieldType::I64 => 8,
-                    FieldType::Decimal => 8,
-                    FieldType::Id => 16,
-                    FieldType::String | FieldType::Bytes => 1 + d[0] as usize,
-                };
-                current_index += 1;
             }
-            DeserializedFields(result)
-        })
+            current_pointer += match field_type {
+                FieldType::Bool => 1,
+                FieldType::I32 => 4,
+                FieldType::I64 => 8,
+                FieldType::Decimal => 8,
+                FieldType::Id => 16,
+                FieldType::String | FieldType::Bytes => 1 + d[0] as usize,
+                FieldType::Null => 0,
+            };
+        }
+    }
+
+    /// Value of the column, or null if the entry has less columns
+    pub(crate) fn column_or_null(&self, index: usize) -> ObjectField {
+        if index < self.len() {
+            self.column(index)
+        } else {
+            ObjectField::Null
+        }
     }
 
     pub(crate) fn get_column_data(&'_ self, index: usize) -> (FieldType, &'_ [u8]) {
@@ -207,6 +231,7 @@ impl EntryFields {
                 FieldType::Decimal => 8,
                 FieldType::Id => 16,
                 FieldType::String | FieldType::Bytes => 1 + data[current_pointer] as usize,
+                FieldType::Null => 0,
             };
             current_index += 1;
         }
@@ -219,7 +244,8 @@ impl EntryFields {
             FieldType::Decimal => &d[..8],
             FieldType::Id => &d[..16],
             FieldType::String => &d[1..][..d[0] as usize],
-            FieldType::Bytes => &d[1..][..d[0] as usize]
+            FieldType::Bytes => &d[1..][..d[0] as usize],
+            FieldType::Null => &d[..0]
         };
         (*field_type, bytes)
     }
@@ -243,6 +269,33 @@ impl EntryFields {
         EntryFields(0..fields_data.len(), fields_data.into())
     }
 
+    /// Checks if the bytes encode a valid list of fields - column count, column types, then the column data
+    pub(crate) fn is_well_formed(data: &[u8]) -> bool {
+        let Some(&count) = data.first() else {
+            return false;
+        };
+        let count = count as usize;
+        if data.len() < 1 + count {
+            return false;
+        }
+        let mut pointer = 1 + count;
+        for &field_type in &data[1..=count] {
+            pointer += match field_type {
+                t if t == FieldType::Bool as u8 => 1,
+                t if t == FieldType::I32 as u8 => 4,
+                t if t == FieldType::I64 as u8 || t == FieldType::Decimal as u8 => 8,
+                t if t == FieldType::Id as u8 => 16,
+                t if t == FieldType::String as u8 || t == FieldType::Bytes as u8 => match data.get(pointer) {
+                    Some(length) => 1 + *length as usize,
+                    None => return false
+                },
+                t if t == FieldType::Null as u8 => 0,
+                _ => return false
+            };
+        }
+        pointer == data.len()
+    }
+
     pub(crate) fn byte_size(&self) -> usize {
         self.0.len() + std::mem::size_of::<Self>()
     }
@@ -263,6 +316,7 @@ impl Display for EntryFields {
                 ObjectField::Id(uuid) => write!(f, "UUID: {}", uuid),
                 ObjectField::Bytes(bytes) => write!(f, "Bytes: {:02X?}{}", &bytes[..24], if bytes.len() > 24 { "..." } else { "" }),
                 ObjectField::String(s) => write!(f, "String: {}", s),
+                ObjectField::Null => write!(f, "Null"),
             }?;
         }
         write!(f, ">")?;
@@ -1,7 +1,7 @@
-use std::{ops::{ControlFlow, Range}, rc::Rc};
+use std::{ops::{ControlFlow, Range}, sync::Arc};
 use log_err::LogErrResult;
 use uuid::Uuid;
-use crate::{errors::storage_error::CompressionError, storage::log_file::entry_fields::EntryFields};
+use crate::{errors::storage_error::CompressionError, objects::FieldType, storage::log_file::entry_fields::EntryFields};
 
 #[derive(Clone)]
 pub enum LogEntry {
@@ -75,7 +75,7 @@ impl LogEntry {
         }
     }
 
-    pub fn decompress(rc: Rc<[u8]>, range: Range<usize>) -> Result<LogEntry, CompressionError> {
+    pub fn decompress(rc: Arc<[u8]>, range: Range<usize>) -> Result<LogEntry, CompressionError> {
         let data = &rc[range.start..];
         let transaction_id = Uuid::from_bytes(data[0..16].try_into().log_unwrap());
         let kind = data[16];
@@ -121,7 +121,7 @@ impl EntityEntry {
     /// <ul>
     /// <li>If the checked object is a tombstone, it automatically has a correct shape to every object, i.e. <code>Break(true)</code></li>
     /// <li>If the other shape is a tombstone, the function requests checking on another object, i.e. <code>Continue(())</code></li>
-    /// <li>If both objects have values, all the values between them have to have the same kind, i.e. <code>Break(bool)</code></li>
+    /// <li>If both objects have values, all the values between them have to have the same kind, or be null, i.e. <code>Break(bool)</code></li>
     /// </ul>
     pub fn is_same_shape(&self, other: &Self) -> ControlFlow<bool> {
         match (self, other) {
@@ -135,7 +135,10 @@ impl EntityEntry {
                 let other_values = &other_row.fields;
                 let self_types = self_values.column_types();
                 let other_types = other_values.column_types();
-                ControlFlow::Break(self_types.eq(other_types))
+                ControlFlow::Break(self_types.len() == other_types.len() && self_types
+                    .iter()
+                    .zip(other_types.iter())
+                    .all(|(a, b)| a == b || *a == FieldType::Null || *b == FieldType::Null))
             },
         }
     }
@@ -160,7 +163,7 @@ impl Row {
 
 #[cfg(test)]
 mod tests {
-    use std::{rc::Rc};
+    use std::{sync::Arc};
 
     use serial_test::parallel;
     use uuid::Uuid;
@@ -186,7 +189,7 @@ mod tests {
         data.extend("Hello, World!".as_bytes());
         data.extend([1]); // Bool 1 byte
 
-        let data: Rc<[u8]> = data.into_boxed_slice().into();
+        let data: Arc<[u8]> = data.into_boxed_slice().into();
 
         // testing deserialization
         let entry = LogEntry::decompress(data.clone(), 0..data.len()).unwrap();
@@ -9,7 +9,7 @@ use crate::errors::client_side_error::ClientSideError;
 use crate::errors::client_side_error::{CollectionAlreadyExistsError, CollectionDoesNotExistError};
 use crate::utils::DBResult;
 
-use self::{storage_config::DatabaseConfig};
+use self::storage_config::{DatabaseConfig, ExecutionConfig, SpillConfig};
 
 pub mod storage_config;
 pub mod log_file;
@@ -32,6 +32,14 @@ impl Storage {
         }
     }
 
+    pub fn spill_config(&self) -> SpillConfig {
+        self.config.spill()
+    }
+
+    pub fn execution_config(&self) -> ExecutionConfig {
+        self.config.execution.clone()
+    }
+
     pub fn get_collection(&self, name: &str) -> Option<&RwLock<Collection>> {
         self.collections.get(name)
     }
@@ -1,4 +1,4 @@
-use std::{path::{Path, PathBuf}, fs};
+use std::{fs, num::NonZeroUsize, path::{Path, PathBuf}, thread};
 use log::{debug, info};
 use serde::{Deserialize, Serialize};
 use serde_json::{from_str, to_string};
@@ -6,11 +6,16 @@ use serde_json::{from_str, to_string};
 use crate::utils::DBResult;
 
 const STORAGE_CONFIG: &str = "storage_config.json";
+const SPILL_DIRECTORY: &str = "tmp";
 
 #[derive(Serialize, Deserialize, Clone, Default)]
 pub struct DatabaseConfig {
     pub log_file: LogFileConfig,
     pub cache: CacheConfig,
+    #[serde(default)]
+    pub spill: SpillConfig,
+    #[serde(default)]
+    pub execution: ExecutionConfig,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -25,6 +30,22 @@ pub struct CacheConfig {
     pub file_count: usize,
 }
 
+/// Configuration of the operators which may spill their intermediate results to disk
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SpillConfig {
+    /// Directory of the spill files - when not set, the `tmp` directory under the log file destination of the storage is used
+    pub directory: Option<PathBuf>,
+    /// Approximate number of bytes an operator may hold in memory before spilling
+    pub memory_budget: usize
+}
+
+/// Configuration of the operators running on a pool of worker threads
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct ExecutionConfig {
+    /// Number of worker threads of a parallel operator - when not set, the available parallelism of the machine is used
+    pub threads: Option<usize>
+}
+
 impl Default for LogFileConfig {
     fn default() -> Self {
         Self {
@@ -43,6 +64,41 @@ impl Default for CacheConfig {
     }
 }
 
+impl Default for SpillConfig {
+    fn default() -> Self {
+        Self {
+            directory: None,
+            memory_budget: 64 * 1024 * 1024
+        }
+    }
+}
+
+impl SpillConfig {
+    /// Returns the spill configuration of the storage, or the default one for rows not read from a storage
+    pub(crate) fn of_storage(config: Option<&DatabaseConfig>) -> Self {
+        config.map_or_else(SpillConfig::default, DatabaseConfig::spill)
+    }
+
+    pub(crate) fn directory(&self) -> PathBuf {
+        self.directory
+            .clone()
+            .unwrap_or_else(|| LogFileConfig::default().destination.join(SPILL_DIRECTORY))
+    }
+}
+
+impl ExecutionConfig {
+    /// Returns the execution configuration of the storage, or the default one for rows not read from a storage
+    pub(crate) fn of_storage(config: Option<&DatabaseConfig>) -> Self {
+        config.map_or_else(ExecutionConfig::default, |config| config.execution.clone())
+    }
+
+    pub(crate) fn threads(&self) -> usize {
+        self.threads
+            .unwrap_or_else(|| thread::available_parallelism().map_or(1, NonZeroUsize::get))
+            .max(1)
+    }
+}
+
 impl DatabaseConfig {
     pub fn load() -> DBResult<Self> {
         Self::ensure()?;
@@ -59,6 +115,16 @@ impl DatabaseConfig {
         Ok(())
     }
 
+    /// Returns the spill configuration, with the directory resolved against the log file destination
+    pub fn spill(&self) -> SpillConfig {
+        SpillConfig {
+            directory: Some(self.spill.directory
+                .clone()
+                .unwrap_or_else(|| self.log_file.destination.join(SPILL_DIRECTORY))),
+            memory_budget: self.spill.memory_budget
+        }
+    }
+
     pub fn ensure() -> DBResult<()> {
         let config_path = Path::new(STORAGE_CONFIG);
         if !Path::is_file(config_path) {
@@ -0,0 +1,84 @@
+use std::hash::{Hash, Hasher};
+
+use gxhash::GxHasher;
+use serde::{Deserialize, Serialize};
+
+const PRECISION: u32 = 10;
+const REGISTER_COUNT: usize = 1 << PRECISION;
+
+/// HyperLogLog sketch estimating the number of distinct values added to it, with ~3% standard error.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HyperLogLog {
+    registers: Vec<u8>
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        HyperLogLog {
+            registers: vec![0; REGISTER_COUNT]
+        }
+    }
+}
+
+impl HyperLogLog {
+    pub fn add(&mut self, value: &impl Hash) {
+        let mut hasher = GxHasher::with_seed(0);
+        value.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        let register = (hash >> (64 - PRECISION)) as usize;
+        let rank = ((hash << PRECISION) | (1 << (PRECISION - 1))).leading_zeros() + 1;
+        self.registers[register] = self.registers[register].max(rank as u8);
+    }
+
+    pub fn merge(&mut self, other: &HyperLogLog) {
+        for (register, other_register) in self.registers.iter_mut().zip(other.registers.iter()) {
+            *register = (*register).max(*other_register);
+        }
+    }
+
+    pub fn estimate(&self) -> usize {
+        let count = REGISTER_COUNT as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / count);
+        let sum: f64 = self.registers.iter().map(|r| 2f64.powi(-i32::from(*r))).sum();
+        let estimate = alpha * count * count / sum;
+
+        let empty_registers = self.registers.iter().filter(|r| **r == 0).count();
+        if estimate <= 2.5 * count && empty_registers > 0 {
+            // Linear counting is more accurate for small cardinalities
+            (count * (count / empty_registers as f64).ln()).round() as usize
+        } else {
+            estimate.round() as usize
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serial_test::parallel;
+
+    use super::HyperLogLog;
+
+    #[test]
+    #[parallel]
+    fn test_estimate() {
+        let mut sketch = HyperLogLog::default();
+        assert_eq!(sketch.estimate(), 0);
+
+        for i in 0..100_000 {
+            sketch.add(&(i % 20_000));
+        }
+        let estimate = sketch.estimate() as f64;
+        assert!((estimate - 20_000.0).abs() / 20_000.0 < 0.1, "Estimate {estimate} too far off");
+
+        let mut small = HyperLogLog::default();
+        for i in 0..10 {
+            small.add(&i);
+        }
+        assert_eq!(small.estimate(), 10);
+
+        sketch.merge(&small);
+        let estimate = sketch.estimate() as f64;
+        assert!((estimate - 20_000.0).abs() / 20_000.0 < 0.1, "Estimate {estimate} too far off");
+    }
+}
@@ -8,6 +8,10 @@ pub use split_by_length_encoding::SplittableByLengthEncoding;
 mod set_macro;
 mod yokeable;
 pub use yokeable::*;
+mod hyper_log_log;
+pub use hyper_log_log::HyperLogLog;
+mod t_digest;
+pub use t_digest::TDigest;
 
 use crate::errors::DatabaseError;
 
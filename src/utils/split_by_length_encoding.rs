@@ -1,4 +1,4 @@
-use std::{iter::FusedIterator, ops::Range, rc::Rc};
+use std::{iter::FusedIterator, ops::Range, sync::Arc};
 
 use log_err::LogErrResult;
 
@@ -6,7 +6,7 @@ pub trait SplittableByLengthEncoding {
     fn split_by_length_encoding(self) -> SplitByLengthEncoding;
 }
 
-impl SplittableByLengthEncoding for Rc<[u8]> {
+impl SplittableByLengthEncoding for Arc<[u8]> {
     fn split_by_length_encoding(self) -> SplitByLengthEncoding
     {
         SplitByLengthEncoding::new(self)
@@ -15,12 +15,12 @@ impl SplittableByLengthEncoding for Rc<[u8]> {
 
 #[derive(Clone)]
 pub struct SplitByLengthEncoding {
-    slice: Rc<[u8]>,
+    slice: Arc<[u8]>,
     position: usize
 }
 
 impl SplitByLengthEncoding {
-    fn new(slice: Rc<[u8]>) -> Self {
+    fn new(slice: Arc<[u8]>) -> Self {
         SplitByLengthEncoding {
             slice,
             position: 0
@@ -30,7 +30,7 @@ impl SplitByLengthEncoding {
 
 impl Iterator for SplitByLengthEncoding
 {
-    type Item = (Rc<[u8]>, Range<usize>);
+    type Item = (Arc<[u8]>, Range<usize>);
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.slice.len() <= self.position {
@@ -66,7 +66,7 @@ impl FusedIterator for SplitByLengthEncoding {}
 
 #[cfg(test)]
 mod tests {
-    use std::rc::Rc;
+    use std::sync::Arc;
 
     use itertools::Itertools;
     use serial_test::parallel;
@@ -81,7 +81,7 @@ mod tests {
         data.extend(vint64::encode(8).as_ref());
         data.extend([8, 7, 6, 5, 4, 3, 2, 1]);
 
-        let data: Rc<[u8]> = data.into_boxed_slice().into();
+        let data: Arc<[u8]> = data.into_boxed_slice().into();
 
         let splitted = data
             .split_by_length_encoding()
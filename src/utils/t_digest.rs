@@ -0,0 +1,147 @@
+const COMPRESSION: f64 = 100.0;
+const BUFFER_SIZE: usize = 500;
+
+/// Merging t-digest sketch estimating quantiles of the values added to it.
+/// Centroids near the tails are kept small, so extreme quantiles stay accurate.
+#[derive(Clone, Debug, Default)]
+pub struct TDigest {
+    /// Merged centroids, as (mean, weight), sorted by the mean
+    centroids: Vec<(f64, f64)>,
+    /// Centroids not merged yet
+    buffer: Vec<(f64, f64)>,
+    min: f64,
+    max: f64
+}
+
+impl TDigest {
+    pub fn add(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        if self.is_empty() {
+            self.min = value;
+            self.max = value;
+        } else {
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+        }
+        self.buffer.push((value, 1.0));
+        if self.buffer.len() >= BUFFER_SIZE {
+            self.compress();
+        }
+    }
+
+    pub fn merge(&mut self, other: &TDigest) {
+        if other.is_empty() {
+            return;
+        }
+        if self.is_empty() {
+            self.min = other.min;
+            self.max = other.max;
+        } else {
+            self.min = self.min.min(other.min);
+            self.max = self.max.max(other.max);
+        }
+        self.buffer.extend(other.centroids.iter().chain(other.buffer.iter()));
+        self.compress();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.centroids.is_empty() && self.buffer.is_empty()
+    }
+
+    /// Number of centroids held by the digest
+    pub fn len(&self) -> usize {
+        self.centroids.len() + self.buffer.len()
+    }
+
+    /// Estimates the value at the given quantile, from the range `0.0..=1.0`
+    pub fn quantile(&mut self, quantile: f64) -> Option<f64> {
+        self.compress();
+        let (first, last) = (self.centroids.first()?, self.centroids.last()?);
+        let total: f64 = self.centroids.iter().map(|(_, weight)| weight).sum();
+        let target = quantile.clamp(0.0, 1.0) * total;
+
+        let first_center = first.1 / 2.0;
+        if target < first_center {
+            return Some(self.min + (first.0 - self.min) * target / first_center);
+        }
+        let mut cumulative = 0.0;
+        for window in self.centroids.windows(2) {
+            let (previous, next) = (window[0], window[1]);
+            let previous_center = cumulative + previous.1 / 2.0;
+            let next_center = cumulative + previous.1 + next.1 / 2.0;
+            if target < next_center {
+                return Some(previous.0 + (next.0 - previous.0) * (target - previous_center) / (next_center - previous_center));
+            }
+            cumulative += previous.1;
+        }
+        let last_center = total - last.1 / 2.0;
+        if target <= last_center {
+            Some(last.0)
+        } else {
+            Some(last.0 + (self.max - last.0) * (target - last_center) / (total - last_center))
+        }
+    }
+
+    fn compress(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+        let mut all = std::mem::take(&mut self.centroids);
+        all.append(&mut self.buffer);
+        all.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+        let total: f64 = all.iter().map(|(_, weight)| weight).sum();
+
+        let mut merged = Vec::with_capacity(all.len().min(COMPRESSION as usize * 2));
+        let mut all = all.into_iter();
+        let Some(mut current) = all.next() else {
+            return;
+        };
+        let mut weight_so_far = 0.0;
+        for next in all {
+            let proposed_weight = current.1 + next.1;
+            let quantile = (weight_so_far + proposed_weight / 2.0) / total;
+            let limit = 4.0 * total * quantile * (1.0 - quantile) / COMPRESSION;
+            if proposed_weight <= limit {
+                current.0 += (next.0 - current.0) * next.1 / proposed_weight;
+                current.1 = proposed_weight;
+            } else {
+                weight_so_far += current.1;
+                merged.push(current);
+                current = next;
+            }
+        }
+        merged.push(current);
+        self.centroids = merged;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serial_test::parallel;
+
+    use super::TDigest;
+
+    #[test]
+    #[parallel]
+    fn test_quantiles() {
+        let mut digest = TDigest::default();
+        for value in 1..=100 {
+            digest.add(f64::from(value));
+        }
+        assert_eq!(digest.quantile(0.5), Some(50.5));
+
+        let mut other = TDigest::default();
+        for value in (1..=100_000).rev() {
+            other.add(f64::from(value));
+        }
+        digest.merge(&other);
+        assert!(digest.len() < 1000);
+        assert!((digest.quantile(0.5).unwrap() - 50_000.0).abs() < 500.0);
+        assert!((digest.quantile(0.99).unwrap() - 99_000.0).abs() < 200.0);
+        assert_eq!(digest.quantile(0.0), Some(1.0));
+        assert_eq!(digest.quantile(1.0), Some(100_000.0));
+        assert_eq!(TDigest::default().quantile(0.5), None);
+    }
+}